@@ -0,0 +1,123 @@
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+use anchor_spl::associated_token::spl_associated_token_account::instruction::create_associated_token_account_idempotent;
+use anchor_spl::token::spl_token::native_mint;
+use anyhow::{anyhow, Context, Result};
+use clmm_sdk::instructions::{PoolKeys, PositionKeys};
+use clmm_sdk::{decode_pool, decode_position, decode_position_bundle, decode_tick, find_position_address,
+    find_position_bundle_address, find_tick_address, Pool, Position, PositionBundle, Quoter, Tick};
+use solana_sdk::compute_budget::ComputeBudgetInstruction;
+use solana_sdk::signature::{Keypair, Signature, Signer};
+use solana_sdk::transaction::Transaction;
+
+use crate::rpc::RpcClient;
+
+// Room for swaps crossing several ticks and positions creating their tick accounts
+const COMPUTE_UNIT_LIMIT: u32 = 400_000;
+
+pub struct Client {
+    pub rpc: RpcClient,
+    pub payer: Keypair,
+}
+
+impl Client {
+    pub fn fetch_pool(&self, address: &Pubkey) -> Result<(Pool, PoolKeys)> {
+        let account = self.rpc.get_account(address)?.with_context(|| format!("pool {address} not found"))?;
+        let pool = decode_pool(&account.data).map_err(|error| anyhow!("{address} is not a pool: {error}"))?;
+        let token_program_a = self.fetch_token_program(&pool.token_a_mint)?;
+        let token_program_b = self.fetch_token_program(&pool.token_b_mint)?;
+        let pool_keys = PoolKeys::from_pool(*address, &pool, token_program_a, token_program_b);
+        Ok((pool, pool_keys))
+    }
+
+    // Mints are owned by the token program their accounts live under
+    pub fn fetch_token_program(&self, mint: &Pubkey) -> Result<Pubkey> {
+        let account = self.rpc.get_account(mint)?.with_context(|| format!("mint {mint} not found"))?;
+        Ok(account.owner)
+    }
+
+    // Initialized ticks of the pool, in ascending order
+    pub fn fetch_ticks(&self, pool_address: &Pubkey, pool: &Pool) -> Result<Vec<(Pubkey, Tick)>> {
+        let addresses = Quoter::tick_addresses(pool_address, pool);
+        let accounts = self.rpc.get_multiple_accounts(&addresses)?;
+        let mut ticks = Vec::with_capacity(addresses.len());
+        for (address, account) in addresses.into_iter().zip(accounts) {
+            let account = account.with_context(|| format!("tick account {address} not found"))?;
+            let tick = decode_tick(&account.data).map_err(|error| anyhow!("{address} is not a tick: {error}"))?;
+            ticks.push((address, tick));
+        }
+        ticks.sort_by_key(|(_, tick)| tick.tick_index);
+        Ok(ticks)
+    }
+
+    // None while no position uses the tick
+    pub fn fetch_tick(&self, pool_address: &Pubkey, tick_index: i32) -> Result<Option<Tick>> {
+        let address = find_tick_address(pool_address, tick_index).0;
+        self.rpc
+            .get_account(&address)?
+            .map(|account| decode_tick(&account.data).map_err(|error| anyhow!("{address} is not a tick: {error}")))
+            .transpose()
+    }
+
+    pub fn fetch_position_bundle(&self, pool_address: &Pubkey, owner: &Pubkey) -> Result<Option<PositionBundle>> {
+        let address = find_position_bundle_address(pool_address, owner).0;
+        self.rpc
+            .get_account(&address)?
+            .map(|account| decode_position_bundle(&account.data).map_err(|error| anyhow!("{address}: {error}")))
+            .transpose()
+    }
+
+    pub fn fetch_position(&self, pool_address: &Pubkey, owner: &Pubkey, bundle_index: u16) -> Result<(Position, PositionKeys)> {
+        let position_bundle = find_position_bundle_address(pool_address, owner).0;
+        let address = find_position_address(&position_bundle, bundle_index).0;
+        let account = self
+            .rpc
+            .get_account(&address)?
+            .with_context(|| format!("no position in slot {bundle_index} of {owner}"))?;
+        let position = decode_position(&account.data).map_err(|error| anyhow!("{address}: {error}"))?;
+        let position_keys = PositionKeys::from_position(pool_address, &position);
+        Ok((position, position_keys))
+    }
+
+    // First slot whose position account was never created, closed positions keep theirs
+    pub fn find_free_slot(&self, pool_address: &Pubkey, owner: &Pubkey) -> Result<u16> {
+        let position_bundle = find_position_bundle_address(pool_address, owner).0;
+        let addresses: Vec<Pubkey> = (0..PositionBundle::MAX_POSITIONS)
+            .map(|index| find_position_address(&position_bundle, index).0)
+            .collect();
+        let accounts = self.rpc.get_multiple_accounts(&addresses)?;
+        accounts
+            .iter()
+            .position(Option::is_none)
+            .map(|index| index as u16)
+            .context("every slot of the position bundle has been used")
+    }
+
+    // Associated token account of the payer, None for native SOL which the program wraps itself
+    pub fn token_account(&self, mint: &Pubkey, token_program: &Pubkey) -> Option<Pubkey> {
+        (*mint != native_mint::ID).then(|| self.associated_token_account(mint, token_program))
+    }
+
+    pub fn associated_token_account(&self, mint: &Pubkey, token_program: &Pubkey) -> Pubkey {
+        get_associated_token_address_with_program_id(&self.payer.pubkey(), mint, token_program)
+    }
+
+    pub fn create_token_account(&self, mint: &Pubkey, token_program: &Pubkey) -> Instruction {
+        create_associated_token_account_idempotent(&self.payer.pubkey(), &self.payer.pubkey(), mint, token_program)
+    }
+
+    pub fn send(&self, instructions: &[Instruction], extra_signers: &[&Keypair]) -> Result<Signature> {
+        let mut all_instructions = vec![ComputeBudgetInstruction::set_compute_unit_limit(COMPUTE_UNIT_LIMIT)];
+        all_instructions.extend_from_slice(instructions);
+
+        let mut signers: Vec<&Keypair> = vec![&self.payer];
+        signers.extend_from_slice(extra_signers);
+
+        let blockhash = self.rpc.get_latest_blockhash()?;
+        let transaction =
+            Transaction::new_signed_with_payer(&all_instructions, Some(&self.payer.pubkey()), &signers, blockhash);
+        self.rpc.send_and_confirm(&transaction)
+    }
+}
+
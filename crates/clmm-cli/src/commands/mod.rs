@@ -0,0 +1,3 @@
+pub mod pool;
+pub mod position;
+pub mod swap;
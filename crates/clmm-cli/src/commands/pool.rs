@@ -0,0 +1,63 @@
+use anchor_lang::prelude::Pubkey;
+use anyhow::{bail, Context, Result};
+use clmm_sdk::instructions::{self, PoolKeys};
+use clmm_sdk::{find_pool_address, PROGRAM_ID};
+use serde_json::{json, Value};
+use solana_sdk::signature::{Keypair, Signer};
+
+use crate::client::Client;
+use crate::json::{pool_json, price_to_sqrt_price, tick_json};
+
+// Vaults are fresh token accounts owned by the pool, their keypairs only sign the creation
+pub fn create_pool(
+    client: &Client,
+    mint_a: Pubkey,
+    mint_b: Pubkey,
+    price: f64,
+    tick_spacing: u16,
+    fee: u8,
+) -> Result<Value> {
+    let sqrt_price = price_to_sqrt_price(price).with_context(|| format!("invalid price {price}"))?;
+    if tick_spacing == 0 {
+        bail!("tick spacing must be positive");
+    }
+
+    let vault_a = Keypair::new();
+    let vault_b = Keypair::new();
+    let pool_keys = PoolKeys {
+        pool: find_pool_address(&mint_a, &mint_b).0,
+        token_mint_a: mint_a,
+        token_mint_b: mint_b,
+        token_vault_a: vault_a.pubkey(),
+        token_vault_b: vault_b.pubkey(),
+        token_program_a: client.fetch_token_program(&mint_a)?,
+        token_program_b: client.fetch_token_program(&mint_b)?,
+    };
+
+    let instruction = instructions::initialize_pool(client.payer.pubkey(), &pool_keys, sqrt_price, tick_spacing, fee, 0, 0);
+    let signature = client.send(&[instruction], &[&vault_a, &vault_b])?;
+
+    Ok(json!({
+        "signature": signature.to_string(),
+        "pool": pool_keys.pool.to_string(),
+        "observation": pool_keys.observation().to_string(),
+        "token_vault_a": pool_keys.token_vault_a.to_string(),
+        "token_vault_b": pool_keys.token_vault_b.to_string(),
+        "sqrt_price": sqrt_price.to_string(),
+    }))
+}
+
+pub fn show_pool(client: &Client, pool_address: Pubkey) -> Result<Value> {
+    let (pool, pool_keys) = client.fetch_pool(&pool_address)?;
+    let mut value = pool_json(&pool_address, &pool);
+    value["token_program_a"] = json!(pool_keys.token_program_a.to_string());
+    value["token_program_b"] = json!(pool_keys.token_program_b.to_string());
+    value["program_id"] = json!(PROGRAM_ID.to_string());
+    Ok(value)
+}
+
+pub fn show_ticks(client: &Client, pool_address: Pubkey) -> Result<Value> {
+    let (pool, _) = client.fetch_pool(&pool_address)?;
+    let ticks = client.fetch_ticks(&pool_address, &pool)?;
+    Ok(Value::Array(ticks.iter().map(|(address, tick)| tick_json(address, tick)).collect()))
+}
@@ -0,0 +1,191 @@
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use anyhow::{bail, Result};
+use clmm_sdk::instructions::{self, PoolKeys, PositionKeys, RangeOrderKeys};
+use clmm_sdk::Pool;
+use serde_json::{json, Value};
+use solana_sdk::signature::Signer;
+
+use crate::client::Client;
+use crate::json::position_json;
+
+pub fn open_position(
+    client: &Client,
+    pool_address: Pubkey,
+    tick_lower: i32,
+    tick_upper: i32,
+    liquidity: u128,
+    range_order: bool,
+) -> Result<Value> {
+    if tick_lower >= tick_upper {
+        bail!("tick_lower must be below tick_upper");
+    }
+    let owner = client.payer.pubkey();
+    let (pool, pool_keys) = client.fetch_pool(&pool_address)?;
+
+    // The bundle is created along with the first position of the owner in the pool
+    let mut instructions = Vec::new();
+    if client.fetch_position_bundle(&pool_address, &owner)?.is_none() {
+        instructions.push(instructions::initialize_position_bundle(pool_address, owner));
+    }
+    let bundle_index = client.find_free_slot(&pool_address, &owner)?;
+    let position_keys = PositionKeys::new(&pool_address, owner, bundle_index, tick_lower, tick_upper);
+    let range_order = if range_order { Some(range_order_keys(client, &pool, &pool_keys, &position_keys)?) } else { None };
+    instructions.push(provide_instruction(client, &pool_keys, &position_keys, liquidity, range_order));
+
+    let signature = client.send(&instructions, &[])?;
+    Ok(json!({
+        "signature": signature.to_string(),
+        "position": position_keys.position.to_string(),
+        "bundle_index": bundle_index,
+        "liquidity": liquidity.to_string(),
+    }))
+}
+
+// Positions cannot change size in place: fees are collected, the position is closed and the new
+// liquidity goes into a fresh slot over the same range, all in one transaction
+pub fn resize_position(client: &Client, pool_address: Pubkey, bundle_index: u16, new_liquidity: impl FnOnce(u128) -> Result<u128>) -> Result<Value> {
+    let owner = client.payer.pubkey();
+    let (pool, pool_keys) = client.fetch_pool(&pool_address)?;
+    let (position, position_keys) = client.fetch_position(&pool_address, &owner, bundle_index)?;
+    if position.range_order {
+        bail!("range orders cannot be resized, close the order and place a new one");
+    }
+    let liquidity = new_liquidity(position.liquidity)?;
+    if liquidity == 0 {
+        bail!("the position would be empty, use close-position instead");
+    }
+
+    let new_index = client.find_free_slot(&pool_address, &owner)?;
+    let new_position_keys = PositionKeys::new(&pool_address, owner, new_index, position.tick_lower, position.tick_upper);
+    let mut instructions = withdraw_instructions(client, &pool, &pool_keys, &position_keys)?;
+    instructions.push(provide_instruction(client, &pool_keys, &new_position_keys, liquidity, None));
+
+    let signature = client.send(&instructions, &[])?;
+    Ok(json!({
+        "signature": signature.to_string(),
+        "closed_bundle_index": bundle_index,
+        "position": new_position_keys.position.to_string(),
+        "bundle_index": new_index,
+        "liquidity": liquidity.to_string(),
+    }))
+}
+
+pub fn close_position(client: &Client, pool_address: Pubkey, bundle_index: u16) -> Result<Value> {
+    let owner = client.payer.pubkey();
+    let (pool, pool_keys) = client.fetch_pool(&pool_address)?;
+    let (position, position_keys) = client.fetch_position(&pool_address, &owner, bundle_index)?;
+
+    let signature = client.send(&withdraw_instructions(client, &pool, &pool_keys, &position_keys)?, &[])?;
+    Ok(json!({
+        "signature": signature.to_string(),
+        "position": position_keys.position.to_string(),
+        "liquidity": position.liquidity.to_string(),
+    }))
+}
+
+pub fn collect_fees(client: &Client, pool_address: Pubkey, bundle_index: u16) -> Result<Value> {
+    let owner = client.payer.pubkey();
+    let (_, pool_keys) = client.fetch_pool(&pool_address)?;
+    let (position, position_keys) = client.fetch_position(&pool_address, &owner, bundle_index)?;
+
+    let mut instructions = create_token_accounts(client, &pool_keys);
+    instructions.push(collect_instruction(client, &pool_keys, &position_keys));
+
+    let signature = client.send(&instructions, &[])?;
+    Ok(json!({
+        "signature": signature.to_string(),
+        "position": position_keys.position.to_string(),
+        "token_a_earned": position.token_a_earned,
+        "token_b_earned": position.token_b_earned,
+    }))
+}
+
+pub fn show_position(client: &Client, pool_address: Pubkey, owner: Pubkey, bundle_index: u16) -> Result<Value> {
+    let (position, position_keys) = client.fetch_position(&pool_address, &owner, bundle_index)?;
+    Ok(position_json(&position_keys.position, &position))
+}
+
+// A range order joins the fill record of the fill count its fill tick is at, a tick no position uses has not filled
+fn range_order_keys(client: &Client, pool: &Pool, pool_keys: &PoolKeys, position_keys: &PositionKeys) -> Result<RangeOrderKeys> {
+    let fills = if pool.tick_current < position_keys.tick_lower {
+        client.fetch_tick(&pool_keys.pool, position_keys.tick_upper)?.map(|tick| tick.range_order_fills_up)
+    } else {
+        client.fetch_tick(&pool_keys.pool, position_keys.tick_lower)?.map(|tick| tick.range_order_fills_down)
+    };
+    Ok(RangeOrderKeys::new(&pool_keys.pool, position_keys, pool.tick_current, fills.unwrap_or(0)))
+}
+
+fn provide_instruction(
+    client: &Client,
+    pool_keys: &PoolKeys,
+    position_keys: &PositionKeys,
+    liquidity: u128,
+    range_order: Option<RangeOrderKeys>,
+) -> Instruction {
+    instructions::provide_liquidity(
+        pool_keys,
+        position_keys,
+        client.token_account(&pool_keys.token_mint_a, &pool_keys.token_program_a),
+        client.token_account(&pool_keys.token_mint_b, &pool_keys.token_program_b),
+        liquidity,
+        range_order,
+        &[],
+    )
+}
+
+// Fees first so they land in token accounts even on a native SOL side, remove_liquidity pays what is left.
+// Rewards have to be collected before the position closes, the reward vaults let it close when one is empty
+fn withdraw_instructions(
+    client: &Client,
+    pool: &Pool,
+    pool_keys: &PoolKeys,
+    position_keys: &PositionKeys,
+) -> Result<Vec<Instruction>> {
+    let mut instructions = create_token_accounts(client, pool_keys);
+    instructions.push(collect_instruction(client, pool_keys, position_keys));
+    for (reward_index, reward_info) in pool.reward_infos.iter().enumerate() {
+        if !reward_info.initialized() {
+            continue;
+        }
+        let token_program = client.fetch_token_program(&reward_info.mint)?;
+        instructions.push(client.create_token_account(&reward_info.mint, &token_program));
+        instructions.push(instructions::collect_reward(
+            pool_keys.pool,
+            position_keys,
+            client.payer.pubkey(),
+            reward_info.mint,
+            client.associated_token_account(&reward_info.mint, &token_program),
+            token_program,
+            reward_index as u8,
+            &[],
+        ));
+    }
+    instructions.push(instructions::remove_liquidity(
+        pool_keys,
+        position_keys,
+        client.token_account(&pool_keys.token_mint_a, &pool_keys.token_program_a),
+        client.token_account(&pool_keys.token_mint_b, &pool_keys.token_program_b),
+        &instructions::reward_vault_accounts(pool),
+    ));
+    Ok(instructions)
+}
+
+// Fees are paid into token accounts, native SOL included
+fn collect_instruction(client: &Client, pool_keys: &PoolKeys, position_keys: &PositionKeys) -> Instruction {
+    instructions::collect_fees(
+        pool_keys,
+        position_keys,
+        client.payer.pubkey(),
+        client.associated_token_account(&pool_keys.token_mint_a, &pool_keys.token_program_a),
+        client.associated_token_account(&pool_keys.token_mint_b, &pool_keys.token_program_b),
+        &[],
+    )
+}
+
+fn create_token_accounts(client: &Client, pool_keys: &PoolKeys) -> Vec<Instruction> {
+    vec![
+        client.create_token_account(&pool_keys.token_mint_a, &pool_keys.token_program_a),
+        client.create_token_account(&pool_keys.token_mint_b, &pool_keys.token_program_b),
+    ]
+}
@@ -0,0 +1,75 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anchor_lang::prelude::Pubkey;
+use anchor_spl::token::spl_token::native_mint;
+use anyhow::{anyhow, bail, Result};
+use clmm_sdk::instructions;
+use clmm_sdk::Quoter;
+use serde_json::{json, Value};
+use solana_sdk::signature::Signer;
+
+use crate::client::Client;
+
+// Sent as a swap on the pool, so the program enforces min_amount_out against what the user receives and handles
+// native SOL. The quote is still checked first to fail early without paying for the transaction.
+pub fn swap(
+    client: &Client,
+    pool_address: Pubkey,
+    amount_in: u64,
+    a_to_b: bool,
+    min_amount_out: u64,
+    quote_only: bool,
+) -> Result<Value> {
+    let (pool, pool_keys) = client.fetch_pool(&pool_address)?;
+    let ticks = client.fetch_ticks(&pool_address, &pool)?;
+    let quoter = Quoter::new(pool_address, pool, ticks.into_iter().map(|(_, tick)| tick));
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
+    let quote = quoter
+        .quote(amount_in, a_to_b, timestamp)
+        .map_err(|error| anyhow!("swap cannot be quoted: {error}"))?;
+
+    let mut value = json!({
+        "amount_in": quote.amount_in,
+        "amount_out": quote.amount_out,
+        "fee_amount": quote.fee_amount,
+        "fee_rate": quote.fee_rate,
+        "price_impact": quote.price_impact,
+        "sqrt_price_after": quote.sqrt_price.to_string(),
+        "tick_after": quote.tick_current,
+        "tick_accounts": quote.tick_accounts.iter().map(Pubkey::to_string).collect::<Vec<_>>(),
+    });
+    if quote_only {
+        return Ok(value);
+    }
+    if quote.amount_out < min_amount_out {
+        bail!("quoted output {} is below the minimum {min_amount_out}", quote.amount_out);
+    }
+
+    // Both token accounts are created since the output may be a token the user never held. The native SOL side has
+    // none, the program wraps and unwraps it through a temporary wSOL account
+    let user_token_a = client.token_account(&pool_keys.token_mint_a, &pool_keys.token_program_a);
+    let user_token_b = client.token_account(&pool_keys.token_mint_b, &pool_keys.token_program_b);
+    let mut instructions = Vec::new();
+    for (mint, token_program) in [
+        (pool_keys.token_mint_a, pool_keys.token_program_a),
+        (pool_keys.token_mint_b, pool_keys.token_program_b),
+    ] {
+        if mint != native_mint::ID {
+            instructions.push(client.create_token_account(&mint, &token_program));
+        }
+    }
+    instructions.push(instructions::swap(
+        &pool_keys,
+        client.payer.pubkey(),
+        user_token_a,
+        user_token_b,
+        amount_in,
+        min_amount_out,
+        a_to_b,
+        &quote.tick_account_metas(),
+    ));
+
+    let signature = client.send(&instructions, &[])?;
+    value["signature"] = json!(signature.to_string());
+    Ok(value)
+}
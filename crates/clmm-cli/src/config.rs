@@ -0,0 +1,113 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use solana_sdk::signature::{read_keypair_file, Keypair};
+
+pub const DEFAULT_RPC_URL: &str = "http://127.0.0.1:8899";
+pub const DEFAULT_COMMITMENT: &str = "confirmed";
+
+// Fields read from a Solana CLI config file, so `solana config set` also configures this tool
+#[derive(Debug, Default, Deserialize)]
+struct ConfigFile {
+    json_rpc_url: Option<String>,
+    keypair_path: Option<String>,
+    commitment: Option<String>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Config {
+    pub rpc_url: String,
+    pub keypair_path: PathBuf,
+    pub commitment: String,
+}
+
+impl Config {
+    // Command line values win over the config file, which wins over the local validator defaults.
+    // Without an explicit path the Solana CLI config is used when it exists.
+    pub fn load(path: Option<&Path>, rpc_url: Option<String>, keypair_path: Option<PathBuf>) -> Result<Self> {
+        let file = match path {
+            Some(path) => read_config_file(path)?,
+            None => match default_config_path() {
+                Some(path) if path.exists() => read_config_file(&path)?,
+                _ => ConfigFile::default(),
+            },
+        };
+
+        Ok(Self {
+            rpc_url: rpc_url.or(file.json_rpc_url).unwrap_or_else(|| DEFAULT_RPC_URL.to_string()),
+            keypair_path: keypair_path
+                .or_else(|| file.keypair_path.map(|path| expand_home(&path)))
+                .or_else(default_keypair_path)
+                .context("no keypair configured and no home directory for the default one")?,
+            commitment: file.commitment.unwrap_or_else(|| DEFAULT_COMMITMENT.to_string()),
+        })
+    }
+
+    pub fn read_keypair(&self) -> Result<Keypair> {
+        read_keypair_file(&self.keypair_path)
+            .map_err(|error| anyhow::anyhow!("cannot read keypair {}: {error}", self.keypair_path.display()))
+    }
+}
+
+fn read_config_file(path: &Path) -> Result<ConfigFile> {
+    let contents = std::fs::read_to_string(path).with_context(|| format!("cannot read config {}", path.display()))?;
+    parse_config_file(&contents).with_context(|| format!("invalid config {}", path.display()))
+}
+
+fn parse_config_file(contents: &str) -> Result<ConfigFile> {
+    Ok(serde_yaml::from_str(contents)?)
+}
+
+fn home_dir() -> Option<PathBuf> {
+    std::env::var_os("HOME").map(PathBuf::from)
+}
+
+fn default_config_path() -> Option<PathBuf> {
+    home_dir().map(|home| home.join(".config/solana/cli/config.yml"))
+}
+
+fn default_keypair_path() -> Option<PathBuf> {
+    home_dir().map(|home| home.join(".config/solana/id.json"))
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_solana_cli_config() {
+        let file = parse_config_file(
+            "---\njson_rpc_url: \"http://localhost:8899\"\nwebsocket_url: \"\"\nkeypair_path: /keys/id.json\naddress_labels:\n  \"11111111111111111111111111111111\": System Program\ncommitment: finalized\n",
+        )
+        .unwrap();
+
+        assert_eq!(file.json_rpc_url.as_deref(), Some("http://localhost:8899"));
+        assert_eq!(file.keypair_path.as_deref(), Some("/keys/id.json"));
+        assert_eq!(file.commitment.as_deref(), Some("finalized"));
+    }
+
+    #[test]
+    fn command_line_overrides_config_file() {
+        let path = std::env::temp_dir().join(format!("clmm-cli-config-{}.yml", std::process::id()));
+        std::fs::write(&path, "json_rpc_url: https://api.devnet.solana.com\nkeypair_path: /keys/id.json\n").unwrap();
+
+        let config = Config::load(Some(&path), None, None).unwrap();
+        assert_eq!(config.rpc_url, "https://api.devnet.solana.com");
+        assert_eq!(config.keypair_path, PathBuf::from("/keys/id.json"));
+        assert_eq!(config.commitment, DEFAULT_COMMITMENT);
+
+        let config = Config::load(Some(&path), Some(DEFAULT_RPC_URL.to_string()), Some("/other.json".into())).unwrap();
+        assert_eq!(config.rpc_url, DEFAULT_RPC_URL);
+        assert_eq!(config.keypair_path, PathBuf::from("/other.json"));
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
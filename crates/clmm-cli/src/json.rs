@@ -0,0 +1,160 @@
+use anchor_lang::prelude::Pubkey;
+use clmm_sdk::{DynamicFee, Pool, Position, RewardInfo, Tick};
+use serde_json::{json, Value};
+
+// Account state as JSON. u128 values are strings, JSON numbers lose precision past 2^53
+
+pub fn sqrt_price_to_price(sqrt_price_x64: u128) -> f64 {
+    let sqrt_price = sqrt_price_x64 as f64 / (1u128 << 64) as f64;
+    sqrt_price * sqrt_price
+}
+
+pub fn price_to_sqrt_price(price: f64) -> Option<u128> {
+    if !price.is_finite() || price <= 0.0 {
+        return None;
+    }
+    let sqrt_price_x64 = price.sqrt() * (1u128 << 64) as f64;
+    (sqrt_price_x64 < u128::MAX as f64).then_some(sqrt_price_x64 as u128)
+}
+
+pub fn pool_json(address: &Pubkey, pool: &Pool) -> Value {
+    json!({
+        "address": address.to_string(),
+        "pool_authority": pool.pool_authority.to_string(),
+        "token_a_mint": pool.token_a_mint.to_string(),
+        "token_b_mint": pool.token_b_mint.to_string(),
+        "token_a_vault": pool.token_a_vault.to_string(),
+        "token_b_vault": pool.token_b_vault.to_string(),
+        "sqrt_price": pool.sqrt_price.to_string(),
+        "price": sqrt_price_to_price(pool.sqrt_price),
+        "tick_current": pool.tick_current,
+        "liquidity": pool.liquidity.to_string(),
+        "tick_spacing": pool.tick_spacing,
+        "initialized_ticks": pool.initialized_ticks,
+        "fee": pool.fee,
+        "pending_fee": pool.pending_fee,
+        "pending_fee_activation": pool.pending_fee_activation,
+        "fee_growth_global_a": pool.fee_growth_global_a.to_string(),
+        "fee_growth_global_b": pool.fee_growth_global_b.to_string(),
+        "protocol_fee_a": pool.protocol_fee_a.to_string(),
+        "protocol_fee_b": pool.protocol_fee_b.to_string(),
+        "reward_last_updated_timestamp": pool.reward_last_updated_timestamp,
+        "reward_infos": pool.reward_infos.iter().filter(|reward| reward.initialized()).map(reward_info_json).collect::<Vec<_>>(),
+        "dynamic_fee": dynamic_fee_json(&pool.dynamic_fee),
+        "pending_dynamic_fee": (pool.pending_dynamic_fee_activation != 0).then(|| dynamic_fee_json(&pool.pending_dynamic_fee)),
+        "pending_dynamic_fee_activation": pool.pending_dynamic_fee_activation,
+        "pause_flags": pool.pause_flags,
+        "flash_loan_amount_a": pool.flash_loan_amount_a,
+        "flash_loan_amount_b": pool.flash_loan_amount_b,
+        "locked": pool.locked,
+    })
+}
+
+fn reward_info_json(reward: &RewardInfo) -> Value {
+    json!({
+        "mint": reward.mint.to_string(),
+        "vault": reward.vault.to_string(),
+        "emissions_per_second_x64": reward.emissions_per_second_x64.to_string(),
+        "growth_global_x64": reward.growth_global_x64.to_string(),
+    })
+}
+
+fn dynamic_fee_json(dynamic_fee: &DynamicFee) -> Value {
+    json!({
+        "enabled": dynamic_fee.enabled,
+        "filter_period": dynamic_fee.filter_period,
+        "decay_period": dynamic_fee.decay_period,
+        "reduction_factor": dynamic_fee.reduction_factor,
+        "variable_fee_control": dynamic_fee.variable_fee_control,
+        "max_volatility_accumulator": dynamic_fee.max_volatility_accumulator,
+        "max_fee": dynamic_fee.max_fee,
+        "volatility_accumulator": dynamic_fee.volatility_accumulator,
+        "volatility_reference": dynamic_fee.volatility_reference,
+        "tick_reference": dynamic_fee.tick_reference,
+        "last_update_timestamp": dynamic_fee.last_update_timestamp,
+    })
+}
+
+pub fn tick_json(address: &Pubkey, tick: &Tick) -> Value {
+    json!({
+        "address": address.to_string(),
+        "tick_index": tick.tick_index,
+        "liquidity_net": tick.liquidity_net.to_string(),
+        "fee_growth_outside_a": tick.fee_growth_outside_a.to_string(),
+        "fee_growth_outside_b": tick.fee_growth_outside_b.to_string(),
+        "initialized": tick.initialized,
+        "range_order_liquidity_up": tick.range_order_liquidity_up.to_string(),
+        "range_order_liquidity_down": tick.range_order_liquidity_down.to_string(),
+        "range_order_fills_up": tick.range_order_fills_up,
+        "range_order_fills_down": tick.range_order_fills_down,
+        "range_order_fee_growth_up_a": tick.range_order_fee_growth_up_a.to_string(),
+        "range_order_fee_growth_up_b": tick.range_order_fee_growth_up_b.to_string(),
+        "range_order_fee_growth_down_a": tick.range_order_fee_growth_down_a.to_string(),
+        "range_order_fee_growth_down_b": tick.range_order_fee_growth_down_b.to_string(),
+        "range_order_reward_growths_up": tick.range_order_reward_growths_up.iter().map(u128::to_string).collect::<Vec<_>>(),
+        "range_order_reward_growths_down": tick.range_order_reward_growths_down.iter().map(u128::to_string).collect::<Vec<_>>(),
+        "reward_growths_outside": tick.reward_growths_outside.iter().map(u128::to_string).collect::<Vec<_>>(),
+    })
+}
+
+pub fn position_json(address: &Pubkey, position: &Position) -> Value {
+    json!({
+        "address": address.to_string(),
+        "owner": position.owner.to_string(),
+        "bundle_index": position.bundle_index,
+        "tick_lower": position.tick_lower,
+        "tick_upper": position.tick_upper,
+        "liquidity": position.liquidity.to_string(),
+        "token_a_earned": position.token_a_earned,
+        "token_b_earned": position.token_b_earned,
+        "delegate": position.delegate.map(|delegate| delegate.to_string()),
+        "range_order": position.range_order,
+        "range_order_sells_a": position.range_order_sells_a,
+        "rewards_owed": position.reward_infos.iter().map(|reward| reward.amount_owed).collect::<Vec<_>>(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_prices() {
+        assert_eq!(price_to_sqrt_price(1.0), Some(1 << 64));
+        assert_eq!(price_to_sqrt_price(4.0), Some(2 << 64));
+        assert_eq!(sqrt_price_to_price(3 << 64), 9.0);
+        assert_eq!(price_to_sqrt_price(0.0), None);
+        assert_eq!(price_to_sqrt_price(f64::NAN), None);
+        assert_eq!(price_to_sqrt_price(1e80), None);
+    }
+
+    #[test]
+    fn writes_large_integers_as_strings() {
+        let tick = Tick {
+            tick_index: -60,
+            liquidity_net: -(1 << 100),
+            fee_growth_outside_a: u128::MAX,
+            fee_growth_outside_b: 0,
+            initialized: true,
+            bump: 255,
+            range_order_liquidity_up: 0,
+            range_order_liquidity_down: 7,
+            range_order_fills_up: 0,
+            range_order_fills_down: 1,
+            range_order_fee_growth_up_a: 0,
+            range_order_fee_growth_up_b: 0,
+            range_order_fee_growth_down_a: 0,
+            range_order_fee_growth_down_b: 0,
+            range_order_reward_growths_up: [0; 3],
+            range_order_reward_growths_down: [0; 3],
+            reward_growths_outside: [1, 2, 3],
+        };
+        let value = tick_json(&Pubkey::default(), &tick);
+
+        assert_eq!(value["tick_index"], -60);
+        assert_eq!(value["liquidity_net"], "-1267650600228229401496703205376");
+        assert_eq!(value["fee_growth_outside_a"], u128::MAX.to_string());
+        assert_eq!(value["range_order_liquidity_down"], "7");
+        assert_eq!(value["reward_growths_outside"], json!(["1", "2", "3"]));
+    }
+}
@@ -0,0 +1,223 @@
+// Operator tool for the CLMM DEX program. Every command prints its result as JSON on stdout.
+// The RPC URL and keypair come from the Solana CLI config unless given on the command line.
+
+mod client;
+mod commands;
+mod config;
+mod json;
+mod rpc;
+
+use std::path::PathBuf;
+
+use anchor_lang::prelude::Pubkey;
+use anyhow::{Context, Result};
+use clap::{Args, Parser, Subcommand};
+use solana_sdk::signature::{Keypair, Signer};
+
+use crate::client::Client;
+use crate::commands::{pool, position, swap};
+use crate::config::Config;
+use crate::rpc::RpcClient;
+
+#[derive(Parser)]
+#[command(name = "clmm-cli", version, about = "Administer CLMM DEX pools, manage liquidity and swap")]
+struct Cli {
+    /// Config file in the Solana CLI format, defaults to ~/.config/solana/cli/config.yml
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+    /// RPC URL, overrides the config file
+    #[arg(long, short = 'u', global = true)]
+    url: Option<String>,
+    /// Keypair file paying for and signing transactions, overrides the config file
+    #[arg(long, short = 'k', global = true)]
+    keypair: Option<PathBuf>,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Args)]
+struct PositionArgs {
+    #[arg(long)]
+    pool: Pubkey,
+    /// Slot of the position in the owner's position bundle
+    #[arg(long)]
+    index: u16,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Create a pool with its observation account and vaults, signed by the pool authority
+    CreatePool {
+        #[arg(long)]
+        mint_a: Pubkey,
+        #[arg(long)]
+        mint_b: Pubkey,
+        /// Initial price of A in B, in raw token units
+        #[arg(long)]
+        price: f64,
+        #[arg(long)]
+        tick_spacing: u16,
+        /// Swap fee in millionths
+        #[arg(long)]
+        fee: u8,
+    },
+    /// Open a position in the first free slot of the owner's bundle, creating the bundle if needed
+    OpenPosition {
+        #[arg(long)]
+        pool: Pubkey,
+        #[arg(long, allow_hyphen_values = true)]
+        tick_lower: i32,
+        #[arg(long, allow_hyphen_values = true)]
+        tick_upper: i32,
+        #[arg(long)]
+        liquidity: u128,
+        /// Place a single spacing range order that settles once the price crosses it
+        #[arg(long)]
+        range_order: bool,
+    },
+    /// Add liquidity to a position, which moves to a new slot
+    IncreasePosition {
+        #[command(flatten)]
+        position: PositionArgs,
+        #[arg(long)]
+        liquidity: u128,
+    },
+    /// Remove part of a position's liquidity, the rest moves to a new slot
+    DecreasePosition {
+        #[command(flatten)]
+        position: PositionArgs,
+        #[arg(long)]
+        liquidity: u128,
+    },
+    /// Collect fees and withdraw all liquidity of a position
+    ClosePosition {
+        #[command(flatten)]
+        position: PositionArgs,
+    },
+    /// Collect the fees earned by a position
+    CollectFees {
+        #[command(flatten)]
+        position: PositionArgs,
+    },
+    /// Swap an exact input amount on one pool, checking the quote against a minimum output first. A native SOL side
+    /// is paid from and into the wallet's lamports, the program wraps and unwraps it
+    Swap {
+        #[arg(long)]
+        pool: Pubkey,
+        #[arg(long)]
+        amount_in: u64,
+        /// Sell token A for token B, otherwise B for A
+        #[arg(long)]
+        a_to_b: bool,
+        #[arg(long, default_value_t = 0)]
+        min_amount_out: u64,
+        /// Print the quote without sending the transaction
+        #[arg(long)]
+        quote_only: bool,
+    },
+    /// Print the pool state
+    Pool {
+        #[arg(long)]
+        pool: Pubkey,
+    },
+    /// Print the initialized ticks of a pool
+    Ticks {
+        #[arg(long)]
+        pool: Pubkey,
+    },
+    /// Print a position, of the configured keypair unless an owner is given
+    Position {
+        #[command(flatten)]
+        position: PositionArgs,
+        #[arg(long)]
+        owner: Option<Pubkey>,
+    },
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let config = Config::load(cli.config.as_deref(), cli.url, cli.keypair)?;
+    // Reading state signs nothing, a throwaway key stands in when no keypair is readable
+    let payer = match &cli.command {
+        Command::Pool { .. } | Command::Ticks { .. } | Command::Position { owner: Some(_), .. } => {
+            config.read_keypair().unwrap_or_else(|_| Keypair::new())
+        }
+        _ => config.read_keypair()?,
+    };
+    let client = Client {
+        rpc: RpcClient::new(config.rpc_url.clone(), config.commitment.clone()),
+        payer,
+    };
+
+    let output = match cli.command {
+        Command::CreatePool { mint_a, mint_b, price, tick_spacing, fee } => {
+            pool::create_pool(&client, mint_a, mint_b, price, tick_spacing, fee)?
+        }
+        Command::OpenPosition { pool, tick_lower, tick_upper, liquidity, range_order } => {
+            position::open_position(&client, pool, tick_lower, tick_upper, liquidity, range_order)?
+        }
+        Command::IncreasePosition { position, liquidity } => {
+            position::resize_position(&client, position.pool, position.index, |current| {
+                current.checked_add(liquidity).context("liquidity overflows")
+            })?
+        }
+        Command::DecreasePosition { position, liquidity } => {
+            position::resize_position(&client, position.pool, position.index, |current| {
+                current.checked_sub(liquidity).context("the position holds less liquidity")
+            })?
+        }
+        Command::ClosePosition { position } => position::close_position(&client, position.pool, position.index)?,
+        Command::CollectFees { position } => position::collect_fees(&client, position.pool, position.index)?,
+        Command::Swap { pool, amount_in, a_to_b, min_amount_out, quote_only } => {
+            swap::swap(&client, pool, amount_in, a_to_b, min_amount_out, quote_only)?
+        }
+        Command::Pool { pool } => pool::show_pool(&client, pool)?,
+        Command::Ticks { pool } => pool::show_ticks(&client, pool)?,
+        Command::Position { position: args, owner } => {
+            let owner = owner.unwrap_or_else(|| client.payer.pubkey());
+            position::show_position(&client, args.pool, owner, args.index)?
+        }
+    };
+
+    println!("{}", serde_json::to_string_pretty(&output)?);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn command_line_is_consistent() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn parses_negative_ticks() {
+        let pool = Pubkey::new_unique();
+        let cli = Cli::parse_from([
+            "clmm-cli",
+            "--url",
+            "http://127.0.0.1:8899",
+            "open-position",
+            "--pool",
+            &pool.to_string(),
+            "--tick-lower",
+            "-120",
+            "--tick-upper",
+            "60",
+            "--liquidity",
+            "1000000",
+        ]);
+
+        assert_eq!(cli.url.as_deref(), Some("http://127.0.0.1:8899"));
+        match cli.command {
+            Command::OpenPosition { pool: parsed, tick_lower, tick_upper, liquidity, range_order } => {
+                assert_eq!(parsed, pool);
+                assert_eq!((tick_lower, tick_upper, liquidity, range_order), (-120, 60, 1_000_000, false));
+            }
+            _ => panic!("parsed the wrong command"),
+        }
+    }
+}
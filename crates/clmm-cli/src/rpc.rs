@@ -0,0 +1,163 @@
+use std::str::FromStr;
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use anchor_lang::prelude::Pubkey;
+use anyhow::{anyhow, bail, Context, Result};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde_json::{json, Value};
+use solana_sdk::hash::Hash;
+use solana_sdk::signature::Signature;
+use solana_sdk::transaction::Transaction;
+
+// getMultipleAccounts takes at most this many keys per request
+const MAX_MULTIPLE_ACCOUNTS: usize = 100;
+const CONFIRM_TIMEOUT: Duration = Duration::from_secs(60);
+const CONFIRM_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+pub struct Account {
+    pub owner: Pubkey,
+    pub data: Vec<u8>,
+}
+
+// The few JSON-RPC methods the tool needs, over plain HTTP
+pub struct RpcClient {
+    url: String,
+    commitment: String,
+}
+
+impl RpcClient {
+    pub fn new(url: String, commitment: String) -> Self {
+        Self { url, commitment }
+    }
+
+    fn request(&self, method: &str, params: Value) -> Result<Value> {
+        let body = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+        let response: Value = ureq::post(&self.url)
+            .send_json(body)
+            .with_context(|| format!("{method} request to {} failed", self.url))?
+            .into_json()?;
+
+        if let Some(error) = response.get("error") {
+            let message = error["message"].as_str().unwrap_or("unknown error");
+            // Preflight failures carry the program logs
+            let logs = error["data"]["logs"]
+                .as_array()
+                .map(|logs| logs.iter().filter_map(Value::as_str).collect::<Vec<_>>().join("\n"))
+                .unwrap_or_default();
+            bail!("{method}: {message}\n{logs}");
+        }
+        Ok(response["result"].clone())
+    }
+
+    pub fn get_account(&self, address: &Pubkey) -> Result<Option<Account>> {
+        let result = self.request(
+            "getAccountInfo",
+            json!([address.to_string(), { "encoding": "base64", "commitment": self.commitment }]),
+        )?;
+        parse_account(&result["value"])
+    }
+
+    // Missing accounts come back as None, in the order of the addresses
+    pub fn get_multiple_accounts(&self, addresses: &[Pubkey]) -> Result<Vec<Option<Account>>> {
+        let mut accounts = Vec::with_capacity(addresses.len());
+        for chunk in addresses.chunks(MAX_MULTIPLE_ACCOUNTS) {
+            let keys: Vec<String> = chunk.iter().map(Pubkey::to_string).collect();
+            let result = self.request(
+                "getMultipleAccounts",
+                json!([keys, { "encoding": "base64", "commitment": self.commitment }]),
+            )?;
+            let values = result["value"].as_array().context("getMultipleAccounts returned no list")?;
+            for value in values {
+                accounts.push(parse_account(value)?);
+            }
+        }
+        Ok(accounts)
+    }
+
+    pub fn get_latest_blockhash(&self) -> Result<Hash> {
+        let result = self.request("getLatestBlockhash", json!([{ "commitment": self.commitment }]))?;
+        let blockhash = result["value"]["blockhash"].as_str().context("getLatestBlockhash returned no blockhash")?;
+        Hash::from_str(blockhash).map_err(|error| anyhow!("invalid blockhash {blockhash}: {error}"))
+    }
+
+    // Sends with preflight and waits until the transaction reaches the configured commitment
+    pub fn send_and_confirm(&self, transaction: &Transaction) -> Result<Signature> {
+        let encoded = STANDARD.encode(bincode::serialize(transaction)?);
+        let result = self.request(
+            "sendTransaction",
+            json!([encoded, { "encoding": "base64", "preflightCommitment": self.commitment }]),
+        )?;
+        let signature = result.as_str().context("sendTransaction returned no signature")?;
+        let signature = Signature::from_str(signature)?;
+
+        let started = Instant::now();
+        while started.elapsed() < CONFIRM_TIMEOUT {
+            let result = self.request("getSignatureStatuses", json!([[signature.to_string()]]))?;
+            let status = &result["value"][0];
+            if !status.is_null() {
+                if !status["err"].is_null() {
+                    bail!("transaction {signature} failed: {}", status["err"]);
+                }
+                if reaches_commitment(status["confirmationStatus"].as_str().unwrap_or(""), &self.commitment) {
+                    return Ok(signature);
+                }
+            }
+            sleep(CONFIRM_POLL_INTERVAL);
+        }
+        bail!("transaction {signature} was not confirmed within {}s", CONFIRM_TIMEOUT.as_secs())
+    }
+}
+
+fn parse_account(value: &Value) -> Result<Option<Account>> {
+    if value.is_null() {
+        return Ok(None);
+    }
+    let data = value["data"][0].as_str().context("account data is not base64")?;
+    let owner = value["owner"].as_str().context("account has no owner")?;
+
+    Ok(Some(Account {
+        owner: Pubkey::from_str(owner)?,
+        data: STANDARD.decode(data)?,
+    }))
+}
+
+fn reaches_commitment(status: &str, commitment: &str) -> bool {
+    match commitment {
+        "finalized" => status == "finalized",
+        "processed" => !status.is_empty(),
+        _ => status == "confirmed" || status == "finalized",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_base64_accounts() {
+        let value = json!({
+            "data": ["AQID", "base64"],
+            "executable": false,
+            "lamports": 1_461_600,
+            "owner": "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA",
+            "rentEpoch": 18_446_744_073_709_551_615u64,
+            "space": 3
+        });
+        let account = parse_account(&value).unwrap().unwrap();
+
+        assert_eq!(account.data, vec![1, 2, 3]);
+        assert_eq!(account.owner, anchor_spl::token::ID);
+        assert!(parse_account(&Value::Null).unwrap().is_none());
+    }
+
+    #[test]
+    fn waits_for_the_configured_commitment() {
+        assert!(!reaches_commitment("processed", "confirmed"));
+        assert!(reaches_commitment("confirmed", "confirmed"));
+        assert!(reaches_commitment("finalized", "confirmed"));
+        assert!(!reaches_commitment("confirmed", "finalized"));
+        assert!(reaches_commitment("processed", "processed"));
+    }
+}
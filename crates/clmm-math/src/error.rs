@@ -0,0 +1,29 @@
+use core::fmt;
+
+pub type Result<T> = core::result::Result<T, MathError>;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MathError {
+    // A checked operation overflowed or underflowed
+    Overflow,
+    DivisionByZero,
+    InvalidTickRange,
+    InvalidFeeRate,
+    InsufficientLiquidity,
+    // No initialized tick in the swap direction
+    TickNotFound,
+}
+
+impl fmt::Display for MathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            MathError::Overflow => "Math overflow",
+            MathError::DivisionByZero => "Division by zero",
+            MathError::InvalidTickRange => "Invalid tick range",
+            MathError::InvalidFeeRate => "Invalid fee rate",
+            MathError::InsufficientLiquidity => "Insufficient liquidity",
+            MathError::TickNotFound => "Tick not found",
+        };
+        f.write_str(message)
+    }
+}
@@ -0,0 +1,15 @@
+#![no_std]
+
+// Arithmetic shared by the on-chain program and off-chain clients, prices are Q64.64 sqrt prices in u128
+
+pub mod error;
+pub mod liquidity_math;
+pub mod sqrt_price_math;
+pub mod swap_math;
+pub mod tick_math;
+
+pub use error::*;
+pub use liquidity_math::*;
+pub use sqrt_price_math::*;
+pub use swap_math::*;
+pub use tick_math::*;
@@ -0,0 +1,194 @@
+use crate::error::{MathError, Result};
+use crate::sqrt_price_math::{get_amount_a_delta, get_amount_b_delta, mul_div_floor};
+use crate::tick_math::get_sqrt_price_from_tick;
+
+// Applies a signed liquidity change, e.g. liquidity_net when crossing a tick
+pub fn add_liquidity_delta(liquidity: u128, liquidity_delta: i128) -> Result<u128> {
+    let new_liquidity = if liquidity_delta < 0 {
+        liquidity.checked_sub(liquidity_delta.unsigned_abs())
+    } else {
+        liquidity.checked_add(liquidity_delta as u128)
+    };
+
+    new_liquidity.ok_or(MathError::Overflow)
+}
+
+// Token amounts backing liquidity in [tick_lower, tick_upper] at sqrt_price_current. Deposits round up and
+// withdrawals round down, so a deposit followed by a withdrawal never takes more out of the vaults than it put in
+pub fn compute_amount(
+    liquidity: u128,
+    tick_upper: i32,
+    tick_lower: i32,
+    sqrt_price_current: u128,
+    round_up: bool,
+) -> Result<(u128, u128)> {
+    let sqrt_lower_price = get_sqrt_price_from_tick(tick_lower)?;
+    let sqrt_upper_price = get_sqrt_price_from_tick(tick_upper)?;
+
+    if sqrt_lower_price >= sqrt_upper_price {
+        return Err(MathError::InvalidTickRange);
+    }
+
+    // Case 1: Current price below lower → all Token A 
+    // a = l * [( sqrt_upper - sqrt_lower)/sqrt_upper * sqrt_lower]
+    if sqrt_price_current <= sqrt_lower_price {
+        let amount_a = get_amount_a_delta(sqrt_lower_price, sqrt_upper_price, liquidity, round_up)?;
+        return Ok((amount_a, 0));
+    }
+
+    // Case 2: Current price above upper → all Token B
+    // b = l * [sqrt_upper - sqrt_lower]
+    if sqrt_price_current >= sqrt_upper_price {
+        let amount_b = get_amount_b_delta(sqrt_lower_price, sqrt_upper_price, liquidity, round_up)?;
+        return Ok((0, amount_b));
+    }
+
+    // Case 3: Price is inside range → split A and B
+    // a = l * [( sqrt_upper - sqrt_current)/sqrt_upper * sqrt_current]
+    // b = l * [sqrt_current - sqrt_lower]
+    let amount_a = get_amount_a_delta(sqrt_price_current, sqrt_upper_price, liquidity, round_up)?;
+    let amount_b = get_amount_b_delta(sqrt_lower_price, sqrt_price_current, liquidity, round_up)?;
+
+    Ok((amount_a, amount_b))
+}
+
+// Calculates the maximum liquidity that amount A and B can provide at sqrt_price_current, rounded down
+pub fn get_liquidity_for_amounts(
+    amount_a: u128,
+    amount_b: u128,
+    tick_upper: i32,
+    tick_lower: i32,
+    sqrt_price_current: u128,
+) -> Result<u128> {
+    let sqrt_lower_price = get_sqrt_price_from_tick(tick_lower)?;
+    let sqrt_upper_price = get_sqrt_price_from_tick(tick_upper)?;
+
+    // Case 1: Current price below lower → only Token A counts
+    // l = a * sqrt_upper * sqrt_lower / (sqrt_upper - sqrt_lower)
+    if sqrt_price_current <= sqrt_lower_price {
+        return get_liquidity_for_amount_a(amount_a, sqrt_upper_price, sqrt_lower_price);
+    }
+
+    // Case 2: Current price above upper → only Token B counts
+    // l = b / (sqrt_upper - sqrt_lower)
+    if sqrt_price_current >= sqrt_upper_price {
+        return get_liquidity_for_amount_b(amount_b, sqrt_upper_price, sqrt_lower_price);
+    }
+
+    // Case 3: Price is inside range → the scarcer token limits liquidity
+    let liquidity_a = get_liquidity_for_amount_a(amount_a, sqrt_upper_price, sqrt_price_current)?;
+    let liquidity_b = get_liquidity_for_amount_b(amount_b, sqrt_price_current, sqrt_lower_price)?;
+
+    Ok(liquidity_a.min(liquidity_b))
+}
+
+// l = a * sqrt_upper * sqrt_lower / (sqrt_upper - sqrt_lower), the product of the two Q64.64 prices takes
+// 256 bits so it is divided by the difference first and brought back from Q64.64 last
+fn get_liquidity_for_amount_a(amount_a: u128, sqrt_upper_price: u128, sqrt_lower_price: u128) -> Result<u128> {
+    let price_diff = sqrt_upper_price
+        .checked_sub(sqrt_lower_price)
+        .ok_or(MathError::InvalidTickRange)?;
+
+    // Depositing rounds token A up in two steps, which can overshoot the exact amount by up to diff / sqrt_lower + 1,
+    // leave that much out so the deposit never takes more than amount_a
+    let amount_a = amount_a.saturating_sub(price_diff / sqrt_lower_price + 1);
+    let amount_over_diff = mul_div_floor(amount_a, sqrt_upper_price, price_diff)?;
+    mul_div_floor(amount_over_diff, sqrt_lower_price, 1 << 64)
+}
+
+// l = b / (sqrt_upper - sqrt_lower), the amount is brought to Q64.64 to match the price difference
+fn get_liquidity_for_amount_b(amount_b: u128, sqrt_upper_price: u128, sqrt_lower_price: u128) -> Result<u128> {
+    let price_diff = sqrt_upper_price
+        .checked_sub(sqrt_lower_price)
+        .ok_or(MathError::InvalidTickRange)?;
+
+    mul_div_floor(amount_b, 1 << 64, price_diff)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Liquidity from amounts, then the amounts depositing that liquidity takes, at the given tick
+    fn round_trip(amount_a: u128, amount_b: u128, tick_lower: i32, tick_upper: i32, tick_current: i32) -> (u128, u128, u128) {
+        let sqrt_price = get_sqrt_price_from_tick(tick_current).unwrap();
+        let liquidity = get_liquidity_for_amounts(amount_a, amount_b, tick_upper, tick_lower, sqrt_price).unwrap();
+        let (used_a, used_b) = compute_amount(liquidity, tick_upper, tick_lower, sqrt_price, true).unwrap();
+        (liquidity, used_a, used_b)
+    }
+
+    #[test]
+    fn liquidity_for_amounts_at_tick_zero() {
+        // At price 1 the range [-1000, 1000] is symmetric, the same amounts of both tokens back it
+        let amount = 1_000_000_000_000;
+        let (liquidity, used_a, used_b) = round_trip(amount, amount, -1_000, 1_000, 0);
+        assert!(liquidity > 0);
+        assert!(used_a <= amount && used_b <= amount);
+        assert!(amount - used_a.min(used_b) <= amount / 1_000_000, "{used_a} {used_b}");
+
+        // Liquidity L over [sqrt_lower, 1] holds L * (1 - sqrt_lower) of token B
+        let sqrt_lower = libm::pow(1.0001, -500.0);
+        let expected = amount as f64 / (1.0 - sqrt_lower);
+        let (liquidity, used_a, used_b) = round_trip(0, amount, -1_000, 0, 0);
+        assert!((liquidity as f64 / expected - 1.0).abs() < 1e-9, "{liquidity} {expected}");
+        assert_eq!(used_a, 0);
+        assert!(amount - used_b <= 1, "{used_b}");
+    }
+
+    #[test]
+    fn liquidity_for_amounts_at_positive_tick() {
+        // Token A only above the range, token B only below it
+        let amount = 5_000_000_000;
+        let (_, used_a, used_b) = round_trip(amount, amount, 10_000, 12_000, 9_000);
+        assert_eq!(used_b, 0);
+        assert!(amount - used_a <= 1, "{used_a}");
+        let (_, used_a, used_b) = round_trip(amount, amount, 10_000, 12_000, 13_000);
+        assert_eq!(used_a, 0);
+        assert!(amount - used_b <= 1, "{used_b}");
+
+        // In range the scarcer token limits the liquidity and is used up
+        let (liquidity, used_a, used_b) = round_trip(amount, amount, 10_000, 12_000, 11_000);
+        assert!(used_a <= amount && used_b <= amount);
+        assert!(amount - used_a.max(used_b) <= 1, "{used_a} {used_b}");
+        let (liquidity_a, _, _) = round_trip(amount, u128::MAX >> 64, 10_000, 12_000, 11_000);
+        let (liquidity_b, _, _) = round_trip(u128::MAX >> 64, amount, 10_000, 12_000, 11_000);
+        assert_eq!(liquidity, liquidity_a.min(liquidity_b));
+    }
+
+    #[test]
+    fn liquidity_from_computed_amounts() {
+        // The amounts backing a liquidity give that liquidity back, less the rounding of the amounts
+        let liquidity = 123_456_789_012_345;
+        for (tick_lower, tick_upper, tick_current) in [(-3_000, 2_000, 0), (-3_000, 2_000, -5_000), (-3_000, 2_000, 4_000), (20_000, 40_000, 33_333)] {
+            let sqrt_price = get_sqrt_price_from_tick(tick_current).unwrap();
+            let (amount_a, amount_b) = compute_amount(liquidity, tick_upper, tick_lower, sqrt_price, false).unwrap();
+            let recovered = get_liquidity_for_amounts(amount_a, amount_b, tick_upper, tick_lower, sqrt_price).unwrap();
+            assert!(recovered <= liquidity, "{recovered}");
+            assert!(liquidity - recovered <= liquidity / 1_000_000_000, "{recovered}");
+        }
+    }
+
+    #[test]
+    fn withdraw_never_exceeds_deposit() {
+        // Between tick prices too, where the split of the two tokens is not exact
+        let sqrt_prices = [
+            get_sqrt_price_from_tick(-3_000).unwrap(),
+            get_sqrt_price_from_tick(0).unwrap() - 1,
+            get_sqrt_price_from_tick(1_234).unwrap() + 987_654_321,
+            get_sqrt_price_from_tick(2_000).unwrap(),
+        ];
+        for liquidity in [1, 7, 999_999, 123_456_789_012_345] {
+            for sqrt_price in sqrt_prices {
+                let deposited = compute_amount(liquidity, 2_000, -3_000, sqrt_price, true).unwrap();
+                let withdrawn = compute_amount(liquidity, 2_000, -3_000, sqrt_price, false).unwrap();
+                assert!(withdrawn.0 <= deposited.0 && withdrawn.1 <= deposited.1, "{deposited:?} {withdrawn:?}");
+                assert!(deposited.0 - withdrawn.0 <= 2 && deposited.1 - withdrawn.1 <= 1, "{deposited:?} {withdrawn:?}");
+            }
+        }
+
+        // Dust liquidity still costs a unit of each token it needs, while withdrawing it returns nothing
+        let sqrt_price = get_sqrt_price_from_tick(0).unwrap();
+        assert_eq!(compute_amount(1, 2_000, -3_000, sqrt_price, true).unwrap(), (1, 1));
+        assert_eq!(compute_amount(1, 2_000, -3_000, sqrt_price, false).unwrap(), (0, 0));
+    }
+}
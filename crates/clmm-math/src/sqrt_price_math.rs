@@ -0,0 +1,228 @@
+use crate::error::{MathError, Result};
+
+// Price after adding amount_in of the input token
+pub fn get_new_sqrt_price_from_input(sqrt_price_current: u128, liquidity: u128, amount_in: u128, a_to_b: bool) -> Result<u128> {
+    if a_to_b {
+        // Swapping token A for token B, token A is added so the token A formula applies:
+        // sqrt_price_new = (liquidity * sqrt_price_current) / (liquidity + amount_in * sqrt_price_current)
+        // The amount term is brought back from Q64.64
+        let denominator_addition = mul_div_floor(amount_in, sqrt_price_current, 1 << 64)?;
+        let denominator = liquidity.checked_add(denominator_addition).ok_or(MathError::Overflow)?;
+        mul_div_floor(liquidity, sqrt_price_current, denominator)
+    } else {
+        // Swapping token B for token A, token B is added:
+        // sqrt_price_new = sqrt_price_current + (amount_in << 64) / liquidity
+        let quotient = mul_div_floor(amount_in, 1 << 64, liquidity)?;
+        sqrt_price_current.checked_add(quotient).ok_or(MathError::Overflow)
+    }
+}
+
+// Price after removing amount_out of the output token
+pub fn get_new_sqrt_price_from_output(sqrt_price_current: u128, liquidity: u128, amount_out: u128, a_to_b: bool) -> Result<u128> {
+    if a_to_b {
+        // Token B leaves the pool:
+        // sqrt_price_new = sqrt_price_current - (amount_out in Q64.64) / liquidity
+        let quotient = mul_div_floor(amount_out, 1 << 64, liquidity)?;
+        sqrt_price_current.checked_sub(quotient).ok_or(MathError::Overflow)
+    } else {
+        // Token A leaves the pool:
+        // sqrt_price_new = (liquidity * sqrt_price_current) / (liquidity - amount_out * sqrt_price_current)
+        let denominator_subtraction = mul_div_floor(amount_out, sqrt_price_current, 1 << 64)?;
+        if liquidity <= denominator_subtraction {
+            return Err(MathError::InsufficientLiquidity);
+        }
+        mul_div_floor(liquidity, sqrt_price_current, liquidity - denominator_subtraction)
+    }
+}
+
+// Token A between two sqrt prices for the given liquidity, the prices may come in either order
+pub fn get_amount_a_delta(
+    sqrt_ratio_a: u128,
+    sqrt_ratio_b: u128,
+    liquidity: u128,
+    round_up: bool,
+) -> Result<u128> {
+    if sqrt_ratio_a > sqrt_ratio_b {
+        return get_amount_a_delta(sqrt_ratio_b, sqrt_ratio_a, liquidity, round_up);
+    }
+
+    let price_diff = sqrt_ratio_b.checked_sub(sqrt_ratio_a).ok_or(MathError::Overflow)?;
+
+    // liquidity * diff / (a * b) in Q64.64, split in two divisions since a * b alone is Q128.128.
+    // Rounding both divisions the same way keeps the result on the requested side
+    if round_up {
+        let liquidity_over_b = mul_div_ceil(liquidity, 1 << 64, sqrt_ratio_b)?;
+        mul_div_ceil(liquidity_over_b, price_diff, sqrt_ratio_a)
+    } else {
+        let liquidity_over_b = mul_div_floor(liquidity, 1 << 64, sqrt_ratio_b)?;
+        mul_div_floor(liquidity_over_b, price_diff, sqrt_ratio_a)
+    }
+}
+
+// Token B between two sqrt prices for the given liquidity, the prices may come in either order
+pub fn get_amount_b_delta(
+    sqrt_ratio_a: u128,
+    sqrt_ratio_b: u128,
+    liquidity: u128,
+    round_up: bool,
+) -> Result<u128> {
+    if sqrt_ratio_a > sqrt_ratio_b {
+        return get_amount_b_delta(sqrt_ratio_b, sqrt_ratio_a, liquidity, round_up);
+    }
+
+    // liquidity * diff brought back from Q64.64
+    let price_diff = sqrt_ratio_b.checked_sub(sqrt_ratio_a).ok_or(MathError::Overflow)?;
+    if round_up {
+        mul_div_ceil(liquidity, price_diff, 1 << 64)
+    } else {
+        mul_div_floor(liquidity, price_diff, 1 << 64)
+    }
+}
+
+// a * b / denominator rounded down, with the product kept in 256 bits
+pub(crate) fn mul_div_floor(a: u128, b: u128, denominator: u128) -> Result<u128> {
+    mul_div(a, b, denominator).map(|(quotient, _)| quotient)
+}
+
+// a * b / denominator rounded up, with the product kept in 256 bits
+fn mul_div_ceil(a: u128, b: u128, denominator: u128) -> Result<u128> {
+    let (quotient, remainder) = mul_div(a, b, denominator)?;
+    if remainder == 0 {
+        Ok(quotient)
+    } else {
+        quotient.checked_add(1).ok_or(MathError::Overflow)
+    }
+}
+
+// a * b / denominator as (quotient, remainder)
+fn mul_div(a: u128, b: u128, denominator: u128) -> Result<(u128, u128)> {
+    if denominator == 0 {
+        return Err(MathError::DivisionByZero);
+    }
+    if let Some(product) = a.checked_mul(b) {
+        return Ok((product / denominator, product % denominator));
+    }
+
+    let (high, low) = full_mul(a, b);
+    // The quotient has to fit in 128 bits
+    if high >= denominator {
+        return Err(MathError::Overflow);
+    }
+
+    // Long division of the low half, the remainder starts as the high half
+    let mut remainder = high;
+    let mut quotient: u128 = 0;
+    for bit in (0..128).rev() {
+        let carry = remainder >> 127;
+        remainder = (remainder << 1) | ((low >> bit) & 1);
+        quotient <<= 1;
+        if carry == 1 || remainder >= denominator {
+            remainder = remainder.wrapping_sub(denominator);
+            quotient |= 1;
+        }
+    }
+
+    Ok((quotient, remainder))
+}
+
+// 256 bit product as (high, low) halves
+fn full_mul(a: u128, b: u128) -> (u128, u128) {
+    const MASK: u128 = u64::MAX as u128;
+    let (a_high, a_low) = (a >> 64, a & MASK);
+    let (b_high, b_low) = (b >> 64, b & MASK);
+
+    let low_low = a_low * b_low;
+    let low_high = a_low * b_high;
+    let high_low = a_high * b_low;
+    let high_high = a_high * b_high;
+
+    let middle = (low_low >> 64) + (low_high & MASK) + (high_low & MASK);
+    let low = (low_low & MASK) | (middle << 64);
+    let high = high_high + (low_high >> 64) + (high_low >> 64) + (middle >> 64);
+
+    (high, low)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tick_math::get_sqrt_price_from_tick;
+
+    #[test]
+    fn amount_deltas_round_as_asked() {
+        let sqrt_lower = get_sqrt_price_from_tick(-1_234).unwrap();
+        let sqrt_upper = get_sqrt_price_from_tick(5_678).unwrap();
+        let liquidity = 987_654_321_987;
+
+        let a_down = get_amount_a_delta(sqrt_lower, sqrt_upper, liquidity, false).unwrap();
+        let a_up = get_amount_a_delta(sqrt_lower, sqrt_upper, liquidity, true).unwrap();
+        let b_down = get_amount_b_delta(sqrt_lower, sqrt_upper, liquidity, false).unwrap();
+        let b_up = get_amount_b_delta(sqrt_lower, sqrt_upper, liquidity, true).unwrap();
+        // Neither amount is a whole number here, rounding up lands one above rounding down
+        assert_eq!(a_up, a_down + 1);
+        assert_eq!(b_up, b_down + 1);
+
+        // a = l * (1 / sqrt_lower - 1 / sqrt_upper), b = l * (sqrt_upper - sqrt_lower)
+        let (lower, upper) = (sqrt_lower as f64 / 2f64.powi(64), sqrt_upper as f64 / 2f64.powi(64));
+        let expected_a = liquidity as f64 * (1.0 / lower - 1.0 / upper);
+        let expected_b = liquidity as f64 * (upper - lower);
+        assert!(a_down as f64 <= expected_a && expected_a <= a_up as f64, "{a_down} {expected_a} {a_up}");
+        assert!(b_down as f64 <= expected_b && expected_b <= b_up as f64, "{b_down} {expected_b} {b_up}");
+
+        // The prices may come in either order
+        assert_eq!(get_amount_a_delta(sqrt_upper, sqrt_lower, liquidity, true).unwrap(), a_up);
+        assert_eq!(get_amount_b_delta(sqrt_upper, sqrt_lower, liquidity, true).unwrap(), b_up);
+    }
+
+    #[test]
+    fn amount_deltas_exact_or_empty() {
+        // Price 1 to price 2 with liquidity 2^64 holds exactly 2^63 of token A and 2^64 of token B
+        let liquidity = 1u128 << 64;
+        let (one, two) = (1u128 << 64, 2u128 << 64);
+        for round_up in [false, true] {
+            assert_eq!(get_amount_a_delta(one, two, liquidity, round_up).unwrap(), 1 << 63);
+            assert_eq!(get_amount_b_delta(one, two, liquidity, round_up).unwrap(), 1 << 64);
+            assert_eq!(get_amount_a_delta(one, one, liquidity, round_up).unwrap(), 0);
+            assert_eq!(get_amount_b_delta(one, one, liquidity, round_up).unwrap(), 0);
+        }
+    }
+
+    #[test]
+    fn amount_deltas_with_large_liquidity() {
+        // liquidity * price_diff does not fit in 128 bits
+        let sqrt_lower = get_sqrt_price_from_tick(-100_000).unwrap();
+        let sqrt_upper = get_sqrt_price_from_tick(100_000).unwrap();
+        let liquidity = u64::MAX as u128;
+        let b_down = get_amount_b_delta(sqrt_lower, sqrt_upper, liquidity, false).unwrap();
+        let b_up = get_amount_b_delta(sqrt_lower, sqrt_upper, liquidity, true).unwrap();
+        assert!(b_up - b_down <= 1);
+        let a_down = get_amount_a_delta(sqrt_lower, sqrt_upper, liquidity, false).unwrap();
+        let a_up = get_amount_a_delta(sqrt_lower, sqrt_upper, liquidity, true).unwrap();
+        assert!(a_up > a_down);
+        // The range is symmetric around price 1, it holds about as much of each token
+        assert!((a_down as f64 / b_down as f64 - 1.0).abs() < 1e-9);
+        assert!((a_up as f64 / b_up as f64 - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn new_sqrt_price_with_liquidity_above_64_bits() {
+        // liquidity * sqrt_price does not fit in 128 bits at price 1 with liquidity 2^80
+        let liquidity = 1u128 << 80;
+        let one = 1u128 << 64;
+        let amount = 1u128 << 70;
+
+        // Adding 2^70 of token A scales the price by 2^80 / (2^80 + 2^70) = 1024 / 1025
+        assert_eq!(get_new_sqrt_price_from_input(one, liquidity, amount, true).unwrap(), (1 << 74) / 1025);
+        // Taking 2^70 of token A out scales it by 1024 / 1023
+        assert_eq!(get_new_sqrt_price_from_output(one, liquidity, amount, false).unwrap(), (1 << 74) / 1023);
+        // Token B moves the price by amount / liquidity = 2^-10
+        assert_eq!(get_new_sqrt_price_from_input(one, liquidity, amount, false).unwrap(), one + (1 << 54));
+        assert_eq!(get_new_sqrt_price_from_output(one, liquidity, amount, true).unwrap(), one - (1 << 54));
+
+        // The output cannot take all of token A
+        assert_eq!(
+            get_new_sqrt_price_from_output(one, liquidity, liquidity, false),
+            Err(MathError::InsufficientLiquidity)
+        );
+    }
+}
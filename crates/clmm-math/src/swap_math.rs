@@ -0,0 +1,288 @@
+use crate::error::{MathError, Result};
+use crate::sqrt_price_math::*;
+
+// Fee rates are expressed in millionths, 1_000_000 is 100%
+pub const FEE_RATE_DENOMINATOR: u128 = 1_000_000;
+
+// Calculates the swap step, how much amount_in, out, and fees we need to pay
+// Returns (amount_in including the fee, amount_out, next sqrt price, fee amount)
+pub fn compute_swap_step(
+    sqrt_price_current: u128,
+    sqrt_price_target: u128,
+    liquidity: u128,
+    amount_remaining: u128,
+    fee_rate: u128, // In millionths, e.g. 3000 = 0.3%
+    exact_in: bool, // true for exact input, false for exact output
+) -> Result<(u128, u128, u128, u128)> {
+    if sqrt_price_current == sqrt_price_target {
+        return Ok((0, 0, sqrt_price_current, 0));
+    }
+
+    let zero_for_one = sqrt_price_target < sqrt_price_current;
+    let fee_denominator = FEE_RATE_DENOMINATOR;
+
+    let mut amount_in: u128;
+    let amount_out: u128;
+    let sqrt_price_next: u128;
+
+    if exact_in {
+        // We know the input amount (after fees), calculate output
+        let amount_remaining_less_fee = amount_remaining
+            .checked_mul(fee_denominator.checked_sub(fee_rate).ok_or(MathError::InvalidFeeRate)?)
+            .ok_or(MathError::Overflow)?
+            .checked_div(fee_denominator)
+            .ok_or(MathError::Overflow)?;
+
+        if zero_for_one {
+            // Swapping token A for token B 
+            // Calculate max amount A needed to reach target price
+            let amount_in_max = get_amount_a_delta(
+                sqrt_price_target,
+                sqrt_price_current,
+                liquidity,
+                true, // round up
+            )?;
+
+            if amount_remaining_less_fee >= amount_in_max {
+                // We can reach the target price
+                amount_in = amount_in_max;
+                sqrt_price_next = sqrt_price_target;
+            } else {
+                // We can't reach target, calculate new price
+                amount_in = amount_remaining_less_fee;
+                sqrt_price_next = get_new_sqrt_price_from_input(
+                    sqrt_price_current,
+                    liquidity,
+                    amount_in,
+                    true,
+                ).map_err(|_| MathError::Overflow)?;
+            }
+
+            // Calculate amount B out
+            amount_out = get_amount_b_delta(
+                sqrt_price_next,
+                sqrt_price_current,
+                liquidity,
+                false, // round down
+            )?;
+
+        } else {
+            // Swapping token B for token A
+            // Calculate max amount B needed to reach target price
+            let amount_in_max = get_amount_b_delta(
+                sqrt_price_current,
+                sqrt_price_target,
+                liquidity,
+                true, // round up
+            )?;
+
+            if amount_remaining_less_fee >= amount_in_max {
+                // We can reach the target price
+                amount_in = amount_in_max;
+                sqrt_price_next = sqrt_price_target;
+            } else {
+                // We can't reach target, calculate new price
+                amount_in = amount_remaining_less_fee;
+                sqrt_price_next = get_new_sqrt_price_from_input(
+                    sqrt_price_current,
+                    liquidity,
+                    amount_in,
+                    false,
+                ).map_err(|_| MathError::Overflow)?;
+            }
+
+            // Calculate amount A out
+            amount_out = get_amount_a_delta(
+                sqrt_price_current,
+                sqrt_price_next,
+                liquidity,
+                false, // round down
+            )?;
+        }
+
+        // Add back fee to get total amount_in including fee
+        amount_in = amount_in
+            .checked_mul(fee_denominator)
+            .ok_or(MathError::Overflow)?
+            .checked_div(fee_denominator.checked_sub(fee_rate).ok_or(MathError::InvalidFeeRate)?)
+            .ok_or(MathError::Overflow)?;
+
+    } else {
+        // Exact output - we know desired output amount
+        if zero_for_one {
+            // Swapping token A for token B, want exact amount B out
+            let amount_out_max = get_amount_b_delta(
+                sqrt_price_target,
+                sqrt_price_current,
+                liquidity,
+                false, // round down
+            )?;
+
+            if amount_remaining >= amount_out_max {
+                // We can reach the target price
+                amount_out = amount_out_max;
+                sqrt_price_next = sqrt_price_target;
+            } else {
+                // We can't reach target
+                amount_out = amount_remaining;
+                sqrt_price_next = get_new_sqrt_price_from_output(
+                    sqrt_price_current,
+                    liquidity,
+                    amount_out,
+                    true,
+                ).map_err(|_| MathError::Overflow)?;
+            }
+
+            // Calculate required amount0 in
+            amount_in = get_amount_a_delta(
+                sqrt_price_next,
+                sqrt_price_current,
+                liquidity,
+                true, // round up
+            )?;
+
+        } else {
+            // Swapping token B for token A, want exact amount A out
+            let amount_out_max = get_amount_a_delta(
+                sqrt_price_current,
+                sqrt_price_target,
+                liquidity,
+                false, // round down
+            )?;
+
+            if amount_remaining >= amount_out_max {
+                // We can reach the target price
+                amount_out = amount_out_max;
+                sqrt_price_next = sqrt_price_target;
+            } else {
+                // We can't reach target
+                amount_out = amount_remaining;
+                sqrt_price_next = get_new_sqrt_price_from_output(
+                    sqrt_price_current,
+                    liquidity,
+                    amount_out,
+                    false,
+                ).map_err(|_| MathError::Overflow)?;
+            }
+
+            // Calculate required amount B in
+            amount_in = get_amount_b_delta(
+                sqrt_price_current,
+                sqrt_price_next,
+                liquidity,
+                true, // round up
+            )?;
+        }
+
+        // Add fee to input amount
+        amount_in = amount_in
+            .checked_mul(fee_denominator)
+            .ok_or(MathError::Overflow)?
+            .checked_div(fee_denominator.checked_sub(fee_rate).ok_or(MathError::InvalidFeeRate)?)
+            .ok_or(MathError::Overflow)?;
+    }
+
+    // Calculate fee amount
+    let fee_amount = amount_in
+        .checked_mul(fee_rate)
+        .ok_or(MathError::Overflow)?
+        .checked_div(fee_denominator)
+        .ok_or(MathError::Overflow)?;
+
+    Ok((amount_in, amount_out, sqrt_price_next, fee_amount))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tick_math::get_sqrt_price_from_tick;
+
+    const LIQUIDITY: u128 = 1_000_000_000_000;
+    const FEE_RATE: u128 = 3_000;
+
+    #[test]
+    fn exact_in_partial_step() {
+        let sqrt_price = get_sqrt_price_from_tick(0).unwrap();
+        let sqrt_target = get_sqrt_price_from_tick(-1_000).unwrap();
+        let amount = 1_000_000;
+
+        let (amount_in, amount_out, sqrt_next, fee) =
+            compute_swap_step(sqrt_price, sqrt_target, LIQUIDITY, amount, FEE_RATE, true).unwrap();
+        // The whole input is spent without reaching the target
+        assert!(sqrt_target < sqrt_next && sqrt_next < sqrt_price);
+        assert!(amount - amount_in <= 1, "{amount_in}");
+        assert_eq!(fee, amount_in * FEE_RATE / FEE_RATE_DENOMINATOR);
+        // The output is what the price move releases, rounded down
+        assert_eq!(amount_out, get_amount_b_delta(sqrt_next, sqrt_price, LIQUIDITY, false).unwrap());
+        // At price 1 and deep liquidity the output is the input less the fee
+        let amount_less_fee = amount * (FEE_RATE_DENOMINATOR - FEE_RATE) / FEE_RATE_DENOMINATOR;
+        assert!(amount_out <= amount_less_fee && amount_less_fee - amount_out <= 2, "{amount_out}");
+    }
+
+    #[test]
+    fn exact_in_reaches_target() {
+        let sqrt_price = get_sqrt_price_from_tick(0).unwrap();
+        let sqrt_target = get_sqrt_price_from_tick(10).unwrap();
+        let amount = 1_000_000_000;
+
+        let (amount_in, amount_out, sqrt_next, fee) =
+            compute_swap_step(sqrt_price, sqrt_target, LIQUIDITY, amount, FEE_RATE, true).unwrap();
+        assert_eq!(sqrt_next, sqrt_target);
+        // Only what it takes to reach the target is used, rounded up in the pool's favour
+        let amount_to_target = get_amount_b_delta(sqrt_price, sqrt_target, LIQUIDITY, true).unwrap();
+        assert_eq!(amount_in, amount_to_target * FEE_RATE_DENOMINATOR / (FEE_RATE_DENOMINATOR - FEE_RATE));
+        assert!(amount_in < amount);
+        assert_eq!(fee, amount_in * FEE_RATE / FEE_RATE_DENOMINATOR);
+        assert_eq!(amount_out, get_amount_a_delta(sqrt_price, sqrt_target, LIQUIDITY, false).unwrap());
+    }
+
+    #[test]
+    fn exact_out_partial_step() {
+        let sqrt_price = get_sqrt_price_from_tick(0).unwrap();
+        let amount = 1_000_000;
+
+        for (sqrt_target, a_to_b) in [(get_sqrt_price_from_tick(-1_000).unwrap(), true), (get_sqrt_price_from_tick(1_000).unwrap(), false)] {
+            let (amount_in, amount_out, sqrt_next, fee) =
+                compute_swap_step(sqrt_price, sqrt_target, LIQUIDITY, amount, FEE_RATE, false).unwrap();
+            // Exactly the requested output comes out short of the target
+            assert_eq!(amount_out, amount);
+            assert!(sqrt_next != sqrt_target);
+            assert_eq!(sqrt_next < sqrt_price, a_to_b);
+
+            // The input less the fee covers the price move, rounded up
+            let amount_in_less_fee = if a_to_b {
+                get_amount_a_delta(sqrt_next, sqrt_price, LIQUIDITY, true).unwrap()
+            } else {
+                get_amount_b_delta(sqrt_price, sqrt_next, LIQUIDITY, true).unwrap()
+            };
+            assert_eq!(amount_in, amount_in_less_fee * FEE_RATE_DENOMINATOR / (FEE_RATE_DENOMINATOR - FEE_RATE));
+            assert!(amount_in_less_fee >= amount_out);
+            assert_eq!(fee, amount_in * FEE_RATE / FEE_RATE_DENOMINATOR);
+        }
+    }
+
+    #[test]
+    fn exact_out_reaches_target() {
+        let sqrt_price = get_sqrt_price_from_tick(0).unwrap();
+        let sqrt_target = get_sqrt_price_from_tick(-10).unwrap();
+
+        let (amount_in, amount_out, sqrt_next, _) =
+            compute_swap_step(sqrt_price, sqrt_target, LIQUIDITY, u64::MAX as u128, FEE_RATE, false).unwrap();
+        // The output is capped at what the range holds down to the target
+        assert_eq!(sqrt_next, sqrt_target);
+        assert_eq!(amount_out, get_amount_b_delta(sqrt_target, sqrt_price, LIQUIDITY, false).unwrap());
+        let amount_in_less_fee = get_amount_a_delta(sqrt_target, sqrt_price, LIQUIDITY, true).unwrap();
+        assert_eq!(amount_in, amount_in_less_fee * FEE_RATE_DENOMINATOR / (FEE_RATE_DENOMINATOR - FEE_RATE));
+    }
+
+    #[test]
+    fn no_step_at_target() {
+        let sqrt_price = get_sqrt_price_from_tick(42).unwrap();
+        for exact_in in [true, false] {
+            assert_eq!(
+                compute_swap_step(sqrt_price, sqrt_price, LIQUIDITY, 1_000, FEE_RATE, exact_in).unwrap(),
+                (0, 0, sqrt_price, 0)
+            );
+        }
+    }
+}
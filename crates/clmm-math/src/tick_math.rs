@@ -0,0 +1,53 @@
+use crate::error::{MathError, Result};
+
+// Floats go through libm so the program and clients round the same way on every target
+
+// Calculates the tick that corresponds to a given sqrt price
+pub fn get_tick_from_sqrt_price(sqrt_price_x64: u128) -> Result<i32> {
+    let sqrt_price = sqrt_price_x64 as f64 / ((1u128 << 64) as f64);
+    let log_base = libm::log(1.0001_f64);
+    let tick = libm::floor(2.0 * libm::log(sqrt_price) / log_base) as i32;
+
+    // The log can land a tick off at the boundaries, settle on the tick whose price range holds sqrt_price_x64
+    if get_sqrt_price_from_tick(tick)? > sqrt_price_x64 {
+        Ok(tick - 1)
+    } else if get_sqrt_price_from_tick(tick + 1)? <= sqrt_price_x64 {
+        Ok(tick + 1)
+    } else {
+        Ok(tick)
+    }
+}
+
+// Calculates the sqrt price from a tick
+pub fn get_sqrt_price_from_tick(tick: i32) -> Result<u128> {
+    let sqrt_price_x64 = libm::pow(1.0001_f64, tick as f64 / 2.0) * ((1u128 << 64) as f64);
+    Ok(sqrt_price_x64 as u128)
+}
+
+// Calculates the next initialized tick in the swap direction. The price sits at or above tick_current, so moving
+// down the next tick to cross may be tick_current itself
+pub fn get_next_tick(tick_current: i32, initialized_ticks: &[i32], a_to_b: bool) -> Result<i32> {
+    let next_tick = if a_to_b {
+        initialized_ticks.iter().filter(|&&x| x <= tick_current).max().copied()
+    } else {
+        initialized_ticks.iter().filter(|&&x| x > tick_current).min().copied()
+    };
+
+    next_tick.ok_or(MathError::TickNotFound)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tick_sqrt_price_round_trip() {
+        for tick in (-400_000..=400_000).step_by(997).chain([-1, 0, 1]) {
+            let sqrt_price = get_sqrt_price_from_tick(tick).unwrap();
+            assert_eq!(get_tick_from_sqrt_price(sqrt_price).unwrap(), tick, "tick {tick}");
+            // Any price short of the next tick still maps to this tick
+            let sqrt_price_next = get_sqrt_price_from_tick(tick + 1).unwrap();
+            assert_eq!(get_tick_from_sqrt_price(sqrt_price_next - 1).unwrap(), tick, "tick {tick}");
+        }
+    }
+}
@@ -0,0 +1,188 @@
+use anchor_lang::error::ErrorCode;
+use anchor_lang::{AccountDeserialize, Result};
+
+use crate::{Config, Observation, ObservationEntry, Pool, Position, PositionBundle, Tick};
+
+// Decoders take the raw account data, discriminator included, and fail on any other account type
+
+pub fn decode_pool(data: &[u8]) -> Result<Pool> {
+    decode_account(data)
+}
+
+pub fn decode_position(data: &[u8]) -> Result<Position> {
+    decode_account(data)
+}
+
+pub fn decode_tick(data: &[u8]) -> Result<Tick> {
+    decode_account(data)
+}
+
+pub fn decode_position_bundle(data: &[u8]) -> Result<PositionBundle> {
+    decode_account(data)
+}
+
+// The header and the ring buffer slots that follow it, the account grows with its cardinality
+pub fn decode_observation(data: &[u8]) -> Result<(Observation, Vec<ObservationEntry>)> {
+    let header = data.get(..Observation::space(0)).ok_or(ErrorCode::AccountDidNotDeserialize)?;
+    let observation = decode_account(header)?;
+    if !(data.len() - header.len()).is_multiple_of(std::mem::size_of::<ObservationEntry>()) {
+        return Err(ErrorCode::AccountDidNotDeserialize.into());
+    }
+    Ok((observation, Observation::split(data).1.to_vec()))
+}
+
+pub fn decode_config(data: &[u8]) -> Result<Config> {
+    decode_account(data)
+}
+
+// Any account of the program
+pub fn decode_account<T: AccountDeserialize>(mut data: &[u8]) -> Result<T> {
+    T::try_deserialize(&mut data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::*;
+
+    #[test]
+    fn decodes_pool_fixture() {
+        let pool = decode_pool(&pool_data()).unwrap();
+
+        assert_eq!(pool.pool_authority, AUTHORITY);
+        assert_eq!(pool.token_a_mint, MINT_A);
+        assert_eq!(pool.token_b_mint, MINT_B);
+        assert_eq!(pool.token_a_vault, VAULT_A);
+        assert_eq!(pool.token_b_vault, VAULT_B);
+        assert_eq!(pool.sqrt_price, 1 << 64);
+        assert_eq!(pool.tick_current, -5);
+        assert_eq!(pool.liquidity, 1_000_000);
+        assert_eq!(pool.tick_spacing, 10);
+        assert_eq!(pool.initialized_ticks, vec![-100, -5, 100]);
+        assert_eq!(pool.fee, 30);
+        assert_eq!(
+            (pool.fee_growth_global_a, pool.fee_growth_global_b, pool.protocol_fee_a, pool.protocol_fee_b),
+            (11, 12, 13, 14)
+        );
+        assert_eq!(pool.bump, 254);
+        assert_eq!(pool.reward_last_updated_timestamp, 1_700_000_000);
+        assert!(pool.reward_infos[0].initialized());
+        assert_eq!(pool.reward_infos[0].mint, REWARD_MINT);
+        assert_eq!(pool.reward_infos[0].emissions_per_second_x64, 1 << 60);
+        assert_eq!(pool.reward_infos[0].growth_global_x64, 77);
+        assert!(!pool.reward_infos[1].initialized());
+        assert!(!pool.reward_infos[2].initialized());
+        assert!(pool.dynamic_fee.enabled);
+        assert_eq!(pool.dynamic_fee.reduction_factor, 5_000);
+        assert_eq!(pool.dynamic_fee.max_fee, 10_000);
+        assert_eq!(pool.dynamic_fee.tick_reference, -7);
+        assert_eq!(pool.dynamic_fee.last_update_timestamp, 1_700_000_100);
+        assert_eq!(pool.pause_flags, 0b0000_0101);
+        assert_eq!(pool.pending_fee, 50);
+        assert_eq!(pool.pending_fee_activation, 1_700_086_400);
+        assert_eq!(
+            (pool.flash_loan_amount_a, pool.flash_loan_amount_b, pool.flash_loan_fee_a, pool.flash_loan_fee_b),
+            (1_000, 2_000, 3, 4)
+        );
+        assert!(!pool.locked);
+        assert!(pool.pending_dynamic_fee.enabled);
+        assert_eq!(pool.pending_dynamic_fee.max_fee, 20_000);
+        assert_eq!(pool.pending_dynamic_fee_activation, 1_700_086_500);
+    }
+
+    #[test]
+    fn decodes_position_fixture() {
+        let position = decode_position(&position_data()).unwrap();
+
+        assert_eq!(position.owner, OWNER);
+        assert_eq!((position.tick_lower, position.tick_upper), (-60, 120));
+        assert_eq!(position.liquidity, 5_000_000);
+        assert_eq!((position.fee_growth_inside_a, position.fee_growth_inside_b), (21, 22));
+        assert_eq!((position.token_a_earned, position.token_b_earned), (300, 400));
+        assert_eq!(position.bundle_index, 3);
+        assert_eq!(position.delegate, Some(DELEGATE));
+        assert!(position.range_order);
+        assert!(!position.range_order_sells_a);
+        assert_eq!(position.range_order_fills, 2);
+        for (i, reward_info) in position.reward_infos.iter().enumerate() {
+            assert_eq!(reward_info.growth_inside_checkpoint, 100 + i as u128);
+            assert_eq!(reward_info.amount_owed, 10 + i as u64);
+        }
+        assert_eq!(position.bump, 253);
+    }
+
+    #[test]
+    fn decodes_tick_fixture() {
+        let tick = decode_tick(&tick_data()).unwrap();
+
+        assert_eq!(tick.tick_index, -60);
+        assert_eq!(tick.liquidity_net, -5_000_000);
+        assert_eq!((tick.fee_growth_outside_a, tick.fee_growth_outside_b), (31, 32));
+        assert!(tick.initialized);
+        assert_eq!(tick.bump, 252);
+        assert_eq!((tick.range_order_liquidity_up, tick.range_order_liquidity_down), (600, 700));
+        assert_eq!((tick.range_order_fills_up, tick.range_order_fills_down), (8, 9));
+        assert_eq!((tick.range_order_fee_growth_up_a, tick.range_order_fee_growth_up_b), (51, 52));
+        assert_eq!((tick.range_order_fee_growth_down_a, tick.range_order_fee_growth_down_b), (53, 54));
+        assert_eq!(tick.range_order_reward_growths_up, [61, 62, 63]);
+        assert_eq!(tick.range_order_reward_growths_down, [71, 72, 73]);
+        assert_eq!(tick.reward_growths_outside, [41, 42, 43]);
+    }
+
+    #[test]
+    fn decodes_observation_fixture() {
+        let (observation, observations) = decode_observation(&observation_data()).unwrap();
+
+        assert_eq!(observation.pool, VAULT_A);
+        assert_eq!({ observation.observation_index }, 2);
+        assert_eq!(({ observation.cardinality }, { observation.cardinality_next }), (2, 3));
+        assert_eq!(observation.bump, 251);
+        assert_eq!(observations.len(), 3);
+        assert!(!observations[1].initialized);
+        assert_eq!({ observations[2].timestamp }, 1_700_000_010);
+        assert_eq!({ observations[2].tick_cumulative }, -50);
+        assert_eq!({ observations[2].seconds_per_liquidity_cumulative }, 10 << 64);
+
+        let data = observation_data();
+        assert!(decode_observation(&data[..data.len() - 1]).is_err());
+        assert!(decode_observation(&tick_data()).is_err());
+    }
+
+    #[test]
+    fn decodes_position_without_delegate() {
+        let mut data = position_data();
+        // Option tag sits after owner, ticks, liquidity, fee growths, earned amounts and bundle index
+        let delegate_offset = 8 + 32 + 4 + 4 + 16 + 16 + 16 + 8 + 8 + 2;
+        data.splice(delegate_offset..delegate_offset + 33, [0]);
+
+        let position = decode_position(&data).unwrap();
+        assert_eq!(position.delegate, None);
+        assert!(position.range_order);
+        assert_eq!(position.bump, 253);
+    }
+
+    #[test]
+    fn rejects_other_account_types() {
+        assert!(decode_pool(&tick_data()).is_err());
+        assert!(decode_tick(&position_data()).is_err());
+        assert!(decode_position(&pool_data()).is_err());
+    }
+
+    #[test]
+    fn rejects_truncated_data() {
+        let data = pool_data();
+        assert!(decode_pool(&data[..data.len() - 1]).is_err());
+        assert!(decode_pool(&data[..4]).is_err());
+        assert!(decode_tick(&[]).is_err());
+    }
+
+    #[test]
+    fn ignores_trailing_space() {
+        // Accounts are allocated with more space than their current contents
+        let mut data = tick_data();
+        data.extend_from_slice(&[0; 64]);
+        let tick = decode_tick(&data).unwrap();
+        assert_eq!(tick.tick_index, -60);
+        assert_eq!(tick.reward_growths_outside, [41, 42, 43]);
+    }
+}
@@ -0,0 +1,218 @@
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::hash::hash;
+
+// Account data written field by field in the program's Borsh layout, independent of the program's own serializers
+
+pub const MINT_A: Pubkey = Pubkey::new_from_array([1; 32]);
+pub const MINT_B: Pubkey = Pubkey::new_from_array([2; 32]);
+pub const VAULT_A: Pubkey = Pubkey::new_from_array([3; 32]);
+pub const VAULT_B: Pubkey = Pubkey::new_from_array([4; 32]);
+pub const AUTHORITY: Pubkey = Pubkey::new_from_array([5; 32]);
+pub const OWNER: Pubkey = Pubkey::new_from_array([6; 32]);
+pub const DELEGATE: Pubkey = Pubkey::new_from_array([7; 32]);
+pub const TOKEN_PROGRAM: Pubkey = Pubkey::new_from_array([8; 32]);
+pub const REWARD_MINT: Pubkey = Pubkey::new_from_array([9; 32]);
+
+pub struct Layout(Vec<u8>);
+
+impl Layout {
+    pub fn account(name: &str) -> Self {
+        Self(discriminator("account", name).to_vec())
+    }
+
+    pub fn bytes(mut self, bytes: &[u8]) -> Self {
+        self.0.extend_from_slice(bytes);
+        self
+    }
+
+    pub fn pubkey(self, key: &Pubkey) -> Self {
+        self.bytes(key.as_ref())
+    }
+
+    pub fn bool(self, value: bool) -> Self {
+        self.bytes(&[value as u8])
+    }
+
+    pub fn u8(self, value: u8) -> Self {
+        self.bytes(&[value])
+    }
+
+    pub fn u16(self, value: u16) -> Self {
+        self.bytes(&value.to_le_bytes())
+    }
+
+    pub fn u32(self, value: u32) -> Self {
+        self.bytes(&value.to_le_bytes())
+    }
+
+    pub fn i32(self, value: i32) -> Self {
+        self.bytes(&value.to_le_bytes())
+    }
+
+    pub fn u64(self, value: u64) -> Self {
+        self.bytes(&value.to_le_bytes())
+    }
+
+    pub fn i64(self, value: i64) -> Self {
+        self.bytes(&value.to_le_bytes())
+    }
+
+    pub fn u128(self, value: u128) -> Self {
+        self.bytes(&value.to_le_bytes())
+    }
+
+    pub fn i128(self, value: i128) -> Self {
+        self.bytes(&value.to_le_bytes())
+    }
+
+    pub fn vec_i32(self, values: &[i32]) -> Self {
+        values.iter().fold(self.u32(values.len() as u32), |layout, &value| layout.i32(value))
+    }
+
+    pub fn option_pubkey(self, key: Option<&Pubkey>) -> Self {
+        match key {
+            Some(key) => self.u8(1).pubkey(key),
+            None => self.u8(0),
+        }
+    }
+
+    pub fn finish(self) -> Vec<u8> {
+        self.0
+    }
+}
+
+// Anchor discriminator, the first 8 bytes of sha256("<namespace>:<name>")
+pub fn discriminator(namespace: &str, name: &str) -> [u8; 8] {
+    let mut discriminator = [0; 8];
+    discriminator.copy_from_slice(&hash(format!("{namespace}:{name}").as_bytes()).to_bytes()[..8]);
+    discriminator
+}
+
+pub fn pool_data() -> Vec<u8> {
+    let layout = Layout::account("Pool")
+        .pubkey(&AUTHORITY)
+        .pubkey(&MINT_A)
+        .pubkey(&MINT_B)
+        .pubkey(&VAULT_A)
+        .pubkey(&VAULT_B)
+        .u128(1 << 64) // sqrt_price, price 1
+        .i32(-5)
+        .u128(1_000_000)
+        .u16(10)
+        .vec_i32(&[-100, -5, 100])
+        .u8(30)
+        .u128(11)
+        .u128(12)
+        .u128(13)
+        .u128(14)
+        .u8(254)
+        .i64(1_700_000_000);
+    // Reward slot 0 is in use, the other two are empty
+    let layout = layout.pubkey(&REWARD_MINT).pubkey(&VAULT_A).u128(1 << 60).u128(77);
+    let layout = (1..3).fold(layout, |layout, _| {
+        layout.pubkey(&Pubkey::default()).pubkey(&Pubkey::default()).u128(0).u128(0)
+    });
+    layout
+        // Dynamic fee
+        .bool(true)
+        .u32(30)
+        .u32(600)
+        .u16(5_000)
+        .u32(40_000)
+        .u32(350_000)
+        .u32(10_000)
+        .u32(1_234)
+        .u32(567)
+        .i32(-7)
+        .i64(1_700_000_100)
+        .u8(0b0000_0101) // pause_flags
+        .u8(50) // pending_fee
+        .i64(1_700_086_400)
+        .u64(1_000)
+        .u64(2_000)
+        .u64(3)
+        .u64(4)
+        .bool(false)
+        .bool(true) // pending_dynamic_fee
+        .u32(60)
+        .u32(1_200)
+        .u16(2_500)
+        .u32(20_000)
+        .u32(300_000)
+        .u32(20_000)
+        .u32(0)
+        .u32(0)
+        .i32(0)
+        .i64(0)
+        .i64(1_700_086_500)
+        .finish()
+}
+
+pub fn position_data() -> Vec<u8> {
+    let layout = Layout::account("Position")
+        .pubkey(&OWNER)
+        .i32(-60)
+        .i32(120)
+        .u128(5_000_000)
+        .u128(21)
+        .u128(22)
+        .u64(300)
+        .u64(400)
+        .u16(3)
+        .option_pubkey(Some(&DELEGATE))
+        .bool(true)
+        .bool(false)
+        .u64(2);
+    (0..3)
+        .fold(layout, |layout, i| layout.u128(100 + i).u64(10 + i as u64))
+        .u8(253)
+        .finish()
+}
+
+pub fn tick_data() -> Vec<u8> {
+    Layout::account("Tick")
+        .i32(-60)
+        .i128(-5_000_000)
+        .u128(31)
+        .u128(32)
+        .bool(true)
+        .u8(252)
+        .u128(600)
+        .u128(700)
+        .u64(8)
+        .u64(9)
+        .u128(51)
+        .u128(52)
+        .u128(53)
+        .u128(54)
+        .u128(61)
+        .u128(62)
+        .u128(63)
+        .u128(71)
+        .u128(72)
+        .u128(73)
+        .u128(41)
+        .u128(42)
+        .u128(43)
+        .finish()
+}
+
+// Header of a buffer grown to three slots, the second one not written yet
+pub fn observation_data() -> Vec<u8> {
+    Layout::account("Observation")
+        .pubkey(&VAULT_A)
+        .u16(2)
+        .u16(2)
+        .u16(3)
+        .u8(251)
+        .i64(1_700_000_000)
+        .i64(0)
+        .u128(0)
+        .bool(true)
+        .bytes(&[0; 33])
+        .i64(1_700_000_010)
+        .i64(-50)
+        .u128(10 << 64)
+        .bool(true)
+        .finish()
+}
@@ -0,0 +1,77 @@
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::system_program;
+use clmm_dex::{accounts, instruction};
+
+use crate::instructions::{build_instruction, event_authority};
+use crate::pda::*;
+use crate::PROGRAM_ID;
+
+// Signed by the program's upgrade authority
+pub fn initialize_config(admin: Pubkey, pause_authority: Pubkey, fee_change_delay: i64) -> Instruction {
+    build_instruction(
+        accounts::InitializeConfig {
+            config: find_config_address().0,
+            program_data: find_program_data_address().0,
+            admin,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: PROGRAM_ID,
+        },
+        instruction::InitializeConfig { pause_authority, fee_change_delay },
+        &[],
+    )
+}
+
+pub fn set_pause_authority(admin: Pubkey, pause_authority: Pubkey) -> Instruction {
+    build_instruction(
+        accounts::SetPauseAuthority {
+            config: find_config_address().0,
+            admin,
+            event_authority: event_authority(),
+            program: PROGRAM_ID,
+        },
+        instruction::SetPauseAuthority { pause_authority },
+        &[],
+    )
+}
+
+pub fn set_transfer_hook_allowlist(admin: Pubkey, transfer_hook_programs: Vec<Pubkey>) -> Instruction {
+    build_instruction(
+        accounts::SetTransferHookAllowlist {
+            config: find_config_address().0,
+            admin,
+            event_authority: event_authority(),
+            program: PROGRAM_ID,
+        },
+        instruction::SetTransferHookAllowlist { transfer_hook_programs },
+        &[],
+    )
+}
+
+pub fn set_global_pause(pause_authority: Pubkey, paused: bool) -> Instruction {
+    build_instruction(
+        accounts::SetGlobalPause {
+            config: find_config_address().0,
+            pause_authority,
+            event_authority: event_authority(),
+            program: PROGRAM_ID,
+        },
+        instruction::SetGlobalPause { paused },
+        &[],
+    )
+}
+
+pub fn set_pool_pause_flags(pool: Pubkey, pause_authority: Pubkey, pause_flags: u8) -> Instruction {
+    build_instruction(
+        accounts::SetPoolPauseFlags {
+            config: find_config_address().0,
+            pool,
+            pause_authority,
+            event_authority: event_authority(),
+            program: PROGRAM_ID,
+        },
+        instruction::SetPoolPauseFlags { pause_flags },
+        &[],
+    )
+}
@@ -0,0 +1,110 @@
+use anchor_lang::prelude::{AccountMeta, Pubkey};
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::sysvar;
+use clmm_dex::{accounts, instruction};
+
+use crate::instructions::{build_instruction, event_authority, PoolKeys};
+use crate::pda::*;
+use crate::PROGRAM_ID;
+
+// Has to be followed in the same transaction by flash_repay with the same pool and amounts
+pub fn flash_loan(
+    pool_keys: &PoolKeys,
+    borrower: Pubkey,
+    borrower_token_a: Pubkey,
+    borrower_token_b: Pubkey,
+    amount_a: u64,
+    amount_b: u64,
+    remaining_accounts: &[AccountMeta],
+) -> Instruction {
+    build_instruction(
+        accounts::FlashLoan {
+            pool: pool_keys.pool,
+            config: find_config_address().0,
+            token_a_vault: pool_keys.token_vault_a,
+            token_b_vault: pool_keys.token_vault_b,
+            borrower_token_a,
+            borrower_token_b,
+            borrower,
+            token_mint_a: pool_keys.token_mint_a,
+            token_mint_b: pool_keys.token_mint_b,
+            token_program_a: pool_keys.token_program_a,
+            token_program_b: pool_keys.token_program_b,
+            instructions: sysvar::instructions::ID,
+            event_authority: event_authority(),
+            program: PROGRAM_ID,
+        },
+        instruction::FlashLoan { amount_a, amount_b },
+        remaining_accounts,
+    )
+}
+
+pub fn flash_repay(
+    pool_keys: &PoolKeys,
+    repayer: Pubkey,
+    repayer_token_a: Pubkey,
+    repayer_token_b: Pubkey,
+    amount_a: u64,
+    amount_b: u64,
+    remaining_accounts: &[AccountMeta],
+) -> Instruction {
+    build_instruction(
+        accounts::FlashRepay {
+            pool: pool_keys.pool,
+            config: find_config_address().0,
+            token_a_vault: pool_keys.token_vault_a,
+            token_b_vault: pool_keys.token_vault_b,
+            repayer_token_a,
+            repayer_token_b,
+            repayer,
+            token_mint_a: pool_keys.token_mint_a,
+            token_mint_b: pool_keys.token_mint_b,
+            token_program_a: pool_keys.token_program_a,
+            token_program_b: pool_keys.token_program_b,
+            event_authority: event_authority(),
+            program: PROGRAM_ID,
+        },
+        instruction::FlashRepay { amount_a, amount_b },
+        remaining_accounts,
+    )
+}
+
+// remaining_accounts holds the crossed ticks, then transfer hook accounts and whatever the callback needs
+#[allow(clippy::too_many_arguments)]
+pub fn flash_swap(
+    pool_keys: &PoolKeys,
+    user: Pubkey,
+    user_token_a: Pubkey,
+    user_token_b: Pubkey,
+    callback_program: Pubkey,
+    amount_in: u64,
+    a_to_b: bool,
+    data: Vec<u8>,
+    remaining_accounts: &[AccountMeta],
+) -> Instruction {
+    build_instruction(
+        accounts::FlashSwap {
+            user,
+            user_token_a_account: user_token_a,
+            user_token_b_account: user_token_b,
+            pool: pool_keys.pool,
+            config: find_config_address().0,
+            observation: pool_keys.observation(),
+            pool_token_a_vault: pool_keys.token_vault_a,
+            pool_token_b_vault: pool_keys.token_vault_b,
+            token_mint_a: pool_keys.token_mint_a,
+            token_mint_b: pool_keys.token_mint_b,
+            token_program_a: pool_keys.token_program_a,
+            token_program_b: pool_keys.token_program_b,
+            callback_program,
+            event_authority: event_authority(),
+            program: PROGRAM_ID,
+        },
+        instruction::FlashSwap {
+            amount_in,
+            a_to_b,
+            data,
+        },
+        remaining_accounts,
+    )
+}
@@ -0,0 +1,288 @@
+use anchor_lang::prelude::{AccountMeta, Pubkey};
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::sysvar;
+use anchor_lang::system_program;
+use clmm_dex::{accounts, instruction};
+
+use crate::instructions::{build_instruction, event_authority, temp_wsol_account, PoolKeys, PositionKeys, RangeOrderKeys};
+use crate::pda::*;
+use crate::{Pool, PROGRAM_ID};
+
+pub fn initialize_position_bundle(pool: Pubkey, owner: Pubkey) -> Instruction {
+    build_instruction(
+        accounts::InitializePositionBundle {
+            pool,
+            position_bundle: find_position_bundle_address(&pool, &owner).0,
+            owner,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: PROGRAM_ID,
+        },
+        instruction::InitializePositionBundle {},
+        &[],
+    )
+}
+
+// Leave an owner token account out on a native SOL side to deposit lamports instead. Range orders pass the fill
+// records they touch
+pub fn provide_liquidity(
+    pool_keys: &PoolKeys,
+    position_keys: &PositionKeys,
+    owner_token_a: Option<Pubkey>,
+    owner_token_b: Option<Pubkey>,
+    liquidity: u128,
+    range_order: Option<RangeOrderKeys>,
+    remaining_accounts: &[AccountMeta],
+) -> Instruction {
+    build_instruction(
+        accounts::ProvideLiquidity {
+            pool: pool_keys.pool,
+            config: find_config_address().0,
+            observation: pool_keys.observation(),
+            position_bundle: position_keys.position_bundle,
+            position: position_keys.position,
+            token_a_vault: pool_keys.token_vault_a,
+            token_b_vault: pool_keys.token_vault_b,
+            owner_token_a,
+            owner_token_b,
+            lower_tick: position_keys.lower_tick,
+            upper_tick: position_keys.upper_tick,
+            range_order_fill: range_order.map(|keys| keys.range_order_fill),
+            previous_range_order_fill: range_order.map(|keys| keys.previous_range_order_fill),
+            owner: position_keys.owner,
+            token_mint_a: pool_keys.token_mint_a,
+            token_mint_b: pool_keys.token_mint_b,
+            temp_wsol_account: temp_wsol_account(&position_keys.owner, owner_token_a, owner_token_b),
+            token_program_a: pool_keys.token_program_a,
+            token_program_b: pool_keys.token_program_b,
+            system_program: system_program::ID,
+            rent: sysvar::rent::ID,
+            event_authority: event_authority(),
+            program: PROGRAM_ID,
+        },
+        instruction::ProvideLiquidity {
+            tick_lower: position_keys.tick_lower,
+            tick_upper: position_keys.tick_upper,
+            liquidity,
+            bundle_index: position_keys.bundle_index,
+            range_order: range_order.is_some(),
+        },
+        remaining_accounts,
+    )
+}
+
+// Leave an owner token account out on a native SOL side to receive lamports instead
+pub fn remove_liquidity(
+    pool_keys: &PoolKeys,
+    position_keys: &PositionKeys,
+    owner_token_a: Option<Pubkey>,
+    owner_token_b: Option<Pubkey>,
+    remaining_accounts: &[AccountMeta],
+) -> Instruction {
+    build_instruction(
+        accounts::RemoveLiquidity {
+            pool: pool_keys.pool,
+            config: find_config_address().0,
+            observation: pool_keys.observation(),
+            position_bundle: position_keys.position_bundle,
+            position: position_keys.position,
+            lower_tick: position_keys.lower_tick,
+            upper_tick: position_keys.upper_tick,
+            token_a_vault: pool_keys.token_vault_a,
+            token_b_vault: pool_keys.token_vault_b,
+            owner_token_a,
+            owner_token_b,
+            owner: position_keys.owner,
+            token_mint_a: pool_keys.token_mint_a,
+            token_mint_b: pool_keys.token_mint_b,
+            temp_wsol_account: temp_wsol_account(&position_keys.owner, owner_token_a, owner_token_b),
+            token_program_a: pool_keys.token_program_a,
+            token_program_b: pool_keys.token_program_b,
+            system_program: system_program::ID,
+            rent: sysvar::rent::ID,
+            event_authority: event_authority(),
+            program: PROGRAM_ID,
+        },
+        instruction::RemoveLiquidity {},
+        remaining_accounts,
+    )
+}
+
+// Vaults of the pool's rewards, passed in remaining_accounts so that a position owed rewards an empty vault
+// cannot pay can still close
+pub fn reward_vault_accounts(pool: &Pool) -> Vec<AccountMeta> {
+    pool.reward_infos
+        .iter()
+        .filter(|reward_info| reward_info.initialized())
+        .map(|reward_info| AccountMeta::new_readonly(reward_info.vault, false))
+        .collect()
+}
+
+pub fn set_position_delegate(pool: Pubkey, position_keys: &PositionKeys, delegate: Option<Pubkey>) -> Instruction {
+    build_instruction(
+        accounts::SetPositionDelegate {
+            pool,
+            position_bundle: position_keys.position_bundle,
+            position: position_keys.position,
+            owner: position_keys.owner,
+            event_authority: event_authority(),
+            program: PROGRAM_ID,
+        },
+        instruction::SetPositionDelegate { delegate },
+        &[],
+    )
+}
+
+// The new owner needs a position bundle in the same pool
+pub fn transfer_position(
+    pool: Pubkey,
+    position_keys: &PositionKeys,
+    new_owner: Pubkey,
+    new_bundle_index: u16,
+) -> Instruction {
+    let new_position_bundle = find_position_bundle_address(&pool, &new_owner).0;
+    build_instruction(
+        accounts::TransferPosition {
+            pool,
+            position_bundle: position_keys.position_bundle,
+            position: position_keys.position,
+            new_position_bundle,
+            new_position: find_position_address(&new_position_bundle, new_bundle_index).0,
+            owner: position_keys.owner,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: PROGRAM_ID,
+        },
+        instruction::TransferPosition { new_bundle_index },
+        &[],
+    )
+}
+
+// The authority is the owner or the position delegate. Range orders need the keys from PositionKeys::from_position
+pub fn collect_fees(
+    pool_keys: &PoolKeys,
+    position_keys: &PositionKeys,
+    authority: Pubkey,
+    owner_token_a: Pubkey,
+    owner_token_b: Pubkey,
+    remaining_accounts: &[AccountMeta],
+) -> Instruction {
+    build_instruction(
+        accounts::CollectFees {
+            pool: pool_keys.pool,
+            config: find_config_address().0,
+            position_bundle: position_keys.position_bundle,
+            position: position_keys.position,
+            lower_tick: position_keys.lower_tick,
+            upper_tick: position_keys.upper_tick,
+            range_order_fill: position_keys.range_order_fill,
+            token_a_vault: pool_keys.token_vault_a,
+            token_b_vault: pool_keys.token_vault_b,
+            owner_token_a,
+            owner_token_b,
+            authority,
+            token_mint_a: pool_keys.token_mint_a,
+            token_mint_b: pool_keys.token_mint_b,
+            token_program_a: pool_keys.token_program_a,
+            token_program_b: pool_keys.token_program_b,
+            event_authority: event_authority(),
+            program: PROGRAM_ID,
+        },
+        instruction::CollectFees {},
+        remaining_accounts,
+    )
+}
+
+pub fn compound_fees(pool: Pubkey, position_keys: &PositionKeys, authority: Pubkey) -> Instruction {
+    build_instruction(
+        accounts::CompoundFees {
+            pool,
+            config: find_config_address().0,
+            observation: find_observation_address(&pool).0,
+            position_bundle: position_keys.position_bundle,
+            position: position_keys.position,
+            lower_tick: position_keys.lower_tick,
+            upper_tick: position_keys.upper_tick,
+            authority,
+            event_authority: event_authority(),
+            program: PROGRAM_ID,
+        },
+        instruction::CompoundFees {},
+        &[],
+    )
+}
+
+// Ticks crossed by the optional swap go in remaining_accounts, the authority pays for new ticks
+#[allow(clippy::too_many_arguments)]
+pub fn rebalance_position(
+    pool: Pubkey,
+    position_keys: &PositionKeys,
+    authority: Pubkey,
+    new_tick_lower: i32,
+    new_tick_upper: i32,
+    min_liquidity: u128,
+    swap_amount_in: u64,
+    a_to_b: bool,
+    remaining_accounts: &[AccountMeta],
+) -> Instruction {
+    build_instruction(
+        accounts::RebalancePosition {
+            pool,
+            config: find_config_address().0,
+            observation: find_observation_address(&pool).0,
+            position_bundle: position_keys.position_bundle,
+            position: position_keys.position,
+            lower_tick: position_keys.lower_tick,
+            upper_tick: position_keys.upper_tick,
+            new_lower_tick: find_tick_address(&pool, new_tick_lower).0,
+            new_upper_tick: find_tick_address(&pool, new_tick_upper).0,
+            authority,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: PROGRAM_ID,
+        },
+        instruction::RebalancePosition {
+            new_tick_lower,
+            new_tick_upper,
+            min_liquidity,
+            swap_amount_in,
+            a_to_b,
+        },
+        remaining_accounts,
+    )
+}
+
+// Position keys from PositionKeys::from_position, which carry the order's fill record
+pub fn claim_range_order(
+    pool_keys: &PoolKeys,
+    position_keys: &PositionKeys,
+    owner_token_a: Pubkey,
+    owner_token_b: Pubkey,
+    remaining_accounts: &[AccountMeta],
+) -> Instruction {
+    build_instruction(
+        accounts::ClaimRangeOrder {
+            pool: pool_keys.pool,
+            config: find_config_address().0,
+            observation: pool_keys.observation(),
+            position_bundle: position_keys.position_bundle,
+            position: position_keys.position,
+            lower_tick: position_keys.lower_tick,
+            upper_tick: position_keys.upper_tick,
+            range_order_fill: position_keys.range_order_fill.unwrap_or_default(),
+            token_a_vault: pool_keys.token_vault_a,
+            token_b_vault: pool_keys.token_vault_b,
+            owner_token_a,
+            owner_token_b,
+            owner: position_keys.owner,
+            token_mint_a: pool_keys.token_mint_a,
+            token_mint_b: pool_keys.token_mint_b,
+            token_program_a: pool_keys.token_program_a,
+            token_program_b: pool_keys.token_program_b,
+            event_authority: event_authority(),
+            program: PROGRAM_ID,
+        },
+        instruction::ClaimRangeOrder {},
+        remaining_accounts,
+    )
+}
@@ -0,0 +1,422 @@
+use anchor_lang::prelude::{AccountMeta, Pubkey};
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::{InstructionData, ToAccountMetas};
+
+use crate::pda::*;
+use crate::{Pool, Position, PROGRAM_ID};
+
+pub mod admin;
+pub mod flash;
+pub mod liquidity;
+pub mod pool;
+pub mod reward;
+pub mod swap;
+
+pub use admin::*;
+pub use flash::*;
+pub use liquidity::*;
+pub use pool::*;
+pub use reward::*;
+pub use swap::*;
+
+// One builder per entry point of the program. Extra accounts the program reads from remaining_accounts
+// (crossed ticks, transfer hook accounts, route hops) are passed as `remaining_accounts` and appended as is.
+
+// Addresses of a pool and its token sides, token programs are not stored in the pool so they are passed in
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PoolKeys {
+    pub pool: Pubkey,
+    pub token_mint_a: Pubkey,
+    pub token_mint_b: Pubkey,
+    pub token_vault_a: Pubkey,
+    pub token_vault_b: Pubkey,
+    pub token_program_a: Pubkey,
+    pub token_program_b: Pubkey,
+}
+
+impl PoolKeys {
+    pub fn from_pool(pool_address: Pubkey, pool: &Pool, token_program_a: Pubkey, token_program_b: Pubkey) -> Self {
+        Self {
+            pool: pool_address,
+            token_mint_a: pool.token_a_mint,
+            token_mint_b: pool.token_b_mint,
+            token_vault_a: pool.token_a_vault,
+            token_vault_b: pool.token_b_vault,
+            token_program_a,
+            token_program_b,
+        }
+    }
+
+    pub fn observation(&self) -> Pubkey {
+        find_observation_address(&self.pool).0
+    }
+}
+
+// Addresses of a position and its range ticks, all derived from the pool, owner and bundle slot. A range order also
+// has the fill record it was placed into, which only the position itself tells
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PositionKeys {
+    pub owner: Pubkey,
+    pub position_bundle: Pubkey,
+    pub position: Pubkey,
+    pub bundle_index: u16,
+    pub tick_lower: i32,
+    pub tick_upper: i32,
+    pub lower_tick: Pubkey,
+    pub upper_tick: Pubkey,
+    pub range_order_fill: Option<Pubkey>,
+}
+
+impl PositionKeys {
+    pub fn new(pool: &Pubkey, owner: Pubkey, bundle_index: u16, tick_lower: i32, tick_upper: i32) -> Self {
+        let position_bundle = find_position_bundle_address(pool, &owner).0;
+        Self {
+            owner,
+            position_bundle,
+            position: find_position_address(&position_bundle, bundle_index).0,
+            bundle_index,
+            tick_lower,
+            tick_upper,
+            lower_tick: find_tick_address(pool, tick_lower).0,
+            upper_tick: find_tick_address(pool, tick_upper).0,
+            range_order_fill: None,
+        }
+    }
+
+    pub fn from_position(pool: &Pubkey, position: &Position) -> Self {
+        let mut keys = Self::new(pool, position.owner, position.bundle_index, position.tick_lower, position.tick_upper);
+        if position.range_order {
+            keys.range_order_fill = Some(
+                find_range_order_fill_address(
+                    pool,
+                    position.range_order_fill_tick(),
+                    position.range_order_sells_a,
+                    position.range_order_fills,
+                )
+                .0,
+            );
+        }
+        keys
+    }
+}
+
+// Fill records provide_liquidity touches for a range order: the one of the orders placed on its fill tick since
+// the last fill, and the one of the orders that fill settled
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RangeOrderKeys {
+    pub range_order_fill: Pubkey,
+    pub previous_range_order_fill: Pubkey,
+}
+
+impl RangeOrderKeys {
+    // fills is the fill tick's fill count in the order's direction, 0 when the tick does not exist yet. Orders
+    // above the price sell A and fill on their upper tick.
+    pub fn new(pool: &Pubkey, position_keys: &PositionKeys, tick_current: i32, fills: u64) -> Self {
+        let sells_a = tick_current < position_keys.tick_lower;
+        let fill_tick = if sells_a { position_keys.tick_upper } else { position_keys.tick_lower };
+        Self {
+            range_order_fill: find_range_order_fill_address(pool, fill_tick, sells_a, fills).0,
+            previous_range_order_fill: find_range_order_fill_address(pool, fill_tick, sells_a, fills.wrapping_sub(1)).0,
+        }
+    }
+}
+
+// Tick accounts in the order a swap crosses them, writable when the instruction updates them
+pub fn tick_account_metas(pool: &Pubkey, tick_indexes: &[i32], is_writable: bool) -> Vec<AccountMeta> {
+    tick_indexes
+        .iter()
+        .map(|&tick_index| {
+            let tick = find_tick_address(pool, tick_index).0;
+            if is_writable {
+                AccountMeta::new(tick, false)
+            } else {
+                AccountMeta::new_readonly(tick, false)
+            }
+        })
+        .collect()
+}
+
+// Accounts of one swap_route hop: its fixed accounts followed by the ticks it crosses
+pub fn route_hop_account_metas(pool_keys: &PoolKeys, tick_indexes: &[i32]) -> Vec<AccountMeta> {
+    let mut metas = vec![
+        AccountMeta::new(pool_keys.pool, false),
+        AccountMeta::new(pool_keys.observation(), false),
+        AccountMeta::new(pool_keys.token_vault_a, false),
+        AccountMeta::new(pool_keys.token_vault_b, false),
+        AccountMeta::new_readonly(pool_keys.token_mint_a, false),
+        AccountMeta::new_readonly(pool_keys.token_mint_b, false),
+        AccountMeta::new_readonly(pool_keys.token_program_a, false),
+        AccountMeta::new_readonly(pool_keys.token_program_b, false),
+    ];
+    metas.extend(tick_account_metas(&pool_keys.pool, tick_indexes, true));
+    metas
+}
+
+// The temporary wSOL account is only passed when a user token account is left out
+pub(crate) fn temp_wsol_account(user: &Pubkey, token_account_a: Option<Pubkey>, token_account_b: Option<Pubkey>) -> Option<Pubkey> {
+    if token_account_a.is_none() || token_account_b.is_none() {
+        Some(find_temp_wsol_address(user).0)
+    } else {
+        None
+    }
+}
+
+pub(crate) fn event_authority() -> Pubkey {
+    find_event_authority_address().0
+}
+
+pub(crate) fn build_instruction(
+    accounts: impl ToAccountMetas,
+    data: impl InstructionData,
+    remaining_accounts: &[AccountMeta],
+) -> Instruction {
+    let mut metas = accounts.to_account_metas(None);
+    metas.extend_from_slice(remaining_accounts);
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: metas,
+        data: data.data(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::*;
+    use crate::{DynamicFeeParams, RouteHop};
+    use anchor_lang::solana_program::sysvar;
+    use anchor_lang::system_program;
+
+    fn pool_keys() -> PoolKeys {
+        PoolKeys {
+            pool: find_pool_address(&MINT_A, &MINT_B).0,
+            token_mint_a: MINT_A,
+            token_mint_b: MINT_B,
+            token_vault_a: VAULT_A,
+            token_vault_b: VAULT_B,
+            token_program_a: TOKEN_PROGRAM,
+            token_program_b: TOKEN_PROGRAM,
+        }
+    }
+
+    fn position_keys() -> PositionKeys {
+        PositionKeys::new(&pool_keys().pool, OWNER, 3, -60, 120)
+    }
+
+    fn args(instruction: &Instruction) -> &[u8] {
+        &instruction.data[8..]
+    }
+
+    #[test]
+    fn every_builder_uses_its_discriminator() {
+        let pool_keys = pool_keys();
+        let position_keys = position_keys();
+        let pool = pool_keys.pool;
+        let dynamic_fee_params = DynamicFeeParams {
+            enabled: true,
+            filter_period: 30,
+            decay_period: 600,
+            reduction_factor: 5_000,
+            variable_fee_control: 40_000,
+            max_volatility_accumulator: 350_000,
+            max_fee: 10_000,
+        };
+
+        let builders = [
+            ("initialize_pool", initialize_pool(AUTHORITY, &pool_keys, 1 << 64, 10, 30, 0, 0)),
+            ("initialize_position_bundle", initialize_position_bundle(pool, OWNER)),
+            ("provide_liquidity", provide_liquidity(&pool_keys, &position_keys, Some(VAULT_A), Some(VAULT_B), 1, None, &[])),
+            ("remove_liquidity", remove_liquidity(&pool_keys, &position_keys, Some(VAULT_A), Some(VAULT_B), &[])),
+            ("swap", swap(&pool_keys, OWNER, Some(VAULT_A), Some(VAULT_B), 1, 0, true, &[])),
+            ("set_position_delegate", set_position_delegate(pool, &position_keys, Some(DELEGATE))),
+            ("transfer_position", transfer_position(pool, &position_keys, DELEGATE, 0)),
+            ("collect_fees", collect_fees(&pool_keys, &position_keys, OWNER, VAULT_A, VAULT_B, &[])),
+            ("compound_fees", compound_fees(pool, &position_keys, OWNER)),
+            ("rebalance_position", rebalance_position(pool, &position_keys, OWNER, -10, 10, 0, 0, true, &[])),
+            ("claim_range_order", claim_range_order(&pool_keys, &position_keys, VAULT_A, VAULT_B, &[])),
+            ("observe", observe(pool, vec![0, 60])),
+            ("increase_observation_cardinality", increase_observation_cardinality(pool, OWNER, 64)),
+            ("initialize_reward", initialize_reward(pool, AUTHORITY, REWARD_MINT, TOKEN_PROGRAM, 0)),
+            ("set_reward_emissions", set_reward_emissions(pool, AUTHORITY, 0, 1 << 64)),
+            ("collect_reward", collect_reward(pool, &position_keys, OWNER, REWARD_MINT, VAULT_A, TOKEN_PROGRAM, 0, &[])),
+            ("set_dynamic_fee", set_dynamic_fee(pool, AUTHORITY, dynamic_fee_params)),
+            ("initialize_config", initialize_config(AUTHORITY, DELEGATE, 86_400)),
+            ("set_pause_authority", set_pause_authority(AUTHORITY, DELEGATE)),
+            ("set_transfer_hook_allowlist", set_transfer_hook_allowlist(AUTHORITY, vec![TOKEN_PROGRAM])),
+            ("set_global_pause", set_global_pause(AUTHORITY, true)),
+            ("set_pool_pause_flags", set_pool_pause_flags(pool, AUTHORITY, 1)),
+            ("propose_fee_change", propose_fee_change(pool, AUTHORITY, 50)),
+            ("apply_fee_change", apply_fee_change(pool)),
+            ("flash_loan", flash_loan(&pool_keys, OWNER, VAULT_A, VAULT_B, 1, 2, &[])),
+            ("flash_repay", flash_repay(&pool_keys, OWNER, VAULT_A, VAULT_B, 1, 2, &[])),
+            ("flash_swap", flash_swap(&pool_keys, OWNER, VAULT_A, VAULT_B, DELEGATE, 1, true, vec![], &[])),
+            ("swap_route", swap_route(OWNER, VAULT_A, VAULT_B, 1, 0, vec![], &[])),
+            ("quote_swap", quote_swap(&pool_keys, 1, true, true, 0, &[])),
+        ];
+
+        for (name, instruction) in builders {
+            assert_eq!(instruction.program_id, PROGRAM_ID, "{name}");
+            assert_eq!(instruction.data[..8], discriminator("global", name), "{name}");
+        }
+    }
+
+    #[test]
+    fn swap_accounts_follow_program_order() {
+        let pool_keys = pool_keys();
+        let ticks = tick_account_metas(&pool_keys.pool, &[-100, -200], true);
+        let instruction = swap(&pool_keys, OWNER, Some(VAULT_B), Some(VAULT_A), 1_000, 990, true, &ticks);
+
+        let expected = vec![
+            AccountMeta::new(OWNER, true),
+            AccountMeta::new(VAULT_B, false),
+            AccountMeta::new(VAULT_A, false),
+            AccountMeta::new(pool_keys.pool, false),
+            AccountMeta::new_readonly(find_config_address().0, false),
+            AccountMeta::new(find_observation_address(&pool_keys.pool).0, false),
+            AccountMeta::new(VAULT_A, false),
+            AccountMeta::new(VAULT_B, false),
+            AccountMeta::new_readonly(MINT_A, false),
+            AccountMeta::new_readonly(MINT_B, false),
+            // No temporary wSOL account when both token accounts are given
+            AccountMeta::new_readonly(PROGRAM_ID, false),
+            AccountMeta::new_readonly(TOKEN_PROGRAM, false),
+            AccountMeta::new_readonly(TOKEN_PROGRAM, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(find_event_authority_address().0, false),
+            AccountMeta::new_readonly(PROGRAM_ID, false),
+            AccountMeta::new(find_tick_address(&pool_keys.pool, -100).0, false),
+            AccountMeta::new(find_tick_address(&pool_keys.pool, -200).0, false),
+        ];
+        assert_eq!(instruction.accounts, expected);
+
+        let mut expected_args = 1_000u64.to_le_bytes().to_vec();
+        expected_args.extend_from_slice(&990u64.to_le_bytes());
+        expected_args.push(1);
+        assert_eq!(args(&instruction), expected_args);
+    }
+
+    #[test]
+    fn native_sol_side_uses_temp_wsol_account() {
+        let pool_keys = pool_keys();
+        let instruction = swap(&pool_keys, OWNER, None, Some(VAULT_B), 1_000, 0, true, &[]);
+
+        // Omitted optional accounts are passed as the program id
+        assert_eq!(instruction.accounts[1], AccountMeta::new_readonly(PROGRAM_ID, false));
+        assert_eq!(instruction.accounts[2], AccountMeta::new(VAULT_B, false));
+        assert_eq!(instruction.accounts[10], AccountMeta::new(find_temp_wsol_address(&OWNER).0, false));
+
+        let position_keys = position_keys();
+        let instruction = provide_liquidity(&pool_keys, &position_keys, Some(VAULT_A), None, 1, None, &[]);
+        assert_eq!(instruction.accounts[8], AccountMeta::new_readonly(PROGRAM_ID, false));
+        assert_eq!(instruction.accounts[16], AccountMeta::new(find_temp_wsol_address(&OWNER).0, false));
+    }
+
+    #[test]
+    fn provide_liquidity_encodes_position_arguments() {
+        let position_keys = position_keys();
+        let range_order_keys = RangeOrderKeys::new(&pool_keys().pool, &position_keys, 200, 4);
+        let instruction =
+            provide_liquidity(&pool_keys(), &position_keys, Some(VAULT_A), Some(VAULT_B), 5_000_000, Some(range_order_keys), &[]);
+
+        let mut expected_args = Vec::new();
+        expected_args.extend_from_slice(&(-60i32).to_le_bytes());
+        expected_args.extend_from_slice(&120i32.to_le_bytes());
+        expected_args.extend_from_slice(&5_000_000u128.to_le_bytes());
+        expected_args.extend_from_slice(&3u16.to_le_bytes());
+        expected_args.push(1);
+        assert_eq!(args(&instruction), expected_args);
+
+        assert_eq!(instruction.accounts[2], AccountMeta::new(pool_keys().observation(), false));
+        assert_eq!(instruction.accounts[3], AccountMeta::new(position_keys.position_bundle, false));
+        assert_eq!(instruction.accounts[4], AccountMeta::new(position_keys.position, false));
+        assert_eq!(instruction.accounts[9], AccountMeta::new(position_keys.lower_tick, false));
+        assert_eq!(instruction.accounts[10], AccountMeta::new(position_keys.upper_tick, false));
+        assert_eq!(instruction.accounts[11], AccountMeta::new(range_order_keys.range_order_fill, false));
+        assert_eq!(instruction.accounts[12], AccountMeta::new(range_order_keys.previous_range_order_fill, false));
+        assert_eq!(instruction.accounts[13], AccountMeta::new(OWNER, true));
+        assert_eq!(instruction.accounts[20], AccountMeta::new_readonly(sysvar::rent::ID, false));
+    }
+
+    #[test]
+    fn swap_route_encodes_hops() {
+        let pool_keys = pool_keys();
+        let hop_accounts = route_hop_account_metas(&pool_keys, &[-100]);
+        assert_eq!(hop_accounts.len(), 9);
+        assert_eq!(hop_accounts[0], AccountMeta::new(pool_keys.pool, false));
+        assert_eq!(hop_accounts[1], AccountMeta::new(pool_keys.observation(), false));
+        assert_eq!(hop_accounts[7], AccountMeta::new_readonly(TOKEN_PROGRAM, false));
+        assert_eq!(hop_accounts[8], AccountMeta::new(find_tick_address(&pool_keys.pool, -100).0, false));
+
+        let hops = vec![
+            RouteHop { a_to_b: true, tick_account_count: 1 },
+            RouteHop { a_to_b: false, tick_account_count: 0 },
+        ];
+        let instruction = swap_route(OWNER, VAULT_A, VAULT_B, 500, 450, hops, &hop_accounts);
+
+        let mut expected_args = Vec::new();
+        expected_args.extend_from_slice(&500u64.to_le_bytes());
+        expected_args.extend_from_slice(&450u64.to_le_bytes());
+        expected_args.extend_from_slice(&2u32.to_le_bytes());
+        expected_args.extend_from_slice(&[1, 1, 0, 0]);
+        assert_eq!(args(&instruction), expected_args);
+        assert_eq!(instruction.accounts[6..], hop_accounts[..]);
+    }
+
+    #[test]
+    fn flash_loan_passes_instructions_sysvar() {
+        let instruction = flash_loan(&pool_keys(), OWNER, VAULT_A, VAULT_B, 10, 20, &[]);
+        assert_eq!(instruction.accounts[11], AccountMeta::new_readonly(sysvar::instructions::ID, false));
+
+        // flash_loan looks for a matching flash_repay, whose pool must come first
+        let repay = flash_repay(&pool_keys(), OWNER, VAULT_A, VAULT_B, 10, 20, &[]);
+        assert_eq!(repay.accounts[0].pubkey, pool_keys().pool);
+        assert_eq!(args(&repay), args(&instruction));
+    }
+
+    #[test]
+    fn position_keys_derive_from_pool_owner_and_slot() {
+        let pool = pool_keys().pool;
+        let position_keys = position_keys();
+        let position_bundle = find_position_bundle_address(&pool, &OWNER).0;
+
+        assert_eq!(position_keys.position_bundle, position_bundle);
+        assert_eq!(position_keys.position, find_position_address(&position_bundle, 3).0);
+        assert_eq!(position_keys.lower_tick, find_tick_address(&pool, -60).0);
+        assert_eq!(position_keys.upper_tick, find_tick_address(&pool, 120).0);
+        assert_eq!(position_keys.range_order_fill, None);
+    }
+
+    #[test]
+    fn range_orders_key_fill_records_by_fill_tick_and_count() {
+        let pool = pool_keys().pool;
+        let position_keys = PositionKeys::new(&pool, OWNER, 3, -60, 0);
+
+        // Below the price the order sells B and fills on its lower tick
+        let range_order_keys = RangeOrderKeys::new(&pool, &position_keys, 10, 4);
+        assert_eq!(range_order_keys.range_order_fill, find_range_order_fill_address(&pool, -60, false, 4).0);
+        assert_eq!(range_order_keys.previous_range_order_fill, find_range_order_fill_address(&pool, -60, false, 3).0);
+
+        // A placed order finds its record again from the position
+        let mut position = crate::decode_position(&position_data()).unwrap();
+        (position.tick_lower, position.tick_upper, position.bundle_index) = (-60, 0, 3);
+        (position.range_order, position.range_order_sells_a, position.range_order_fills) = (true, false, 4);
+        let keys = PositionKeys::from_position(&pool, &position);
+        assert_eq!(keys.position, position_keys.position);
+        assert_eq!(keys.range_order_fill, Some(range_order_keys.range_order_fill));
+
+        // Above the price it sells A and fills on its upper tick
+        let range_order_keys = RangeOrderKeys::new(&pool, &position_keys, -100, 0);
+        assert_eq!(range_order_keys.range_order_fill, find_range_order_fill_address(&pool, 0, true, 0).0);
+        position.range_order_sells_a = true;
+        position.range_order_fills = 0;
+        let keys = PositionKeys::from_position(&pool, &position);
+        assert_eq!(keys.range_order_fill, Some(range_order_keys.range_order_fill));
+    }
+
+    #[test]
+    fn pool_keys_from_decoded_pool() {
+        let pool = crate::decode_pool(&pool_data()).unwrap();
+        let address = find_pool_address(&MINT_A, &MINT_B).0;
+        assert_eq!(PoolKeys::from_pool(address, &pool, TOKEN_PROGRAM, TOKEN_PROGRAM), pool_keys());
+    }
+}
@@ -0,0 +1,139 @@
+use anchor_lang::prelude::{AccountMeta, Pubkey};
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::sysvar;
+use anchor_lang::system_program;
+use clmm_dex::{accounts, instruction};
+
+use crate::instructions::{build_instruction, event_authority, PoolKeys};
+use crate::pda::*;
+use crate::{DynamicFeeParams, PROGRAM_ID};
+
+// The pool address in pool_keys must be find_pool_address of its mints. Both vaults are fresh keypairs
+// that have to sign the transaction.
+pub fn initialize_pool(
+    admin: Pubkey,
+    pool_keys: &PoolKeys,
+    sqrt_price_x64: u128,
+    tick_spacing: u16,
+    fee: u8,
+    fee_growth_global_a: u128,
+    fee_growth_global_b: u128,
+) -> Instruction {
+    build_instruction(
+        accounts::InitializePool {
+            token_mint_a: pool_keys.token_mint_a,
+            token_mint_b: pool_keys.token_mint_b,
+            pool: pool_keys.pool,
+            observation: pool_keys.observation(),
+            token_vault_a: pool_keys.token_vault_a,
+            token_vault_b: pool_keys.token_vault_b,
+            admin,
+            system_program: system_program::ID,
+            token_program_a: pool_keys.token_program_a,
+            token_program_b: pool_keys.token_program_b,
+            rent: sysvar::rent::ID,
+            event_authority: event_authority(),
+            program: PROGRAM_ID,
+        },
+        instruction::InitializePool {
+            sqrt_price_x64,
+            tick_spacing,
+            fee,
+            fee_growth_global_a,
+            fee_growth_global_b,
+        },
+        &[],
+    )
+}
+
+pub fn observe(pool: Pubkey, seconds_agos: Vec<u32>) -> Instruction {
+    build_instruction(
+        accounts::Observe {
+            pool,
+            observation: find_observation_address(&pool).0,
+        },
+        instruction::Observe { seconds_agos },
+        &[],
+    )
+}
+
+// The payer funds the new slots, at most Observation::MAX_CARDINALITY_INCREASE of them per call
+pub fn increase_observation_cardinality(pool: Pubkey, payer: Pubkey, cardinality_next: u16) -> Instruction {
+    build_instruction(
+        accounts::IncreaseObservationCardinality {
+            pool,
+            observation: find_observation_address(&pool).0,
+            payer,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: PROGRAM_ID,
+        },
+        instruction::IncreaseObservationCardinality { cardinality_next },
+        &[],
+    )
+}
+
+pub fn set_dynamic_fee(pool: Pubkey, pool_authority: Pubkey, params: DynamicFeeParams) -> Instruction {
+    build_instruction(
+        accounts::SetDynamicFee {
+            config: find_config_address().0,
+            pool,
+            pool_authority,
+            event_authority: event_authority(),
+            program: PROGRAM_ID,
+        },
+        instruction::SetDynamicFee { params },
+        &[],
+    )
+}
+
+pub fn propose_fee_change(pool: Pubkey, pool_authority: Pubkey, new_fee: u8) -> Instruction {
+    build_instruction(
+        accounts::ProposeFeeChange {
+            config: find_config_address().0,
+            pool,
+            pool_authority,
+            event_authority: event_authority(),
+            program: PROGRAM_ID,
+        },
+        instruction::ProposeFeeChange { new_fee },
+        &[],
+    )
+}
+
+pub fn apply_fee_change(pool: Pubkey) -> Instruction {
+    build_instruction(
+        accounts::ApplyFeeChange {
+            pool,
+            event_authority: event_authority(),
+            program: PROGRAM_ID,
+        },
+        instruction::ApplyFeeChange {},
+        &[],
+    )
+}
+
+// Crossed ticks go in remaining_accounts as for swap, read-only is enough
+pub fn quote_swap(
+    pool_keys: &PoolKeys,
+    amount: u64,
+    a_to_b: bool,
+    exact_in: bool,
+    sqrt_price_limit: u128,
+    remaining_accounts: &[AccountMeta],
+) -> Instruction {
+    build_instruction(
+        accounts::QuoteSwap {
+            pool: pool_keys.pool,
+            token_mint_a: pool_keys.token_mint_a,
+            token_mint_b: pool_keys.token_mint_b,
+        },
+        instruction::QuoteSwap {
+            amount,
+            a_to_b,
+            exact_in,
+            sqrt_price_limit,
+        },
+        remaining_accounts,
+    )
+}
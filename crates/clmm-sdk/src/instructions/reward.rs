@@ -0,0 +1,88 @@
+use anchor_lang::prelude::{AccountMeta, Pubkey};
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::sysvar;
+use anchor_lang::system_program;
+use clmm_dex::{accounts, instruction};
+
+use crate::instructions::{build_instruction, event_authority, PositionKeys};
+use crate::pda::*;
+use crate::PROGRAM_ID;
+
+pub fn initialize_reward(
+    pool: Pubkey,
+    pool_authority: Pubkey,
+    reward_mint: Pubkey,
+    token_program: Pubkey,
+    reward_index: u8,
+) -> Instruction {
+    build_instruction(
+        accounts::InitializeReward {
+            pool,
+            reward_mint,
+            reward_vault: find_reward_vault_address(&pool, &reward_mint).0,
+            pool_authority,
+            system_program: system_program::ID,
+            token_program,
+            rent: sysvar::rent::ID,
+            event_authority: event_authority(),
+            program: PROGRAM_ID,
+        },
+        instruction::InitializeReward { reward_index },
+        &[],
+    )
+}
+
+pub fn set_reward_emissions(
+    pool: Pubkey,
+    pool_authority: Pubkey,
+    reward_index: u8,
+    emissions_per_second_x64: u128,
+) -> Instruction {
+    build_instruction(
+        accounts::SetRewardEmissions {
+            pool,
+            pool_authority,
+            event_authority: event_authority(),
+            program: PROGRAM_ID,
+        },
+        instruction::SetRewardEmissions {
+            reward_index,
+            emissions_per_second_x64,
+        },
+        &[],
+    )
+}
+
+// The authority is the owner or the position delegate. Range orders need the keys from PositionKeys::from_position
+#[allow(clippy::too_many_arguments)]
+pub fn collect_reward(
+    pool: Pubkey,
+    position_keys: &PositionKeys,
+    authority: Pubkey,
+    reward_mint: Pubkey,
+    owner_reward_token: Pubkey,
+    token_program: Pubkey,
+    reward_index: u8,
+    remaining_accounts: &[AccountMeta],
+) -> Instruction {
+    build_instruction(
+        accounts::CollectReward {
+            pool,
+            config: find_config_address().0,
+            position_bundle: position_keys.position_bundle,
+            position: position_keys.position,
+            lower_tick: position_keys.lower_tick,
+            upper_tick: position_keys.upper_tick,
+            range_order_fill: position_keys.range_order_fill,
+            reward_vault: find_reward_vault_address(&pool, &reward_mint).0,
+            reward_mint,
+            owner_reward_token,
+            authority,
+            token_program,
+            event_authority: event_authority(),
+            program: PROGRAM_ID,
+        },
+        instruction::CollectReward { reward_index },
+        remaining_accounts,
+    )
+}
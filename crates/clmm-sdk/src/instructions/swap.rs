@@ -0,0 +1,74 @@
+use anchor_lang::prelude::{AccountMeta, Pubkey};
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::system_program;
+use clmm_dex::{accounts, instruction};
+
+use crate::instructions::{build_instruction, event_authority, temp_wsol_account, PoolKeys};
+use crate::pda::*;
+use crate::{RouteHop, PROGRAM_ID};
+
+// Leave a user token account out on a native SOL side to pay or receive lamports. min_amount_out bounds what the
+// user receives after the output mint's transfer fee.
+// remaining_accounts holds the crossed ticks (see tick_account_metas) followed by transfer hook accounts.
+#[allow(clippy::too_many_arguments)]
+pub fn swap(
+    pool_keys: &PoolKeys,
+    user: Pubkey,
+    user_token_a: Option<Pubkey>,
+    user_token_b: Option<Pubkey>,
+    amount_in: u64,
+    min_amount_out: u64,
+    a_to_b: bool,
+    remaining_accounts: &[AccountMeta],
+) -> Instruction {
+    build_instruction(
+        accounts::Swap {
+            user,
+            user_token_a_account: user_token_a,
+            user_token_b_account: user_token_b,
+            pool: pool_keys.pool,
+            config: find_config_address().0,
+            observation: pool_keys.observation(),
+            pool_token_a_vault: pool_keys.token_vault_a,
+            pool_token_b_vault: pool_keys.token_vault_b,
+            token_mint_a: pool_keys.token_mint_a,
+            token_mint_b: pool_keys.token_mint_b,
+            temp_wsol_account: temp_wsol_account(&user, user_token_a, user_token_b),
+            token_program_a: pool_keys.token_program_a,
+            token_program_b: pool_keys.token_program_b,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: PROGRAM_ID,
+        },
+        instruction::Swap { amount_in, min_amount_out, a_to_b },
+        remaining_accounts,
+    )
+}
+
+// remaining_accounts holds one group per hop (see route_hop_account_metas) followed by transfer hook accounts
+pub fn swap_route(
+    user: Pubkey,
+    user_token_in: Pubkey,
+    user_token_out: Pubkey,
+    amount_in: u64,
+    min_amount_out: u64,
+    hops: Vec<RouteHop>,
+    remaining_accounts: &[AccountMeta],
+) -> Instruction {
+    build_instruction(
+        accounts::SwapRoute {
+            user,
+            user_token_in,
+            user_token_out,
+            config: find_config_address().0,
+            event_authority: event_authority(),
+            program: PROGRAM_ID,
+        },
+        instruction::SwapRoute {
+            amount_in,
+            min_amount_out,
+            hops,
+        },
+        remaining_accounts,
+    )
+}
@@ -0,0 +1,22 @@
+// Client side of the CLMM DEX program. Layouts, discriminators and account orders come from the program crate
+// itself, so they cannot drift from what is deployed.
+
+pub mod decode;
+pub mod instructions;
+pub mod local;
+pub mod pda;
+pub mod quote;
+
+#[cfg(test)]
+mod fixtures;
+
+pub use clmm_dex::ID as PROGRAM_ID;
+pub use clmm_dex::instructions::{DynamicFeeParams, ObserveResult, RouteHop, SwapQuote};
+pub use clmm_dex::state::{
+    Config, DynamicFee, Observation, ObservationEntry, Pool, Position, PositionBundle, PositionRewardInfo,
+    RangeOrderFill, RewardInfo, Tick, NUM_REWARDS,
+};
+pub use clmm_math;
+pub use decode::*;
+pub use pda::*;
+pub use quote::{Quote, Quoter};
@@ -0,0 +1,74 @@
+use anchor_lang::error::Error;
+use anchor_lang::prelude::*;
+use clmm_dex::errors::Errors;
+use clmm_dex::instructions::swap::swap_internal;
+use clmm_dex::math::dynamic_fee_math::update_volatility_accumulator;
+
+use crate::{Pool, Tick, PROGRAM_ID, TICK_SEED};
+
+// The program's own swap over accounts held in memory, for simulations and tests that have to match it exactly
+#[derive(Clone)]
+pub struct LocalSwap {
+    pub amount_in: u128, // Consumed by the pool, swap fee included
+    pub amount_out: u128,
+    pub fee_amount: u128,
+    pub pool: Pool, // State after the swap
+    pub ticks: Vec<Tick>, // The ticks passed in, as the swap left them
+}
+
+// Runs swap_internal with the volatility update swap_handler makes first. Ticks are the swap's tick accounts in the
+// order the program reads them and need their bump, amounts are pool side.
+pub fn swap_on_chain(
+    pool_address: &Pubkey,
+    pool: &Pool,
+    ticks: &[Tick],
+    amount_in: u64,
+    a_to_b: bool,
+    timestamp: i64,
+) -> Result<LocalSwap> {
+    let owner = PROGRAM_ID;
+    let mut pool_lamports = 1;
+    let mut pool_data = Vec::new();
+    pool.try_serialize(&mut pool_data)?;
+    let pool_info = AccountInfo::new(pool_address, false, true, &mut pool_lamports, &mut pool_data, &owner, false, 0);
+    let mut pool_account = Account::<Pool>::try_from(&pool_info)?;
+
+    let mut storage = Vec::with_capacity(ticks.len());
+    for tick in ticks {
+        let address = Pubkey::create_program_address(
+            &[TICK_SEED, pool_address.as_ref(), &tick.tick_index.to_le_bytes(), &[tick.bump]],
+            &PROGRAM_ID,
+        )
+        .map_err(|_| Errors::InvalidTick)?;
+        let mut data = Vec::new();
+        tick.try_serialize(&mut data)?;
+        storage.push((address, 1u64, data));
+    }
+    let tick_infos: Vec<AccountInfo> = storage
+        .iter_mut()
+        .map(|(key, lamports, data)| AccountInfo::new(key, false, true, lamports, data, &owner, false, 0))
+        .collect();
+
+    update_volatility_accumulator(&mut pool_account, timestamp)?;
+    let (amount_in, amount_out, fee_amount) = swap_internal(&mut pool_account, &tick_infos, amount_in, a_to_b, timestamp)?;
+    let ticks = tick_infos
+        .iter()
+        .map(|tick_info| Tick::try_deserialize(&mut &tick_info.data.borrow()[..]))
+        .collect::<Result<Vec<Tick>>>()?;
+
+    Ok(LocalSwap {
+        amount_in,
+        amount_out,
+        fee_amount,
+        pool: (*pool_account).clone(),
+        ticks,
+    })
+}
+
+// Numeric code of an error, program errors by their u64 encoding, to compare failures across implementations
+pub fn error_code(error: &Error) -> u64 {
+    match error {
+        Error::AnchorError(error) => error.error_code_number.into(),
+        Error::ProgramError(error) => error.program_error.clone().into(),
+    }
+}
@@ -0,0 +1,157 @@
+use anchor_lang::prelude::Pubkey;
+#[allow(deprecated)]
+use anchor_lang::solana_program::bpf_loader_upgradeable;
+
+use crate::PROGRAM_ID;
+
+// Seeds of every program derived address, as used in the program's account constraints
+pub const CONFIG_SEED: &[u8] = b"config";
+pub const POOL_SEED: &[u8] = b"pool";
+pub const OBSERVATION_SEED: &[u8] = b"observation";
+pub const TICK_SEED: &[u8] = b"tick";
+pub const POSITION_BUNDLE_SEED: &[u8] = b"position_bundle";
+pub const POSITION_SEED: &[u8] = b"position";
+pub const REWARD_VAULT_SEED: &[u8] = b"reward_vault";
+pub const RANGE_ORDER_FILL_SEED: &[u8] = b"range_order_fill";
+pub const TEMP_WSOL_SEED: &[u8] = b"wsol";
+pub const EVENT_AUTHORITY_SEED: &[u8] = b"__event_authority";
+
+pub fn find_config_address() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[CONFIG_SEED], &PROGRAM_ID)
+}
+
+pub fn find_pool_address(token_mint_a: &Pubkey, token_mint_b: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[POOL_SEED, token_mint_a.as_ref(), token_mint_b.as_ref()], &PROGRAM_ID)
+}
+
+pub fn find_observation_address(pool: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[OBSERVATION_SEED, pool.as_ref()], &PROGRAM_ID)
+}
+
+pub fn find_tick_address(pool: &Pubkey, tick_index: i32) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[TICK_SEED, pool.as_ref(), &tick_index.to_le_bytes()], &PROGRAM_ID)
+}
+
+pub fn find_position_bundle_address(pool: &Pubkey, owner: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[POSITION_BUNDLE_SEED, pool.as_ref(), owner.as_ref()], &PROGRAM_ID)
+}
+
+pub fn find_position_address(position_bundle: &Pubkey, bundle_index: u16) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[POSITION_SEED, position_bundle.as_ref(), &bundle_index.to_le_bytes()],
+        &PROGRAM_ID,
+    )
+}
+
+pub fn find_reward_vault_address(pool: &Pubkey, reward_mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[REWARD_VAULT_SEED, pool.as_ref(), reward_mint.as_ref()], &PROGRAM_ID)
+}
+
+// Record of the range orders on one side of a fill tick placed while it had filled `fills` times
+pub fn find_range_order_fill_address(pool: &Pubkey, tick_index: i32, sells_a: bool, fills: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[RANGE_ORDER_FILL_SEED, pool.as_ref(), &tick_index.to_le_bytes(), &[sells_a as u8], &fills.to_le_bytes()],
+        &PROGRAM_ID,
+    )
+}
+
+// Account swap, provide_liquidity and remove_liquidity wrap native SOL in when a user token account is omitted
+pub fn find_temp_wsol_address(user: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[TEMP_WSOL_SEED, user.as_ref()], &PROGRAM_ID)
+}
+
+// Signer of the self-CPI that carries events
+pub fn find_event_authority_address() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[EVENT_AUTHORITY_SEED], &PROGRAM_ID)
+}
+
+// Program data account of the upgradeable loader, initialize_config checks its upgrade authority
+pub fn find_program_data_address() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PROGRAM_ID.as_ref()], &bpf_loader_upgradeable::ID)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::*;
+    use std::str::FromStr;
+
+    fn assert_derived(found: (Pubkey, u8), seeds: &[&[u8]], program_id: &Pubkey, expected: &str) {
+        let (address, bump) = found;
+        assert_eq!(address, Pubkey::from_str(expected).unwrap());
+        let mut seeds = seeds.to_vec();
+        let bump = [bump];
+        seeds.push(&bump);
+        assert_eq!(Pubkey::create_program_address(&seeds, program_id).unwrap(), address);
+    }
+
+    #[test]
+    fn derives_fixture_addresses() {
+        let pool = find_pool_address(&MINT_A, &MINT_B).0;
+        let position_bundle = find_position_bundle_address(&pool, &OWNER).0;
+
+        assert_derived(find_config_address(), &[b"config"], &PROGRAM_ID, "8CreFUVJCN7Bbkaei1bczyMXREVXxDaEjUh3H4m6gyBP");
+        assert_derived(
+            find_pool_address(&MINT_A, &MINT_B),
+            &[b"pool", MINT_A.as_ref(), MINT_B.as_ref()],
+            &PROGRAM_ID,
+            "9NxKYi4oqWNbkzg2kEkLyJDhCnZ4HhjfWMAGfcJrdS6U",
+        );
+        assert_derived(
+            find_observation_address(&pool),
+            &[b"observation", pool.as_ref()],
+            &PROGRAM_ID,
+            "D8eiyFQBM1vqChvZbVU29PpBjyvf8HPKiXosK19dT17Z",
+        );
+        assert_derived(
+            find_tick_address(&pool, -60),
+            &[b"tick", pool.as_ref(), &(-60i32).to_le_bytes()],
+            &PROGRAM_ID,
+            "ARANj9EZtq8KD79YdbmiRz2x2BNJAKcaSYe2RqTz6AZj",
+        );
+        assert_derived(
+            find_position_bundle_address(&pool, &OWNER),
+            &[b"position_bundle", pool.as_ref(), OWNER.as_ref()],
+            &PROGRAM_ID,
+            "2T17MuTAcD4UpTwrHkCxRQ5Z2qPp9HtkuEYvLfTFPhGc",
+        );
+        assert_derived(
+            find_position_address(&position_bundle, 3),
+            &[b"position", position_bundle.as_ref(), &3u16.to_le_bytes()],
+            &PROGRAM_ID,
+            "2MEHb295MSFaEMZfHCEcPMgn3bAXZ3aK6nyjmoCNiWJf",
+        );
+        assert_derived(
+            find_reward_vault_address(&pool, &REWARD_MINT),
+            &[b"reward_vault", pool.as_ref(), REWARD_MINT.as_ref()],
+            &PROGRAM_ID,
+            "4oDLVh4AwEqoMcsfLN6ppMZ45aEQ7werPDtACcrygyrD",
+        );
+        assert_derived(
+            find_temp_wsol_address(&OWNER),
+            &[b"wsol", OWNER.as_ref()],
+            &PROGRAM_ID,
+            "iyTymoNfBpRtemvxNBBXwxMHDw3Kqb9gA7H3EfoKKwJ",
+        );
+        assert_derived(
+            find_event_authority_address(),
+            &[b"__event_authority"],
+            &PROGRAM_ID,
+            "8GLmkn2r9HxVfist5XX72GJx2vQsYtrGhDKG7M1GqDux",
+        );
+        #[allow(deprecated)]
+        let loader = bpf_loader_upgradeable::ID;
+        assert_derived(find_program_data_address(), &[PROGRAM_ID.as_ref()], &loader, "GxhwAfYe5izNxsb1a6zZXtw45WQrxMwwvSgX1ov4r463");
+    }
+
+    #[test]
+    fn tick_seeds_distinguish_sign() {
+        let pool = find_pool_address(&MINT_A, &MINT_B).0;
+        assert_ne!(find_tick_address(&pool, 60).0, find_tick_address(&pool, -60).0);
+    }
+
+    #[test]
+    fn pool_seeds_are_ordered() {
+        assert_ne!(find_pool_address(&MINT_A, &MINT_B).0, find_pool_address(&MINT_B, &MINT_A).0);
+    }
+}
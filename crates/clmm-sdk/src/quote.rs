@@ -0,0 +1,427 @@
+use std::collections::BTreeMap;
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::AccountMeta;
+use clmm_dex::errors::Errors;
+use clmm_dex::math::dynamic_fee_math::get_effective_fee;
+use clmm_math::tick_math::get_sqrt_price_from_tick;
+
+use crate::local::swap_on_chain;
+use crate::{find_tick_address, Pool, Tick};
+
+// Runs the program's swap_internal over fetched pool and tick accounts and reports the tick accounts it crossed.
+// Amounts are pool side, like in the Swapped event: mint transfer fees are not applied.
+pub struct Quoter {
+    pool_address: Pubkey,
+    pool: Pool,
+    ticks: BTreeMap<i32, Tick>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Quote {
+    pub amount_in: u64, // Consumed by the pool, swap fee included
+    pub amount_out: u64,
+    pub fee_amount: u64,
+    pub fee_rate: u128, // Effective fee rate of the swap in millionths, dynamic fee included
+    pub price_impact: f64, // Shortfall of the execution price against the starting price, swap fee excluded
+    pub sqrt_price: u128, // Pool state after the swap
+    pub tick_current: i32,
+    pub liquidity: u128,
+    pub tick_accounts: Vec<Pubkey>, // Remaining accounts of the swap, in the order the program reads them
+}
+
+impl Quote {
+    // Tick accounts as passed to instructions::swap
+    pub fn tick_account_metas(&self) -> Vec<AccountMeta> {
+        self.tick_accounts
+            .iter()
+            .map(|tick| AccountMeta::new(*tick, false))
+            .collect()
+    }
+}
+
+impl Quoter {
+    // Ticks may include accounts the swap never reaches, only the crossed ones are needed
+    pub fn new(pool_address: Pubkey, pool: Pool, ticks: impl IntoIterator<Item = Tick>) -> Self {
+        let ticks = ticks.into_iter().map(|tick| (tick.tick_index, tick)).collect();
+        Self {
+            pool_address,
+            pool,
+            ticks,
+        }
+    }
+
+    // Addresses of every initialized tick of the pool, to fetch before building a Quoter
+    pub fn tick_addresses(pool_address: &Pubkey, pool: &Pool) -> Vec<Pubkey> {
+        pool.initialized_ticks
+            .iter()
+            .map(|tick| find_tick_address(pool_address, *tick).0)
+            .collect()
+    }
+
+    pub fn pool(&self) -> &Pool {
+        &self.pool
+    }
+
+    // Quotes swap(amount_in, a_to_b) landing at timestamp, which the dynamic fee depends on.
+    // Fails with the error the program would return, TickNotFound when the amount runs past the last tick.
+    pub fn quote(&self, amount_in: u64, a_to_b: bool, timestamp: i64) -> Result<Quote> {
+        let candidates = self.ticks_in_order(a_to_b);
+        let ticks: Vec<Tick> = candidates.iter().map(|(tick_index, _)| self.ticks[tick_index].clone()).collect();
+        let swap = swap_on_chain(&self.pool_address, &self.pool, &ticks, amount_in, a_to_b, timestamp)?;
+
+        // The swap read the ticks it crossed, each with its partner. The price only rests on a tick it has crossed,
+        // even when the swap runs out right there
+        let sqrt_price = swap.pool.sqrt_price;
+        let mut tick_accounts = Vec::new();
+        for (tick_index, partner) in candidates {
+            let tick_sqrt_price = get_sqrt_price_from_tick(tick_index).map_err(Errors::from)?;
+            let crossed = amount_in > 0 && if a_to_b { sqrt_price <= tick_sqrt_price } else { sqrt_price >= tick_sqrt_price };
+            if !partner && !crossed {
+                break;
+            }
+            tick_accounts.push(find_tick_address(&self.pool_address, tick_index).0);
+        }
+
+        let amount_in = u64::try_from(swap.amount_in).map_err(|_| Errors::TokenMaxExceeded)?;
+        let amount_out = u64::try_from(swap.amount_out).map_err(|_| Errors::TokenMaxExceeded)?;
+        let fee_amount = u64::try_from(swap.fee_amount).map_err(|_| Errors::TokenMaxExceeded)?;
+
+        Ok(Quote {
+            amount_in,
+            amount_out,
+            fee_amount,
+            fee_rate: get_effective_fee(&swap.pool, timestamp),
+            price_impact: price_impact(self.pool.sqrt_price, amount_in.saturating_sub(fee_amount), amount_out, a_to_b),
+            sqrt_price,
+            tick_current: swap.pool.tick_current,
+            liquidity: swap.pool.liquidity,
+            tick_accounts,
+        })
+    }
+
+    // Initialized ticks in swap direction as (tick_index, is_partner), each followed by its partner when it fills
+    // range orders. Stops at the first tick without data, the swap then fails there like the program would.
+    fn ticks_in_order(&self, a_to_b: bool) -> Vec<(i32, bool)> {
+        let tick_current = self.pool.tick_current;
+        let tick_spacing = self.pool.tick_spacing as i32;
+        let mut tick_indexes: Vec<i32> = self
+            .pool
+            .initialized_ticks
+            .iter()
+            .copied()
+            .filter(|tick_index| if a_to_b { *tick_index <= tick_current } else { *tick_index > tick_current })
+            .collect();
+        tick_indexes.sort_unstable();
+        if a_to_b {
+            tick_indexes.reverse();
+        }
+
+        let mut candidates = Vec::new();
+        for tick_index in tick_indexes {
+            let Some(tick) = self.ticks.get(&tick_index) else {
+                break;
+            };
+            candidates.push((tick_index, false));
+            let (range_order_liquidity, partner_tick) = if a_to_b {
+                (tick.range_order_liquidity_down, tick_index.checked_add(tick_spacing))
+            } else {
+                (tick.range_order_liquidity_up, tick_index.checked_sub(tick_spacing))
+            };
+            if range_order_liquidity > 0 {
+                match partner_tick.filter(|partner_tick| self.ticks.contains_key(partner_tick)) {
+                    Some(partner_tick) => candidates.push((partner_tick, true)),
+                    None => break,
+                }
+            }
+        }
+        candidates
+    }
+}
+
+// 1 - execution price / starting price, both as output per unit of input
+fn price_impact(sqrt_price_before: u128, amount_in_less_fee: u64, amount_out: u64, a_to_b: bool) -> f64 {
+    if amount_in_less_fee == 0 {
+        return 0.0;
+    }
+    let sqrt_price = sqrt_price_before as f64 / (1u128 << 64) as f64;
+    let spot_price = if a_to_b { sqrt_price * sqrt_price } else { 1.0 / (sqrt_price * sqrt_price) };
+    let execution_price = amount_out as f64 / amount_in_less_fee as f64;
+
+    1.0 - execution_price / spot_price
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::*;
+    use crate::local::{error_code, LocalSwap};
+    use clmm_math::tick_math::get_tick_from_sqrt_price;
+    use crate::{decode_pool, find_pool_address};
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    const CASES: usize = 2_000;
+
+    struct Scenario {
+        pool_address: Pubkey,
+        pool: Pool,
+        ticks: Vec<Tick>,
+    }
+
+    fn tick(pool_address: &Pubkey, tick_index: i32) -> Tick {
+        Tick {
+            tick_index,
+            liquidity_net: 0,
+            fee_growth_outside_a: 0,
+            fee_growth_outside_b: 0,
+            initialized: true,
+            bump: find_tick_address(pool_address, tick_index).1,
+            range_order_liquidity_up: 0,
+            range_order_liquidity_down: 0,
+            range_order_fills_up: 0,
+            range_order_fills_down: 0,
+            range_order_fee_growth_up_a: 0,
+            range_order_fee_growth_up_b: 0,
+            range_order_fee_growth_down_a: 0,
+            range_order_fee_growth_down_b: 0,
+            range_order_reward_growths_up: [0; 3],
+            range_order_reward_growths_down: [0; 3],
+            reward_growths_outside: [0; 3],
+        }
+    }
+
+    // Positions with random ranges around a random price, some of them one spacing wide with range orders on top
+    fn random_scenario(rng: &mut StdRng) -> Scenario {
+        let pool_address = find_pool_address(&MINT_A, &MINT_B).0;
+        let tick_spacing: i32 = [1, 8, 64][rng.gen_range(0..3)];
+
+        let mut ticks: BTreeMap<i32, Tick> = BTreeMap::new();
+        let mut ranges = Vec::new();
+        for _ in 0..rng.gen_range(1..8) {
+            let lower = rng.gen_range(-40..40) * tick_spacing;
+            let upper = lower + rng.gen_range(1..20) * tick_spacing;
+            let digits = rng.gen_range(3..13);
+            let liquidity: u128 = rng.gen_range(1..=10u128.pow(digits));
+            ticks.entry(lower).or_insert_with(|| tick(&pool_address, lower)).liquidity_net += liquidity as i128;
+            ticks.entry(upper).or_insert_with(|| tick(&pool_address, upper)).liquidity_net -= liquidity as i128;
+            ranges.push((lower, upper, liquidity));
+
+            if upper - lower == tick_spacing && rng.gen_bool(0.5) {
+                if rng.gen_bool(0.5) {
+                    ticks.get_mut(&lower).unwrap().range_order_liquidity_down += liquidity;
+                } else {
+                    ticks.get_mut(&upper).unwrap().range_order_liquidity_up += liquidity;
+                }
+            }
+        }
+
+        // Anywhere within a tick, ranges may or may not cover it
+        let start_tick = rng.gen_range(-45..45) * tick_spacing + rng.gen_range(0..tick_spacing);
+        let start_sqrt_price = get_sqrt_price_from_tick(start_tick).unwrap();
+        let next_sqrt_price = get_sqrt_price_from_tick(start_tick + 1).unwrap();
+        let sqrt_price = rng.gen_range(start_sqrt_price..next_sqrt_price);
+        let tick_current = get_tick_from_sqrt_price(sqrt_price).unwrap();
+
+        let mut pool = decode_pool(&pool_data()).unwrap();
+        pool.sqrt_price = sqrt_price;
+        pool.tick_current = tick_current;
+        pool.tick_spacing = tick_spacing as u16;
+        pool.liquidity = ranges
+            .iter()
+            .filter(|(lower, upper, _)| *lower <= tick_current && tick_current < *upper)
+            .map(|(_, _, liquidity)| liquidity)
+            .sum();
+        pool.initialized_ticks = ticks.keys().copied().collect();
+        pool.fee = rng.gen();
+        pool.pending_fee = rng.gen();
+        pool.pending_fee_activation = rng.gen_range(0..3) * 1_000;
+        pool.dynamic_fee.enabled = rng.gen_bool(0.5);
+        pool.dynamic_fee.filter_period = rng.gen_range(0..100);
+        pool.dynamic_fee.decay_period = rng.gen_range(100..1_000);
+        pool.dynamic_fee.reduction_factor = rng.gen_range(0..=10_000);
+        pool.dynamic_fee.variable_fee_control = rng.gen_range(0..100_000);
+        pool.dynamic_fee.max_volatility_accumulator = rng.gen_range(0..1_000_000);
+        pool.dynamic_fee.max_fee = rng.gen_range(0..100_000);
+        pool.dynamic_fee.volatility_accumulator = rng.gen_range(0..1_000_000);
+        pool.dynamic_fee.volatility_reference = rng.gen_range(0..1_000_000);
+        pool.dynamic_fee.tick_reference = tick_current + rng.gen_range(-10..10) * tick_spacing;
+        pool.dynamic_fee.last_update_timestamp = rng.gen_range(0..2_000);
+
+        Scenario {
+            pool_address,
+            pool,
+            ticks: ticks.into_values().collect(),
+        }
+    }
+
+    // Every tick in swap direction, each followed by its partner when it fills range orders
+    fn tick_accounts_in_order(scenario: &Scenario, a_to_b: bool) -> Vec<Pubkey> {
+        let mut ticks: Vec<&Tick> = scenario
+            .ticks
+            .iter()
+            .filter(|tick| if a_to_b { tick.tick_index <= scenario.pool.tick_current } else { tick.tick_index > scenario.pool.tick_current })
+            .collect();
+        if a_to_b {
+            ticks.reverse();
+        }
+        let tick_spacing = scenario.pool.tick_spacing as i32;
+        let mut accounts = Vec::new();
+        for tick in ticks {
+            accounts.push(find_tick_address(&scenario.pool_address, tick.tick_index).0);
+            if a_to_b && tick.range_order_liquidity_down > 0 {
+                accounts.push(find_tick_address(&scenario.pool_address, tick.tick_index + tick_spacing).0);
+            }
+            if !a_to_b && tick.range_order_liquidity_up > 0 {
+                accounts.push(find_tick_address(&scenario.pool_address, tick.tick_index - tick_spacing).0);
+            }
+        }
+        accounts
+    }
+
+    // The scenario's ticks behind the given tick accounts, in the same order
+    fn ticks_at(scenario: &Scenario, tick_accounts: &[Pubkey]) -> Vec<Tick> {
+        tick_accounts
+            .iter()
+            .map(|address| {
+                scenario
+                    .ticks
+                    .iter()
+                    .find(|tick| find_tick_address(&scenario.pool_address, tick.tick_index).0 == *address)
+                    .unwrap()
+                    .clone()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn matches_swap_internal_on_random_pools() {
+        let mut rng = StdRng::seed_from_u64(0x636c_6d6d);
+        let mut swaps = 0;
+        let mut crossings = 0;
+
+        for case in 0..CASES {
+            let scenario = random_scenario(&mut rng);
+            let a_to_b = rng.gen_bool(0.5);
+            let digits = rng.gen_range(1..10);
+            let amount_in = rng.gen_range(1..10u64.pow(digits));
+            let timestamp = rng.gen_range(2_000..4_000);
+
+            let quoter = Quoter::new(scenario.pool_address, scenario.pool.clone(), scenario.ticks.clone());
+            match quoter.quote(amount_in, a_to_b, timestamp) {
+                Ok(quote) => {
+                    // The quoted tick accounts alone must be enough for the program
+                    let ticks = ticks_at(&scenario, &quote.tick_accounts);
+                    let LocalSwap { amount_in: consumed, amount_out, fee_amount, pool, .. } =
+                        swap_on_chain(&scenario.pool_address, &scenario.pool, &ticks, amount_in, a_to_b, timestamp)
+                            .unwrap_or_else(|error| panic!("case {case}: program failed with {error}"));
+                    assert_eq!(quote.amount_in as u128, consumed, "case {case}");
+                    assert_eq!(quote.amount_out as u128, amount_out, "case {case}");
+                    assert_eq!(quote.fee_amount as u128, fee_amount, "case {case}");
+                    assert_eq!(quote.sqrt_price, pool.sqrt_price, "case {case}");
+                    assert_eq!(quote.tick_current, pool.tick_current, "case {case}");
+                    assert_eq!(quote.liquidity, pool.liquidity, "case {case}");
+                    assert_eq!(quote.fee_rate, get_effective_fee(&pool, timestamp), "case {case}");
+                    assert_eq!(quote.tick_accounts[..], tick_accounts_in_order(&scenario, a_to_b)[..quote.tick_accounts.len()]);
+                    swaps += 1;
+                    crossings += quote.tick_accounts.len();
+                }
+                Err(quote_error) => {
+                    let ticks = ticks_at(&scenario, &tick_accounts_in_order(&scenario, a_to_b));
+                    let program_error = swap_on_chain(&scenario.pool_address, &scenario.pool, &ticks, amount_in, a_to_b, timestamp)
+                        .err()
+                        .unwrap_or_else(|| panic!("case {case}: program succeeded where the quote failed with {quote_error}"));
+                    assert_eq!(error_code(&quote_error), error_code(&program_error), "case {case}");
+                }
+            }
+        }
+
+        // Make sure the generator exercises both outcomes and multi-tick swaps
+        assert!(swaps > CASES / 4, "only {swaps} successful swaps");
+        assert!(crossings > CASES / 2, "only {crossings} tick crossings");
+    }
+
+    #[test]
+    fn quote_fails_without_crossed_tick_data() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let scenario = loop {
+            let scenario = random_scenario(&mut rng);
+            let quoter = Quoter::new(scenario.pool_address, scenario.pool.clone(), scenario.ticks.clone());
+            if quoter.quote(1_000, true, 3_000).is_ok_and(|quote| !quote.tick_accounts.is_empty()) {
+                break scenario;
+            }
+        };
+
+        let quoter = Quoter::new(scenario.pool_address, scenario.pool.clone(), Vec::new());
+        let error = quoter.quote(1_000, true, 3_000).unwrap_err();
+        assert_eq!(error, Errors::TickNotFound.into());
+    }
+
+    #[test]
+    fn price_impact_grows_with_size() {
+        let pool_address = find_pool_address(&MINT_A, &MINT_B).0;
+        let mut pool = decode_pool(&pool_data()).unwrap();
+        pool.dynamic_fee.enabled = false;
+        pool.pending_fee_activation = 0;
+        pool.initialized_ticks = vec![-1_000, 1_000];
+        pool.tick_current = 0;
+        pool.sqrt_price = get_sqrt_price_from_tick(0).unwrap();
+        pool.liquidity = 1_000_000_000_000;
+        let mut lower = tick(&pool_address, -1_000);
+        lower.liquidity_net = 1_000_000_000_000;
+        let mut upper = tick(&pool_address, 1_000);
+        upper.liquidity_net = -1_000_000_000_000;
+        let quoter = Quoter::new(pool_address, pool, [lower, upper]);
+
+        let small = quoter.quote(1_000_000, true, 0).unwrap();
+        let large = quoter.quote(10_000_000_000, true, 0).unwrap();
+        assert!(small.price_impact < 0.0001, "{}", small.price_impact);
+        assert!(large.price_impact > small.price_impact);
+        assert!(large.tick_accounts.is_empty());
+        assert_eq!(large.fee_rate, 30);
+        assert_eq!(Quoter::tick_addresses(&pool_address, quoter.pool()).len(), 2);
+    }
+
+    #[test]
+    fn swap_ending_on_a_tick_boundary() {
+        // [-1_000, 1_000] holds the price, [-2_000, -1_000] sits right below it
+        let pool_address = find_pool_address(&MINT_A, &MINT_B).0;
+        let (upper_liquidity, lower_liquidity) = (1_000_000_000_000u128, 3_000_000_000_000u128);
+        let mut pool = decode_pool(&pool_data()).unwrap();
+        pool.fee = 0;
+        pool.dynamic_fee.enabled = false;
+        pool.pending_fee_activation = 0;
+        pool.initialized_ticks = vec![-2_000, -1_000, 1_000];
+        pool.tick_current = 0;
+        pool.sqrt_price = get_sqrt_price_from_tick(0).unwrap();
+        pool.liquidity = upper_liquidity;
+        let mut ticks: BTreeMap<i32, Tick> = BTreeMap::new();
+        for (tick_index, liquidity_net) in [
+            (-2_000, lower_liquidity as i128),
+            (-1_000, upper_liquidity as i128 - lower_liquidity as i128),
+            (1_000, -(upper_liquidity as i128)),
+        ] {
+            ticks.insert(tick_index, Tick { liquidity_net, ..tick(&pool_address, tick_index) });
+        }
+
+        // Exactly the input that takes the price down to tick -1_000
+        let sqrt_boundary = get_sqrt_price_from_tick(-1_000).unwrap();
+        let amount_in = clmm_math::get_amount_a_delta(sqrt_boundary, pool.sqrt_price, upper_liquidity, true).unwrap() as u64;
+        let quote = Quoter::new(pool_address, pool.clone(), ticks.values().cloned()).quote(amount_in, true, 0).unwrap();
+        let down = swap_on_chain(&pool_address, &pool, &[ticks[&-1_000].clone()], amount_in, true, 0).unwrap();
+
+        // The price rests on the crossed tick, which is now above it
+        assert_eq!(down.pool.sqrt_price, sqrt_boundary);
+        assert_eq!(down.pool.tick_current, -1_001);
+        assert_eq!(down.pool.liquidity, lower_liquidity);
+        assert_eq!((quote.sqrt_price, quote.tick_current, quote.liquidity), (sqrt_boundary, -1_001, lower_liquidity));
+
+        // Going back up crosses tick -1_000 first and is back in the upper range
+        ticks.insert(-1_000, down.ticks[0].clone());
+        let up_ticks = [ticks[&-1_000].clone(), ticks[&1_000].clone()];
+        let up = swap_on_chain(&pool_address, &down.pool, &up_ticks, 1_000, false, 0).unwrap();
+        assert_eq!(up.pool.liquidity, upper_liquidity);
+        assert_eq!(up.pool.tick_current, -1_000);
+        assert!(up.pool.sqrt_price > sqrt_boundary);
+    }
+}
@@ -0,0 +1,253 @@
+use std::collections::BTreeMap;
+
+use anchor_lang::error::Error;
+use anyhow::{anyhow, Result};
+use serde::{Serialize, Serializer};
+
+use crate::scenario::sqrt_price_to_price;
+use crate::simulator::Simulator;
+use crate::trades::Trade;
+
+// Outcome of replaying a trade stream. Values are in token B at the final price, u128 values are strings
+// since JSON numbers lose precision past 2^53.
+#[derive(Debug, Serialize)]
+pub struct Report {
+    pub trades: usize,
+    pub swaps: usize,
+    pub rejected: BTreeMap<String, usize>, // Trades the program would have failed, by error name
+    #[serde(serialize_with = "as_string")]
+    pub volume_a: u128, // Swapped in, fees included
+    #[serde(serialize_with = "as_string")]
+    pub volume_b: u128,
+    #[serde(serialize_with = "as_string")]
+    pub fees_a: u128,
+    #[serde(serialize_with = "as_string")]
+    pub fees_b: u128,
+    pub price_start: f64,
+    pub price_end: f64,
+    pub tick_start: i32,
+    pub tick_end: i32,
+    pub tick_min: i32,
+    pub tick_max: i32,
+    pub positions: Vec<PositionReport>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PositionReport {
+    pub name: String,
+    pub tick_lower: i32,
+    pub tick_upper: i32,
+    #[serde(serialize_with = "as_string")]
+    pub liquidity: u128,
+    pub deposited_a: u64,
+    pub deposited_b: u64,
+    pub amount_a: u64, // Withdrawable at the final price
+    pub amount_b: u64,
+    pub drift_a: i128, // Inventory change from the deposit, amount less deposited
+    pub drift_b: i128,
+    pub fees_a: u64,
+    pub fees_b: u64,
+    pub collect_error: Option<String>, // Error collect_fees would fail with, the fees are then zero
+    pub time_in_range: f64, // Share of trades after which the position was in range
+    pub hold_value: f64, // Deposit held aside
+    pub position_value: f64, // Withdrawable amounts, fees excluded
+    pub fees_value: f64,
+    pub impermanent_loss: f64, // position_value / hold_value - 1
+    pub pnl_vs_hold: f64, // position_value + fees_value - hold_value
+}
+
+// Replays the trades in order. A trade the program would reject is counted and skipped,
+// like a failed transaction it leaves the pool as it was.
+pub fn run(simulator: &mut Simulator, trades: &[Trade]) -> Result<Report> {
+    let pool = simulator.pool();
+    let mut report = Report {
+        trades: trades.len(),
+        swaps: 0,
+        rejected: BTreeMap::new(),
+        volume_a: 0,
+        volume_b: 0,
+        fees_a: 0,
+        fees_b: 0,
+        price_start: sqrt_price_to_price(pool.sqrt_price),
+        price_end: 0.0,
+        tick_start: pool.tick_current,
+        tick_end: 0,
+        tick_min: pool.tick_current,
+        tick_max: pool.tick_current,
+        positions: Vec::new(),
+    };
+    let mut trades_in_range = vec![0usize; simulator.positions().len()];
+
+    for trade in trades {
+        match simulator.swap(trade.amount_in, trade.a_to_b, trade.timestamp) {
+            Ok(swap) => {
+                report.swaps += 1;
+                let (volume, fees) = if trade.a_to_b {
+                    (&mut report.volume_a, &mut report.fees_a)
+                } else {
+                    (&mut report.volume_b, &mut report.fees_b)
+                };
+                *volume += swap.amount_in as u128;
+                *fees += swap.fee_amount as u128;
+                report.tick_min = report.tick_min.min(swap.tick_current);
+                report.tick_max = report.tick_max.max(swap.tick_current);
+            }
+            Err(error) => *report.rejected.entry(error_name(&error)).or_default() += 1,
+        }
+
+        let tick_current = simulator.pool().tick_current;
+        for (in_range, sim_position) in trades_in_range.iter_mut().zip(simulator.positions()) {
+            let position = &sim_position.position;
+            if position.tick_lower <= tick_current && tick_current < position.tick_upper {
+                *in_range += 1;
+            }
+        }
+    }
+
+    let pool = simulator.pool();
+    let price = sqrt_price_to_price(pool.sqrt_price);
+    report.price_end = price;
+    report.tick_end = pool.tick_current;
+
+    for (index, sim_position) in simulator.positions().iter().enumerate() {
+        let (amount_a, amount_b) = simulator
+            .position_amounts(index)
+            .map_err(|error| anyhow!("cannot value {}: {error}", sim_position.name))?;
+        let (fees_a, fees_b, collect_error) = match simulator.position_fees(index) {
+            Ok((fees_a, fees_b)) => (fees_a, fees_b, None),
+            Err(error) => (0, 0, Some(error_name(&error))),
+        };
+
+        let value = |amount_a: u64, amount_b: u64| amount_a as f64 * price + amount_b as f64;
+        let hold_value = value(sim_position.deposited_a, sim_position.deposited_b);
+        let position_value = value(amount_a, amount_b);
+        let fees_value = value(fees_a, fees_b);
+
+        report.positions.push(PositionReport {
+            name: sim_position.name.clone(),
+            tick_lower: sim_position.position.tick_lower,
+            tick_upper: sim_position.position.tick_upper,
+            liquidity: sim_position.position.liquidity,
+            deposited_a: sim_position.deposited_a,
+            deposited_b: sim_position.deposited_b,
+            amount_a,
+            amount_b,
+            drift_a: amount_a as i128 - sim_position.deposited_a as i128,
+            drift_b: amount_b as i128 - sim_position.deposited_b as i128,
+            fees_a,
+            fees_b,
+            collect_error,
+            time_in_range: if trades.is_empty() {
+                0.0
+            } else {
+                trades_in_range[index] as f64 / trades.len() as f64
+            },
+            hold_value,
+            position_value,
+            fees_value,
+            impermanent_loss: if hold_value > 0.0 { position_value / hold_value - 1.0 } else { 0.0 },
+            pnl_vs_hold: position_value + fees_value - hold_value,
+        });
+    }
+
+    Ok(report)
+}
+
+fn error_name(error: &Error) -> String {
+    match error {
+        Error::AnchorError(error) => error.error_name.clone(),
+        Error::ProgramError(error) => error.program_error.to_string(),
+    }
+}
+
+fn as_string<S: Serializer>(value: &u128, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+    serializer.serialize_str(&value.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIQUIDITY: u128 = 1_000_000_000_000;
+
+    fn trade(timestamp: i64, a_to_b: bool, amount_in: u64) -> Trade {
+        Trade {
+            timestamp,
+            a_to_b,
+            amount_in,
+        }
+    }
+
+    #[test]
+    fn in_range_positions_split_the_fees() {
+        let mut simulator = Simulator::new(1 << 64, 10, 100).unwrap();
+        simulator.open_position("wide".to_string(), -1_000, 1_000, LIQUIDITY).unwrap();
+        simulator.open_position("narrow".to_string(), -100, 100, LIQUIDITY).unwrap();
+        simulator.open_position("above".to_string(), 2_000, 2_100, LIQUIDITY).unwrap();
+
+        let trades = [trade(0, true, 2_000_000_000), trade(10, false, 2_000_000_000)];
+        let report = run(&mut simulator, &trades).unwrap();
+
+        assert_eq!(report.swaps, 2);
+        assert!(report.rejected.is_empty());
+        assert_eq!((report.volume_a, report.volume_b), (2_000_000_000, 2_000_000_000));
+        assert!(report.tick_min < -1 && report.tick_max >= 0, "{} {}", report.tick_min, report.tick_max);
+
+        // Both in-range positions hold the same liquidity for the whole trip, so they split the fees evenly,
+        // and together they earn all the pool fees but the rounding
+        let [wide, narrow, above] = &report.positions[..] else { panic!("three positions expected") };
+        assert_eq!((wide.fees_a, wide.fees_b), (narrow.fees_a, narrow.fees_b));
+        assert!(report.fees_a - 2 * wide.fees_a as u128 <= 2, "{} {}", report.fees_a, wide.fees_a);
+        assert!(report.fees_b - 2 * wide.fees_b as u128 <= 2, "{} {}", report.fees_b, wide.fees_b);
+        assert_eq!((above.fees_a, above.fees_b, above.time_in_range), (0, 0, 0.0));
+        assert_eq!(wide.time_in_range, 1.0);
+        assert!(report.positions.iter().all(|position| position.collect_error.is_none()));
+
+        // Out of range positions keep their inventory
+        assert_eq!((above.drift_a, above.drift_b), (0, 0));
+        assert_eq!(above.deposited_b, 0);
+    }
+
+    #[test]
+    fn impermanent_loss_matches_the_closed_form() {
+        let mut simulator = Simulator::new(1 << 64, 10, 0).unwrap();
+        simulator.open_position("range".to_string(), -2_000, 2_000, LIQUIDITY).unwrap();
+
+        // Buying A pushes the price up, the position sells A on the way
+        let report = run(&mut simulator, &[trade(0, false, 40_000_000_000)]).unwrap();
+        let position = &report.positions[0];
+        assert!(position.drift_a < 0 && position.drift_b > 0);
+        assert!(position.impermanent_loss < 0.0);
+        assert_eq!(position.fees_value, 0.0);
+
+        // Liquidity L in [pa, pb] at price p is worth L * (2 sqrt(p) - p / sqrt(pb) - sqrt(pa)) in B,
+        // and was deposited at price 1
+        let (sqrt_lower, sqrt_upper) = (1.0001f64.powf(-1_000.0), 1.0001f64.powf(1_000.0));
+        let value = |price: f64| {
+            let sqrt_price = price.sqrt();
+            LIQUIDITY as f64 * (2.0 * sqrt_price - price / sqrt_upper - sqrt_lower)
+        };
+        let hold_value = LIQUIDITY as f64 * ((1.0 - 1.0 / sqrt_upper) * report.price_end + (1.0 - sqrt_lower));
+        let expected = value(report.price_end) / hold_value - 1.0;
+        assert!((position.impermanent_loss - expected).abs() < 1e-6, "{} {expected}", position.impermanent_loss);
+    }
+
+    #[test]
+    fn rejected_trades_leave_the_pool_untouched() {
+        let mut simulator = Simulator::new(1 << 64, 10, 100).unwrap();
+        simulator.open_position("range".to_string(), -100, 100, LIQUIDITY).unwrap();
+        let before = simulator.pool().clone();
+
+        // Runs past the last initialized tick
+        let report = run(&mut simulator, &[trade(0, true, u64::MAX / 2)]).unwrap();
+        assert_eq!(report.swaps, 0);
+        assert_eq!(report.rejected, BTreeMap::from([("TickNotFound".to_string(), 1)]));
+        assert_eq!(simulator.pool().sqrt_price, before.sqrt_price);
+        assert_eq!(simulator.pool().fee_growth_global_a, before.fee_growth_global_a);
+        assert_eq!(report.positions[0].time_in_range, 1.0);
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["positions"][0]["liquidity"], LIQUIDITY.to_string());
+        assert_eq!(json["volume_a"], "0");
+    }
+}
@@ -0,0 +1,12 @@
+// Backtester for liquidity strategies. Swaps run through the program's own fee, tick and swap step math
+// on an in-memory pool, so a replayed stream earns the fees it would have earned on chain.
+
+pub mod backtest;
+pub mod scenario;
+pub mod simulator;
+pub mod trades;
+
+pub use backtest::{run, PositionReport, Report};
+pub use scenario::Scenario;
+pub use simulator::{position_amounts, SimPosition, Simulator, SwapResult};
+pub use trades::{read_trades, Trade};
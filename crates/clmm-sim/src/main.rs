@@ -0,0 +1,43 @@
+// Replays a swap stream against a scenario and prints the backtest report as JSON on stdout
+
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::Parser;
+use clmm_sim::{read_trades, run, Scenario};
+
+#[derive(Parser)]
+#[command(name = "clmm-sim", version, about = "Backtest CLMM DEX liquidity positions against a swap stream")]
+struct Cli {
+    /// Scenario JSON with the starting pool and the positions to evaluate
+    #[arg(long)]
+    scenario: PathBuf,
+    /// Swaps to replay, CSV with a timestamp,a_to_b,amount_in header or a .json array of the same fields
+    #[arg(long)]
+    trades: PathBuf,
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let scenario = Scenario::read(&cli.scenario)?;
+    let trades = read_trades(&cli.trades)?;
+
+    // The dynamic fee starts measuring volatility with the first trade
+    let start = trades.first().map_or(0, |trade| trade.timestamp);
+    let mut simulator = scenario.build(start)?;
+    let report = run(&mut simulator, &trades)?;
+
+    println!("{}", serde_json::to_string_pretty(&report)?);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn command_line_is_consistent() {
+        Cli::command().debug_assert();
+    }
+}
@@ -0,0 +1,136 @@
+use std::path::Path;
+
+use anyhow::{anyhow, bail, Context, Result};
+use clmm_dex::instructions::DynamicFeeParams;
+use serde::Deserialize;
+
+use crate::simulator::Simulator;
+
+// Starting pool and the positions of the strategy under test, read from JSON
+#[derive(Debug, Deserialize)]
+pub struct Scenario {
+    pub price: f64, // Price of A in B, in raw token units
+    pub tick_spacing: u16,
+    pub fee: u8, // Static swap fee in millionths
+    #[serde(default)]
+    pub dynamic_fee: Option<DynamicFeeConfig>,
+    pub positions: Vec<PositionConfig>,
+}
+
+// set_dynamic_fee parameters, the dynamic fee is enabled whenever they are given
+#[derive(Debug, Deserialize)]
+pub struct DynamicFeeConfig {
+    pub filter_period: u32,
+    pub decay_period: u32,
+    pub reduction_factor: u16,
+    pub variable_fee_control: u32,
+    pub max_volatility_accumulator: u32,
+    pub max_fee: u32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PositionConfig {
+    #[serde(default)]
+    pub name: Option<String>,
+    pub tick_lower: i32,
+    pub tick_upper: i32,
+    pub liquidity: u128,
+}
+
+impl Scenario {
+    pub fn read(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path).with_context(|| format!("cannot read scenario {}", path.display()))?;
+        serde_json::from_str(&contents).with_context(|| format!("invalid scenario {}", path.display()))
+    }
+
+    // Pool with every position opened, the dynamic fee starts measuring volatility at timestamp
+    pub fn build(&self, timestamp: i64) -> Result<Simulator> {
+        let sqrt_price = price_to_sqrt_price(self.price).with_context(|| format!("invalid price {}", self.price))?;
+        let mut simulator = Simulator::new(sqrt_price, self.tick_spacing, self.fee)
+            .map_err(|error| anyhow!("cannot create the pool: {error}"))?;
+
+        if let Some(dynamic_fee) = &self.dynamic_fee {
+            let params = DynamicFeeParams {
+                enabled: true,
+                filter_period: dynamic_fee.filter_period,
+                decay_period: dynamic_fee.decay_period,
+                reduction_factor: dynamic_fee.reduction_factor,
+                variable_fee_control: dynamic_fee.variable_fee_control,
+                max_volatility_accumulator: dynamic_fee.max_volatility_accumulator,
+                max_fee: dynamic_fee.max_fee,
+            };
+            simulator
+                .set_dynamic_fee(params, timestamp)
+                .map_err(|error| anyhow!("invalid dynamic fee: {error}"))?;
+        }
+
+        if self.positions.is_empty() {
+            bail!("the scenario has no positions");
+        }
+        for (index, position) in self.positions.iter().enumerate() {
+            let name = position.name.clone().unwrap_or_else(|| format!("position {index}"));
+            simulator
+                .open_position(name.clone(), position.tick_lower, position.tick_upper, position.liquidity)
+                .map_err(|error| anyhow!("cannot open {name}: {error}"))?;
+        }
+        Ok(simulator)
+    }
+}
+
+pub fn price_to_sqrt_price(price: f64) -> Option<u128> {
+    if !price.is_finite() || price <= 0.0 {
+        return None;
+    }
+    let sqrt_price_x64 = price.sqrt() * (1u128 << 64) as f64;
+    (sqrt_price_x64 < u128::MAX as f64).then_some(sqrt_price_x64 as u128)
+}
+
+pub fn sqrt_price_to_price(sqrt_price_x64: u128) -> f64 {
+    let sqrt_price = sqrt_price_x64 as f64 / (1u128 << 64) as f64;
+    sqrt_price * sqrt_price
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_the_pool_and_positions() {
+        let scenario: Scenario = serde_json::from_str(
+            r#"{
+                "price": 1.0,
+                "tick_spacing": 8,
+                "fee": 30,
+                "dynamic_fee": {
+                    "filter_period": 30,
+                    "decay_period": 600,
+                    "reduction_factor": 5000,
+                    "variable_fee_control": 40000,
+                    "max_volatility_accumulator": 350000,
+                    "max_fee": 10000
+                },
+                "positions": [
+                    { "name": "wide", "tick_lower": -800, "tick_upper": 800, "liquidity": 1000000000 },
+                    { "tick_lower": 80, "tick_upper": 160, "liquidity": 340282366920938463463374607431768211455 }
+                ]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(scenario.positions[1].liquidity, u128::MAX);
+
+        // The second position's liquidity does not fit liquidity_net
+        let error = scenario.build(1_700_000_000).err().unwrap();
+        assert!(error.to_string().starts_with("cannot open position 1"), "{error}");
+
+        let scenario = Scenario {
+            positions: scenario.positions.into_iter().take(1).collect(),
+            ..scenario
+        };
+        let simulator = scenario.build(1_700_000_000).unwrap();
+        assert_eq!(simulator.pool().sqrt_price, 1 << 64);
+        assert_eq!(simulator.pool().liquidity, 1_000_000_000);
+        assert_eq!(simulator.pool().initialized_ticks, vec![-800, 800]);
+        assert_eq!(simulator.pool().dynamic_fee.last_update_timestamp, 1_700_000_000);
+        assert_eq!(simulator.positions()[0].name, "wide");
+    }
+}
@@ -0,0 +1,382 @@
+use std::collections::BTreeMap;
+use std::ops::Bound;
+
+use anchor_lang::prelude::*;
+use clmm_dex::errors::Errors;
+use clmm_dex::instructions::DynamicFeeParams;
+use clmm_dex::math::fee_math::{get_fee_growth_inside, update_position_fees};
+use clmm_dex::math::reward_math::get_reward_growths_global;
+use clmm_dex::math::tick_math::{initialize_tick, update_tick_liquidity};
+use clmm_dex::state::{DynamicFee, Pool, Position, PositionRewardInfo, Tick, NUM_REWARDS};
+use clmm_math::sqrt_price_math::{get_amount_a_delta, get_amount_b_delta};
+use clmm_math::tick_math::get_sqrt_price_from_tick;
+use clmm_sdk::local::swap_on_chain;
+use clmm_sdk::{find_pool_address, find_tick_address};
+
+// A pool held in memory, with the state transitions of provide_liquidity applied to plain structs and swaps run
+// through the program's own swap loop. Range orders and rewards are not simulated, and amounts are pool side: mint transfer fees are not applied.
+pub struct Simulator {
+    pool_address: Pubkey, // Stands in for the pool's PDA, the swap checks tick accounts against it
+    pool: Pool,
+    ticks: BTreeMap<i32, Tick>,
+    positions: Vec<SimPosition>,
+}
+
+pub struct SimPosition {
+    pub name: String,
+    pub position: Position,
+    pub deposited_a: u64, // Tokens backing the liquidity when it was opened
+    pub deposited_b: u64,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SwapResult {
+    pub amount_in: u64, // Consumed by the pool, swap fee included
+    pub amount_out: u64,
+    pub fee_amount: u64,
+    pub sqrt_price: u128, // Pool state after the swap
+    pub tick_current: i32,
+    pub liquidity: u128,
+}
+
+impl Simulator {
+    // Fresh pool at sqrt_price with a static fee in millionths, set up by the same Pool::initialize as initialize_pool
+    pub fn new(sqrt_price: u128, tick_spacing: u16, fee: u8) -> Result<Self> {
+        require!(tick_spacing > 0, Errors::InvalidTickSpacing);
+        let (pool_address, bump) = find_pool_address(&Pubkey::default(), &Pubkey::default());
+
+        let mut pool = Pool { bump, ..Pool::default() };
+        pool.initialize(sqrt_price, tick_spacing, fee, 0)?;
+
+        Ok(Self {
+            pool_address,
+            pool,
+            ticks: BTreeMap::new(),
+            positions: Vec::new(),
+        })
+    }
+
+    pub fn pool(&self) -> &Pool {
+        &self.pool
+    }
+
+    // Initialized ticks in ascending order
+    pub fn ticks(&self) -> impl Iterator<Item = &Tick> {
+        self.ticks.values()
+    }
+
+    pub fn positions(&self) -> &[SimPosition] {
+        &self.positions
+    }
+
+    // Same checks and state reset as set_dynamic_fee once apply_fee_change commits it, without the timelock.
+    // timestamp stands in for the clock.
+    pub fn set_dynamic_fee(&mut self, params: DynamicFeeParams, timestamp: i64) -> Result<()> {
+        let dynamic_fee = DynamicFee {
+            enabled: params.enabled,
+            filter_period: params.filter_period,
+            decay_period: params.decay_period,
+            reduction_factor: params.reduction_factor,
+            variable_fee_control: params.variable_fee_control,
+            max_volatility_accumulator: params.max_volatility_accumulator,
+            max_fee: params.max_fee,
+            volatility_accumulator: 0,
+            volatility_reference: 0,
+            tick_reference: self.pool.tick_current,
+            last_update_timestamp: timestamp,
+        };
+        dynamic_fee.validate(self.pool.fee)?;
+
+        self.pool.dynamic_fee = dynamic_fee;
+        Ok(())
+    }
+
+    // Opens a position like provide_liquidity and returns its index in positions()
+    pub fn open_position(&mut self, name: String, tick_lower: i32, tick_upper: i32, liquidity: u128) -> Result<usize> {
+        let tick_spacing = self.pool.tick_spacing as i32;
+        require!(tick_lower < tick_upper, Errors::InvalidTickRange);
+        require!(
+            tick_lower % tick_spacing == 0 && tick_upper % tick_spacing == 0,
+            Errors::InvalidTickSpacing
+        );
+        require!(liquidity > 0, Errors::InvalidAmount);
+        let liquidity_delta = i128::try_from(liquidity).map_err(|_| Errors::MultiplicationOverflow)?;
+
+        let (deposited_a, deposited_b) = position_amounts(liquidity, tick_lower, tick_upper, self.pool.sqrt_price)?;
+        let bundle_index = u16::try_from(self.positions.len()).map_err(|_| Errors::InvalidPositionSlot)?;
+
+        // Ticks are updated on copies and written back once nothing can fail anymore
+        let pool = &self.pool;
+        let pool_address = &self.pool_address;
+        let reward_growths_global = get_reward_growths_global(pool);
+        let [mut lower_tick, mut upper_tick] = [tick_lower, tick_upper].map(|tick_index| {
+            self.ticks.get(&tick_index).cloned().unwrap_or_else(|| {
+                let mut tick = empty_tick();
+                initialize_tick(
+                    &mut tick,
+                    tick_index,
+                    find_tick_address(pool_address, tick_index).1,
+                    pool.tick_current,
+                    pool.fee_growth_global_a,
+                    pool.fee_growth_global_b,
+                    reward_growths_global,
+                );
+                tick
+            })
+        });
+        update_tick_liquidity(&mut lower_tick, &mut upper_tick, liquidity_delta)?;
+        let (fee_growth_inside_a, fee_growth_inside_b) = get_fee_growth_inside(
+            &lower_tick,
+            &upper_tick,
+            pool.tick_current,
+            pool.fee_growth_global_a,
+            pool.fee_growth_global_b,
+        );
+
+        let mut pool_liquidity = pool.liquidity;
+        if tick_lower <= pool.tick_current && pool.tick_current < tick_upper {
+            pool_liquidity = pool_liquidity
+                .checked_add(liquidity)
+                .ok_or(Errors::MultiplicationOverflow)?;
+        }
+
+        self.pool.liquidity = pool_liquidity;
+        for tick in [lower_tick, upper_tick] {
+            self.pool.insert_initialized_tick(tick.tick_index)?;
+            self.ticks.insert(tick.tick_index, tick);
+        }
+
+        self.positions.push(SimPosition {
+            name,
+            position: Position {
+                owner: Pubkey::default(),
+                tick_lower,
+                tick_upper,
+                liquidity,
+                fee_growth_inside_a,
+                fee_growth_inside_b,
+                token_a_earned: 0,
+                token_b_earned: 0,
+                bundle_index,
+                delegate: None,
+                range_order: false,
+                range_order_sells_a: false,
+                range_order_fills: 0,
+                reward_infos: [PositionRewardInfo::default(); NUM_REWARDS],
+                bump: 0,
+            },
+            deposited_a,
+            deposited_b,
+        });
+
+        Ok(self.positions.len() - 1)
+    }
+
+    // Applies swap(amount_in, a_to_b) landing at timestamp through the program's own swap loop. A failed swap
+    // leaves the pool untouched, as the program's transaction would revert.
+    pub fn swap(&mut self, amount_in: u64, a_to_b: bool, timestamp: i64) -> Result<SwapResult> {
+        let ticks = ticks_in_swap_direction(&self.ticks, self.pool.tick_current, a_to_b);
+        let swap = swap_on_chain(&self.pool_address, &self.pool, &ticks, amount_in, a_to_b, timestamp)?;
+
+        let result = SwapResult {
+            amount_in: u64::try_from(swap.amount_in).map_err(|_| Errors::TokenMaxExceeded)?,
+            amount_out: u64::try_from(swap.amount_out).map_err(|_| Errors::TokenMaxExceeded)?,
+            fee_amount: u64::try_from(swap.fee_amount).map_err(|_| Errors::TokenMaxExceeded)?,
+            sqrt_price: swap.pool.sqrt_price,
+            tick_current: swap.pool.tick_current,
+            liquidity: swap.pool.liquidity,
+        };
+
+        self.pool = swap.pool;
+        self.ticks.extend(swap.ticks.into_iter().map(|tick| (tick.tick_index, tick)));
+
+        Ok(result)
+    }
+
+    // Fees the position would collect now, like collect_fees computes them
+    pub fn position_fees(&self, index: usize) -> Result<(u64, u64)> {
+        let sim_position = self.positions.get(index).ok_or(Errors::InvalidPosition)?;
+        let mut position = sim_position.position.clone();
+        let lower_tick = self.ticks.get(&position.tick_lower).ok_or(Errors::TickNotFound)?;
+        let upper_tick = self.ticks.get(&position.tick_upper).ok_or(Errors::TickNotFound)?;
+
+        let (fee_growth_inside_a, fee_growth_inside_b) = get_fee_growth_inside(
+            lower_tick,
+            upper_tick,
+            self.pool.tick_current,
+            self.pool.fee_growth_global_a,
+            self.pool.fee_growth_global_b,
+        );
+        update_position_fees(&mut position, fee_growth_inside_a, fee_growth_inside_b)?;
+        Ok((position.token_a_earned, position.token_b_earned))
+    }
+
+    // Tokens the position's liquidity would withdraw at the current price
+    pub fn position_amounts(&self, index: usize) -> Result<(u64, u64)> {
+        let position = &self.positions.get(index).ok_or(Errors::InvalidPosition)?.position;
+        position_amounts(position.liquidity, position.tick_lower, position.tick_upper, self.pool.sqrt_price)
+    }
+}
+
+// Tokens backing liquidity in [tick_lower, tick_upper] at the exact sqrt price rather than at its tick,
+// with the amount deltas the swap steps use, rounded down
+pub fn position_amounts(liquidity: u128, tick_lower: i32, tick_upper: i32, sqrt_price: u128) -> Result<(u64, u64)> {
+    let sqrt_lower_price = get_sqrt_price_from_tick(tick_lower).map_err(Errors::from)?;
+    let sqrt_upper_price = get_sqrt_price_from_tick(tick_upper).map_err(Errors::from)?;
+    let sqrt_price = sqrt_price.clamp(sqrt_lower_price, sqrt_upper_price);
+
+    let amount_a = get_amount_a_delta(sqrt_price, sqrt_upper_price, liquidity, false).map_err(Errors::from)?;
+    let amount_b = get_amount_b_delta(sqrt_lower_price, sqrt_price, liquidity, false).map_err(Errors::from)?;
+    Ok((
+        u64::try_from(amount_a).map_err(|_| Errors::TokenMaxExceeded)?,
+        u64::try_from(amount_b).map_err(|_| Errors::TokenMaxExceeded)?,
+    ))
+}
+
+// Every tick the swap may cross, in the order swap takes their accounts. Range orders are not simulated,
+// so no partner tick is ever needed.
+fn ticks_in_swap_direction(ticks: &BTreeMap<i32, Tick>, tick_current: i32, a_to_b: bool) -> Vec<Tick> {
+    if a_to_b {
+        ticks.range(..=tick_current).rev().map(|(_, tick)| tick.clone()).collect()
+    } else {
+        ticks.range((Bound::Excluded(tick_current), Bound::Unbounded)).map(|(_, tick)| tick.clone()).collect()
+    }
+}
+
+fn empty_tick() -> Tick {
+    Tick {
+        tick_index: 0,
+        liquidity_net: 0,
+        fee_growth_outside_a: 0,
+        fee_growth_outside_b: 0,
+        initialized: false,
+        bump: 0,
+        range_order_liquidity_up: 0,
+        range_order_liquidity_down: 0,
+        range_order_fills_up: 0,
+        range_order_fills_down: 0,
+        range_order_fee_growth_up_a: 0,
+        range_order_fee_growth_up_b: 0,
+        range_order_fee_growth_down_a: 0,
+        range_order_fee_growth_down_b: 0,
+        range_order_reward_growths_up: [0; NUM_REWARDS],
+        range_order_reward_growths_down: [0; NUM_REWARDS],
+        reward_growths_outside: [0; NUM_REWARDS],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clmm_sdk::local::error_code;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    const CASES: usize = 200;
+    const SWAPS: usize = 20;
+
+    // The program's swap over a separate copy of the state, passing every tick in swap direction
+    fn swap_program(
+        pool_address: &Pubkey,
+        pool: &Pool,
+        ticks: &mut BTreeMap<i32, Tick>,
+        amount_in: u64,
+        a_to_b: bool,
+        timestamp: i64,
+    ) -> Result<(SwapResult, Pool)> {
+        let crossable = ticks_in_swap_direction(ticks, pool.tick_current, a_to_b);
+        let swap = swap_on_chain(pool_address, pool, &crossable, amount_in, a_to_b, timestamp)?;
+        ticks.extend(swap.ticks.into_iter().map(|tick| (tick.tick_index, tick)));
+
+        let result = SwapResult {
+            amount_in: swap.amount_in as u64,
+            amount_out: swap.amount_out as u64,
+            fee_amount: swap.fee_amount as u64,
+            sqrt_price: swap.pool.sqrt_price,
+            tick_current: swap.pool.tick_current,
+            liquidity: swap.pool.liquidity,
+        };
+        Ok((result, swap.pool))
+    }
+
+    fn assert_same_ticks(simulator: &Simulator, ticks: &BTreeMap<i32, Tick>, case: usize) {
+        assert_eq!(simulator.ticks.len(), ticks.len(), "case {case}");
+        for (tick, expected) in simulator.ticks().zip(ticks.values()) {
+            assert_eq!(tick.tick_index, expected.tick_index, "case {case}");
+            assert_eq!(tick.liquidity_net, expected.liquidity_net, "case {case}");
+            assert_eq!(tick.fee_growth_outside_a, expected.fee_growth_outside_a, "case {case}");
+            assert_eq!(tick.fee_growth_outside_b, expected.fee_growth_outside_b, "case {case}");
+        }
+    }
+
+    // Swap sequences on random pools, the simulator has to end every swap in the program's state
+    #[test]
+    fn matches_swap_internal_over_swap_sequences() {
+        let mut rng = StdRng::seed_from_u64(0x636c_6d6d);
+        let mut swaps = 0;
+        let mut crossings = 0;
+
+        for case in 0..CASES {
+            let tick_spacing: i32 = [1, 8, 64][rng.gen_range(0..3)];
+            let start_tick = rng.gen_range(-40..40) * tick_spacing + rng.gen_range(0..tick_spacing);
+            let sqrt_price = get_sqrt_price_from_tick(start_tick).unwrap() + rng.gen_range(0..1_000_000);
+            let mut simulator = Simulator::new(sqrt_price, tick_spacing as u16, rng.gen()).unwrap();
+            if rng.gen_bool(0.5) {
+                let params = DynamicFeeParams {
+                    enabled: true,
+                    filter_period: rng.gen_range(0..100),
+                    decay_period: rng.gen_range(100..1_000),
+                    reduction_factor: rng.gen_range(0..=10_000),
+                    variable_fee_control: rng.gen_range(0..100_000),
+                    max_volatility_accumulator: rng.gen_range(0..1_000_000),
+                    max_fee: rng.gen_range(255..100_000),
+                };
+                simulator.set_dynamic_fee(params, 0).unwrap();
+            }
+            for index in 0..rng.gen_range(1..8) {
+                let lower = rng.gen_range(-40..40) * tick_spacing;
+                let upper = lower + rng.gen_range(1..20) * tick_spacing;
+                let digits = rng.gen_range(3..13);
+                simulator
+                    .open_position(format!("{index}"), lower, upper, rng.gen_range(1..=10u128.pow(digits)))
+                    .unwrap();
+            }
+
+            let mut pool = simulator.pool().clone();
+            let mut ticks = simulator.ticks.clone();
+            let mut timestamp = 0;
+            for _ in 0..SWAPS {
+                let a_to_b = rng.gen_bool(0.5);
+                let digits = rng.gen_range(1..10);
+                let amount_in = rng.gen_range(1..10u64.pow(digits));
+                timestamp += rng.gen_range(0..200);
+
+                let mut ticks_after = ticks.clone();
+                let expected = swap_program(&simulator.pool_address, &pool, &mut ticks_after, amount_in, a_to_b, timestamp);
+                match (simulator.swap(amount_in, a_to_b, timestamp), expected) {
+                    (Ok(result), Ok((expected, pool_after))) => {
+                        assert_eq!(result, expected, "case {case}");
+                        assert_eq!(simulator.pool.fee_growth_global_a, pool_after.fee_growth_global_a, "case {case}");
+                        assert_eq!(simulator.pool.fee_growth_global_b, pool_after.fee_growth_global_b, "case {case}");
+                        assert_eq!(
+                            simulator.pool.dynamic_fee.volatility_accumulator,
+                            pool_after.dynamic_fee.volatility_accumulator,
+                            "case {case}"
+                        );
+                        let (low, high) = (pool.tick_current.min(result.tick_current), pool.tick_current.max(result.tick_current));
+                        crossings += ticks.keys().filter(|tick| low < **tick && **tick <= high).count();
+                        pool = pool_after;
+                        ticks = ticks_after;
+                        swaps += 1;
+                    }
+                    (Err(error), Err(expected)) => assert_eq!(error_code(&error), error_code(&expected), "case {case}"),
+                    (result, expected) => panic!("case {case}: simulator {result:?}, program {:?}", expected.map(|(result, _)| result)),
+                }
+                assert_same_ticks(&simulator, &ticks, case);
+            }
+        }
+
+        assert!(swaps > CASES * SWAPS / 4, "only {swaps} successful swaps");
+        assert!(crossings > CASES, "only {crossings} tick crossings");
+    }
+}
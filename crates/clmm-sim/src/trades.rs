@@ -0,0 +1,84 @@
+use std::io::Read;
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+
+// One exact input swap of the replayed stream. Streams are CSV with a timestamp,a_to_b,amount_in header,
+// or a JSON array of objects with the same fields.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct Trade {
+    pub timestamp: i64, // Unix seconds, the dynamic fee decays with it
+    pub a_to_b: bool,
+    pub amount_in: u64,
+}
+
+// Reads a .json file as JSON and anything else as CSV
+pub fn read_trades(path: &Path) -> Result<Vec<Trade>> {
+    let file = std::fs::File::open(path).with_context(|| format!("cannot open trades {}", path.display()))?;
+    let trades = if path.extension().is_some_and(|extension| extension == "json") {
+        parse_json(file)
+    } else {
+        parse_csv(file)
+    }
+    .with_context(|| format!("invalid trades {}", path.display()))?;
+
+    check_order(&trades)?;
+    Ok(trades)
+}
+
+pub fn parse_csv(reader: impl Read) -> Result<Vec<Trade>> {
+    let mut reader = csv::ReaderBuilder::new().trim(csv::Trim::All).from_reader(reader);
+    reader
+        .deserialize()
+        .enumerate()
+        .map(|(row, trade)| trade.with_context(|| format!("row {}", row + 1)))
+        .collect()
+}
+
+pub fn parse_json(reader: impl Read) -> Result<Vec<Trade>> {
+    Ok(serde_json::from_reader(reader)?)
+}
+
+// The program never sees time go backwards, neither may the replay
+pub fn check_order(trades: &[Trade]) -> Result<()> {
+    if let Some(index) = trades.windows(2).position(|pair| pair[1].timestamp < pair[0].timestamp) {
+        bail!("trade {} is older than the one before it", index + 2);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_csv_and_json_alike() {
+        let csv = "timestamp, a_to_b, amount_in\n1700000000, true, 1000\n1700000012, false, 250\n";
+        let json = r#"[
+            { "timestamp": 1700000000, "a_to_b": true, "amount_in": 1000 },
+            { "timestamp": 1700000012, "a_to_b": false, "amount_in": 250 }
+        ]"#;
+
+        let trades = parse_csv(csv.as_bytes()).unwrap();
+        assert_eq!(trades, parse_json(json.as_bytes()).unwrap());
+        assert_eq!(
+            trades[1],
+            Trade {
+                timestamp: 1_700_000_012,
+                a_to_b: false,
+                amount_in: 250,
+            }
+        );
+    }
+
+    #[test]
+    fn rejects_bad_rows_and_time_travel() {
+        let error = parse_csv("timestamp,a_to_b,amount_in\n1,true,10\n2,sideways,10\n".as_bytes()).unwrap_err();
+        assert_eq!(error.to_string(), "row 2");
+
+        let trades = parse_csv("timestamp,a_to_b,amount_in\n5,true,10\n5,false,10\n4,true,10\n".as_bytes()).unwrap();
+        let error = check_order(&trades).unwrap_err();
+        assert_eq!(error.to_string(), "trade 3 is older than the one before it");
+    }
+}
@@ -1,4 +1,5 @@
 use anchor_lang::prelude::*;
+use clmm_math::MathError;
 
 #[error_code]
 pub enum Errors {
@@ -32,4 +33,66 @@ pub enum Errors {
     InvalidFeeRate,
     #[msg("Invalid tick.")]
     InvalidTick,
-}
\ No newline at end of file
+    #[msg("Position slot is out of range or empty.")]
+    InvalidPositionSlot,
+    #[msg("Position slot is already occupied.")]
+    PositionSlotOccupied,
+    #[msg("Signer is not the position owner or delegate.")]
+    Unauthorized,
+    #[msg("Slippage tolerance exceeded.")]
+    SlippageExceeded,
+    #[msg("Invalid range order.")]
+    InvalidRangeOrder,
+    #[msg("Requested observation is older than the oldest stored observation.")]
+    ObservationTooOld,
+    #[msg("Invalid reward index.")]
+    InvalidRewardIndex,
+    #[msg("Operation is paused.")]
+    OperationPaused,
+    #[msg("No fee change is pending.")]
+    NoPendingFeeChange,
+    #[msg("Fee change delay has not passed yet.")]
+    FeeChangeNotActive,
+    #[msg("Transfer fee calculation failed.")]
+    TransferFeeCalculationError,
+    #[msg("Transfer hook program is not on the allowlist.")]
+    TransferHookNotAllowed,
+    #[msg("Native SOL requires the native mint and the temporary wSOL account.")]
+    NativeSolNotSupported,
+    #[msg("A flash loan is already outstanding on this pool.")]
+    FlashLoanInProgress,
+    #[msg("No matching flash_repay follows the flash loan.")]
+    FlashRepayMissing,
+    #[msg("Flash repay does not match the outstanding loan.")]
+    InvalidFlashRepay,
+    #[msg("Pool is locked by a flash swap in progress.")]
+    PoolLocked,
+    #[msg("Flash swap callback did not pay the owed input.")]
+    FlashSwapNotPaid,
+    #[msg("Callback program cannot be this program.")]
+    InvalidCallbackProgram,
+    #[msg("Route hops or their accounts do not match.")]
+    InvalidRoute,
+    #[msg("Sqrt price limit is on the wrong side of the current price.")]
+    InvalidSqrtPriceLimit,
+    #[msg("Rewards owed to the position have to be collected first.")]
+    RewardsOwed,
+    #[msg("Pool has reached its maximum number of initialized ticks.")]
+    TooManyTicks,
+    #[msg("Range order fill record is missing or does not match the order.")]
+    InvalidRangeOrderFill,
+}
+
+// Errors of the shared math crate as program errors
+impl From<MathError> for Errors {
+    fn from(error: MathError) -> Self {
+        match error {
+            MathError::Overflow => Errors::MathError,
+            MathError::DivisionByZero => Errors::DivisionByZero,
+            MathError::InvalidTickRange => Errors::InvalidTickRange,
+            MathError::InvalidFeeRate => Errors::InvalidFeeRate,
+            MathError::InsufficientLiquidity => Errors::InsufficientLiquidity,
+            MathError::TickNotFound => Errors::TickNotFound,
+        }
+    }
+}
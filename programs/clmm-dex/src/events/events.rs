@@ -0,0 +1,222 @@
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct FeeChangeProposed {
+    pub pool: Pubkey,
+    pub current_fee: u8,
+    pub pending_fee: u8,
+    pub activation_timestamp: i64,
+}
+
+#[event]
+pub struct FeeChangeApplied {
+    pub pool: Pubkey,
+    pub old_fee: u8,
+    pub new_fee: u8,
+}
+
+#[event]
+pub struct ConfigInitialized {
+    pub config: Pubkey,
+    pub admin: Pubkey,
+    pub pause_authority: Pubkey,
+    pub fee_change_delay: i64,
+}
+
+#[event]
+pub struct PauseAuthoritySet {
+    pub config: Pubkey,
+    pub pause_authority: Pubkey,
+}
+
+#[event]
+pub struct TransferHookAllowlistSet {
+    pub config: Pubkey,
+    pub transfer_hook_programs: Vec<Pubkey>,
+}
+
+#[event]
+pub struct GlobalPauseSet {
+    pub config: Pubkey,
+    pub paused: bool,
+}
+
+#[event]
+pub struct PoolPauseFlagsSet {
+    pub pool: Pubkey,
+    pub pause_flags: u8,
+}
+
+#[event]
+pub struct PoolInitialized {
+    pub pool: Pubkey,
+    pub token_a_mint: Pubkey,
+    pub token_b_mint: Pubkey,
+    pub token_a_vault: Pubkey,
+    pub token_b_vault: Pubkey,
+    pub tick_spacing: u16,
+    pub fee: u8,
+    pub sqrt_price: u128,
+    pub tick_current: i32,
+}
+
+#[event]
+pub struct DynamicFeeProposed {
+    pub pool: Pubkey,
+    pub enabled: bool,
+    pub max_fee: u32,
+    pub activation_timestamp: i64,
+}
+
+#[event]
+pub struct DynamicFeeSet {
+    pub pool: Pubkey,
+    pub enabled: bool,
+    pub max_fee: u32,
+}
+
+#[event]
+pub struct ObservationCardinalityIncreased {
+    pub pool: Pubkey,
+    pub cardinality_next_old: u16,
+    pub cardinality_next_new: u16,
+}
+
+#[event]
+pub struct PositionBundleInitialized {
+    pub pool: Pubkey,
+    pub position_bundle: Pubkey,
+    pub owner: Pubkey,
+}
+
+#[event]
+pub struct LiquidityIncreased {
+    pub pool: Pubkey,
+    pub position: Pubkey,
+    pub owner: Pubkey,
+    pub tick_lower: i32,
+    pub tick_upper: i32,
+    pub liquidity: u128,
+    pub amount_a: u64,
+    pub amount_b: u64,
+    pub range_order: bool,
+}
+
+// Also emitted when a range order is claimed, amounts are what left the vaults
+#[event]
+pub struct LiquidityDecreased {
+    pub pool: Pubkey,
+    pub position: Pubkey,
+    pub owner: Pubkey,
+    pub tick_lower: i32,
+    pub tick_upper: i32,
+    pub liquidity: u128,
+    pub amount_a: u64,
+    pub amount_b: u64,
+}
+
+#[event]
+pub struct PositionDelegateSet {
+    pub position: Pubkey,
+    pub delegate: Option<Pubkey>,
+}
+
+#[event]
+pub struct PositionTransferred {
+    pub pool: Pubkey,
+    pub position: Pubkey,
+    pub new_position: Pubkey,
+    pub old_owner: Pubkey,
+    pub new_owner: Pubkey,
+}
+
+#[event]
+pub struct PositionRebalanced {
+    pub pool: Pubkey,
+    pub position: Pubkey,
+    pub old_tick_lower: i32,
+    pub old_tick_upper: i32,
+    pub old_liquidity: u128,
+    pub new_tick_lower: i32,
+    pub new_tick_upper: i32,
+    pub new_liquidity: u128,
+}
+
+// Amounts are pool side: what the pool took in and sent out before mint transfer fees
+#[event]
+pub struct Swapped {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub a_to_b: bool,
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub fee_amount: u64,
+    pub sqrt_price_before: u128,
+    pub sqrt_price_after: u128,
+    pub tick_before: i32,
+    pub tick_after: i32,
+    pub liquidity_before: u128,
+    pub liquidity_after: u128,
+}
+
+#[event]
+pub struct FeesCollected {
+    pub pool: Pubkey,
+    pub position: Pubkey,
+    pub owner: Pubkey,
+    pub amount_a: u64,
+    pub amount_b: u64,
+}
+
+#[event]
+pub struct FeesCompounded {
+    pub pool: Pubkey,
+    pub position: Pubkey,
+    pub liquidity: u128,
+    pub amount_a: u64,
+    pub amount_b: u64,
+}
+
+#[event]
+pub struct RewardInitialized {
+    pub pool: Pubkey,
+    pub reward_index: u8,
+    pub mint: Pubkey,
+    pub vault: Pubkey,
+}
+
+#[event]
+pub struct RewardEmissionsSet {
+    pub pool: Pubkey,
+    pub reward_index: u8,
+    pub emissions_per_second_x64: u128,
+}
+
+#[event]
+pub struct RewardCollected {
+    pub pool: Pubkey,
+    pub position: Pubkey,
+    pub owner: Pubkey,
+    pub reward_index: u8,
+    pub amount: u64,
+}
+
+#[event]
+pub struct FlashLoanTaken {
+    pub pool: Pubkey,
+    pub borrower: Pubkey,
+    pub amount_a: u64,
+    pub amount_b: u64,
+    pub fee_a: u64,
+    pub fee_b: u64,
+}
+
+#[event]
+pub struct FlashLoanRepaid {
+    pub pool: Pubkey,
+    pub repayer: Pubkey,
+    pub amount_a: u64,
+    pub amount_b: u64,
+    pub fee_a: u64,
+    pub fee_b: u64,
+}
@@ -0,0 +1,4 @@
+#[allow(clippy::module_inception)]
+pub mod events;
+
+pub use events::*;
\ No newline at end of file
@@ -0,0 +1,273 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::errors::Errors;
+use crate::state::{Config, Observation, Pool, Position, PositionBundle, RangeOrderFill, Tick, PAUSE_WITHDRAWALS};
+use crate::math::compute_amount::*;
+use crate::math::fee_math::*;
+use crate::math::oracle_math::write_observation;
+use crate::math::reward_math::*;
+use crate::math::tick_math::{get_sqrt_price_from_tick, update_tick_liquidity};
+use crate::utils::{get_transfer_hook_program_id, transfer_from_vault};
+use crate::events::LiquidityDecreased;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimRangeOrder<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"pool",
+            pool.token_a_mint.as_ref(),
+            pool.token_b_mint.as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut,
+        seeds = [b"observation", pool.key().as_ref()],
+        bump = observation.load()?.bump,
+    )]
+    pub observation: AccountLoader<'info, Observation>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"position_bundle",
+            pool.key().as_ref(),
+            owner.key().as_ref(),
+        ],
+        bump = position_bundle.bump,
+    )]
+    pub position_bundle: Account<'info, PositionBundle>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"position",
+            position_bundle.key().as_ref(),
+            position.bundle_index.to_le_bytes().as_ref(),
+        ],
+        close = owner,
+        bump = position.bump,
+        constraint = position.range_order @ Errors::InvalidRangeOrder,
+    )]
+    pub position: Account<'info, Position>,
+
+    #[account(mut,
+        seeds = [b"tick", pool.key().as_ref(), &position.tick_lower.to_le_bytes()],
+        bump = lower_tick.bump,
+    )]
+    pub lower_tick: Account<'info, Tick>,
+
+    #[account(mut,
+        seeds = [b"tick", pool.key().as_ref(), &position.tick_upper.to_le_bytes()],
+        bump = upper_tick.bump,
+    )]
+    pub upper_tick: Account<'info, Tick>,
+
+    // Record of the orders placed on the fill tick along with this one, closed by the last of them to be claimed
+    #[account(
+        mut,
+        seeds = [
+            b"range_order_fill",
+            pool.key().as_ref(),
+            &position.range_order_fill_tick().to_le_bytes(),
+            &[position.range_order_sells_a as u8],
+            &position.range_order_fills.to_le_bytes(),
+        ],
+        bump = range_order_fill.bump,
+    )]
+    pub range_order_fill: Account<'info, RangeOrderFill>,
+
+    #[account(
+        mut,
+        address = pool.token_a_vault,
+        token::mint = pool.token_a_mint,
+        token::authority = pool,
+        token::token_program = token_program_a,
+    )]
+    pub token_a_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        address = pool.token_b_vault,
+        token::mint = pool.token_b_mint,
+        token::authority = pool,
+        token::token_program = token_program_b,
+    )]
+    pub token_b_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        token::mint = pool.token_a_mint,
+        token::authority = owner,
+        token::token_program = token_program_a,
+    )]
+    pub owner_token_a: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        token::mint = pool.token_b_mint,
+        token::authority = owner,
+        token::token_program = token_program_b,
+    )]
+    pub owner_token_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        address = pool.token_a_mint,
+        constraint = config.is_transfer_hook_allowed(get_transfer_hook_program_id(&token_mint_a)) @ Errors::TransferHookNotAllowed,
+    )]
+    pub token_mint_a: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        address = pool.token_b_mint,
+        constraint = config.is_transfer_hook_allowed(get_transfer_hook_program_id(&token_mint_b)) @ Errors::TransferHookNotAllowed,
+    )]
+    pub token_mint_b: Box<InterfaceAccount<'info, Mint>>,
+    pub token_program_a: Interface<'info, TokenInterface>,
+    pub token_program_b: Interface<'info, TokenInterface>,
+}
+
+// Claims the filled side of a range order, or cancels it at the current price if it has not filled yet
+pub fn claim_range_order_handler<'info>(ctx: Context<'_, '_, '_, 'info, ClaimRangeOrder<'info>>) -> Result<()> {
+    ctx.accounts.config.check_not_paused(&ctx.accounts.pool, PAUSE_WITHDRAWALS)?;
+
+    let pool = &mut ctx.accounts.pool;
+    let position = &mut ctx.accounts.position;
+    let lower_tick = &mut ctx.accounts.lower_tick;
+    let upper_tick = &mut ctx.accounts.upper_tick;
+    let range_order_fill = &mut ctx.accounts.range_order_fill;
+
+    // Record the tick and liquidity in effect until now, before the pool liquidity changes
+    let timestamp = Clock::get()?.unix_timestamp;
+    write_observation(&ctx.accounts.observation, timestamp, pool.tick_current, pool.liquidity)?;
+
+    // Accrue rewards before the pool liquidity changes
+    update_pool_rewards(pool, timestamp)?;
+
+    let (amount_a, amount_b) = if position.is_range_order_filled(lower_tick, upper_tick) {
+        // Credit fees and rewards accrued up to the fill, from the growth inside the range recorded then
+        range_order_fill.archive(if position.range_order_sells_a { upper_tick } else { lower_tick })?;
+        update_position_fees(position, range_order_fill.fee_growth_inside_a, range_order_fill.fee_growth_inside_b)?;
+        update_position_rewards(position, range_order_fill.reward_growths_inside)?;
+
+        // The liquidity already left the ticks when the order filled, it is held fully in the other token
+        compute_withdraw_amount(
+            position.liquidity,
+            position.tick_upper,
+            position.tick_lower,
+            get_sqrt_price_from_tick(position.range_order_fill_tick())?,
+        )?
+    } else {
+        // Credit fees accrued since the last checkpoint
+        let (fee_growth_inside_a, fee_growth_inside_b) = get_fee_growth_inside(
+            lower_tick,
+            upper_tick,
+            pool.tick_current,
+            pool.fee_growth_global_a,
+            pool.fee_growth_global_b,
+        );
+        update_position_fees(position, fee_growth_inside_a, fee_growth_inside_b)?;
+
+        // Same for rewards
+        let reward_growths_inside = get_reward_growths_inside(
+            lower_tick,
+            upper_tick,
+            pool.tick_current,
+            get_reward_growths_global(pool),
+        );
+        update_position_rewards(position, reward_growths_inside)?;
+
+        // Cancel the open order
+        update_tick_liquidity(lower_tick, upper_tick, -(position.liquidity as i128))?;
+        if position.range_order_sells_a {
+            upper_tick.range_order_liquidity_up = upper_tick
+                .range_order_liquidity_up
+                .checked_sub(position.liquidity)
+                .ok_or(Errors::MathError)?;
+        } else {
+            lower_tick.range_order_liquidity_down = lower_tick
+                .range_order_liquidity_down
+                .checked_sub(position.liquidity)
+                .ok_or(Errors::MathError)?;
+        }
+        if position.tick_lower <= pool.tick_current && pool.tick_current < position.tick_upper {
+            pool.liquidity = pool
+                .liquidity
+                .checked_sub(position.liquidity)
+                .ok_or(Errors::MathError)?;
+        }
+
+        compute_withdraw_amount(
+            position.liquidity,
+            position.tick_upper,
+            position.tick_lower,
+            pool.sqrt_price,
+        )?
+    };
+
+    // The order leaves its fill record
+    range_order_fill.liquidity = range_order_fill
+        .liquidity
+        .checked_sub(position.liquidity)
+        .ok_or(Errors::MathError)?;
+
+    // Rewards owed have to be collected before the position closes
+    check_rewards_collected(pool, position, ctx.remaining_accounts)?;
+
+    // Release the slot so it can be reused by a new position
+    ctx.accounts.position_bundle.free_slot(position.bundle_index)?;
+
+    // Principal plus fees still owed to the position
+    let amount_a_u64 = u64::try_from(amount_a)
+        .ok()
+        .and_then(|amount| amount.checked_add(position.token_a_earned))
+        .ok_or(Errors::TokenMaxExceeded)?;
+    let amount_b_u64 = u64::try_from(amount_b)
+        .ok()
+        .and_then(|amount| amount.checked_add(position.token_b_earned))
+        .ok_or(Errors::TokenMaxExceeded)?;
+    position.token_a_earned = 0;
+    position.token_b_earned = 0;
+
+    // Transfer tokens to owner
+    transfer_from_vault(
+        pool,
+        &ctx.accounts.token_a_vault,
+        ctx.accounts.owner_token_a.to_account_info(),
+        &ctx.accounts.token_mint_a,
+        &ctx.accounts.token_program_a,
+        ctx.remaining_accounts,
+        amount_a_u64,
+    )?;
+    transfer_from_vault(
+        pool,
+        &ctx.accounts.token_b_vault,
+        ctx.accounts.owner_token_b.to_account_info(),
+        &ctx.accounts.token_mint_b,
+        &ctx.accounts.token_program_b,
+        ctx.remaining_accounts,
+        amount_b_u64,
+    )?;
+
+    emit_cpi!(LiquidityDecreased {
+        pool: pool.key(),
+        position: position.key(),
+        owner: position.owner,
+        tick_lower: position.tick_lower,
+        tick_upper: position.tick_upper,
+        liquidity: position.liquidity,
+        amount_a: amount_a_u64,
+        amount_b: amount_b_u64,
+    });
+
+    // The last order of the record to be claimed gets its rent back
+    if ctx.accounts.range_order_fill.liquidity == 0 {
+        ctx.accounts.range_order_fill.close(ctx.accounts.owner.to_account_info())?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,180 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::errors::Errors;
+use crate::state::{Config, Pool, Position, PositionBundle, RangeOrderFill, Tick, PAUSE_FEE_COLLECTION};
+use crate::math::fee_math::*;
+use crate::utils::{get_transfer_hook_program_id, transfer_from_vault};
+use crate::events::FeesCollected;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CollectFees<'info> {
+    #[account(
+        seeds = [
+            b"pool",
+            pool.token_a_mint.as_ref(),
+            pool.token_b_mint.as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        seeds = [
+            b"position_bundle",
+            pool.key().as_ref(),
+            position_bundle.owner.as_ref(),
+        ],
+        bump = position_bundle.bump,
+    )]
+    pub position_bundle: Account<'info, PositionBundle>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"position",
+            position_bundle.key().as_ref(),
+            position.bundle_index.to_le_bytes().as_ref(),
+        ],
+        bump = position.bump,
+        constraint = position.is_authorized(&authority.key()) @ Errors::Unauthorized,
+    )]
+    pub position: Account<'info, Position>,
+
+    #[account(
+        seeds = [b"tick", pool.key().as_ref(), &position.tick_lower.to_le_bytes()],
+        bump = lower_tick.bump,
+    )]
+    pub lower_tick: Account<'info, Tick>,
+
+    #[account(
+        seeds = [b"tick", pool.key().as_ref(), &position.tick_upper.to_le_bytes()],
+        bump = upper_tick.bump,
+    )]
+    pub upper_tick: Account<'info, Tick>,
+
+    // Fill record of a range order, a filled order earned fees and rewards up to its fill
+    #[account(
+        mut,
+        seeds = [
+            b"range_order_fill",
+            pool.key().as_ref(),
+            &position.range_order_fill_tick().to_le_bytes(),
+            &[position.range_order_sells_a as u8],
+            &position.range_order_fills.to_le_bytes(),
+        ],
+        bump = range_order_fill.bump,
+    )]
+    pub range_order_fill: Option<Account<'info, RangeOrderFill>>,
+
+    #[account(
+        mut,
+        address = pool.token_a_vault,
+        token::mint = pool.token_a_mint,
+        token::authority = pool,
+        token::token_program = token_program_a,
+    )]
+    pub token_a_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        address = pool.token_b_vault,
+        token::mint = pool.token_b_mint,
+        token::authority = pool,
+        token::token_program = token_program_b,
+    )]
+    pub token_b_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    // Fees are always paid to the position owner, even when a delegate collects
+    #[account(
+        mut,
+        token::mint = pool.token_a_mint,
+        token::authority = position.owner,
+        token::token_program = token_program_a,
+    )]
+    pub owner_token_a: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        token::mint = pool.token_b_mint,
+        token::authority = position.owner,
+        token::token_program = token_program_b,
+    )]
+    pub owner_token_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    // Position owner or delegate
+    pub authority: Signer<'info>,
+    #[account(
+        address = pool.token_a_mint,
+        constraint = config.is_transfer_hook_allowed(get_transfer_hook_program_id(&token_mint_a)) @ Errors::TransferHookNotAllowed,
+    )]
+    pub token_mint_a: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        address = pool.token_b_mint,
+        constraint = config.is_transfer_hook_allowed(get_transfer_hook_program_id(&token_mint_b)) @ Errors::TransferHookNotAllowed,
+    )]
+    pub token_mint_b: Box<InterfaceAccount<'info, Mint>>,
+    pub token_program_a: Interface<'info, TokenInterface>,
+    pub token_program_b: Interface<'info, TokenInterface>,
+}
+
+pub fn collect_fees_handler<'info>(ctx: Context<'_, '_, '_, 'info, CollectFees<'info>>) -> Result<()> {
+    ctx.accounts.config.check_not_paused(&ctx.accounts.pool, PAUSE_FEE_COLLECTION)?;
+
+    let pool = &ctx.accounts.pool;
+    let position = &mut ctx.accounts.position;
+
+    // Credit fees accrued since the last checkpoint, a filled range order earned them up to its fill
+    if position.is_range_order_filled(&ctx.accounts.lower_tick, &ctx.accounts.upper_tick) {
+        let fill_tick = if position.range_order_sells_a { &ctx.accounts.upper_tick } else { &ctx.accounts.lower_tick };
+        let range_order_fill = ctx.accounts.range_order_fill.as_mut().ok_or(Errors::InvalidRangeOrderFill)?;
+        range_order_fill.archive(fill_tick)?;
+        update_position_fees(position, range_order_fill.fee_growth_inside_a, range_order_fill.fee_growth_inside_b)?;
+    } else {
+        let (fee_growth_inside_a, fee_growth_inside_b) = get_fee_growth_inside(
+            &ctx.accounts.lower_tick,
+            &ctx.accounts.upper_tick,
+            pool.tick_current,
+            pool.fee_growth_global_a,
+            pool.fee_growth_global_b,
+        );
+        update_position_fees(position, fee_growth_inside_a, fee_growth_inside_b)?;
+    }
+
+    let amount_a = position.token_a_earned;
+    let amount_b = position.token_b_earned;
+    position.token_a_earned = 0;
+    position.token_b_earned = 0;
+
+    // Transfer fees to owner
+    transfer_from_vault(
+        pool,
+        &ctx.accounts.token_a_vault,
+        ctx.accounts.owner_token_a.to_account_info(),
+        &ctx.accounts.token_mint_a,
+        &ctx.accounts.token_program_a,
+        ctx.remaining_accounts,
+        amount_a,
+    )?;
+    transfer_from_vault(
+        pool,
+        &ctx.accounts.token_b_vault,
+        ctx.accounts.owner_token_b.to_account_info(),
+        &ctx.accounts.token_mint_b,
+        &ctx.accounts.token_program_b,
+        ctx.remaining_accounts,
+        amount_b,
+    )?;
+
+    emit_cpi!(FeesCollected {
+        pool: pool.key(),
+        position: position.key(),
+        owner: position.owner,
+        amount_a,
+        amount_b,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,153 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::errors::Errors;
+use crate::state::{Config, Pool, Position, PositionBundle, RangeOrderFill, Tick, NUM_REWARDS, PAUSE_WITHDRAWALS};
+use crate::math::reward_math::*;
+use crate::utils::{get_transfer_hook_program_id, transfer_from_vault};
+use crate::events::RewardCollected;
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(reward_index: u8)]
+pub struct CollectReward<'info> {
+    #[account(mut,
+        seeds = [
+            b"pool",
+            pool.token_a_mint.as_ref(),
+            pool.token_b_mint.as_ref(),
+        ],
+        bump = pool.bump,
+        constraint = (reward_index as usize) < NUM_REWARDS @ Errors::InvalidRewardIndex,
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        seeds = [
+            b"position_bundle",
+            pool.key().as_ref(),
+            position_bundle.owner.as_ref(),
+        ],
+        bump = position_bundle.bump,
+    )]
+    pub position_bundle: Account<'info, PositionBundle>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"position",
+            position_bundle.key().as_ref(),
+            position.bundle_index.to_le_bytes().as_ref(),
+        ],
+        bump = position.bump,
+        constraint = position.is_authorized(&authority.key()) @ Errors::Unauthorized,
+    )]
+    pub position: Account<'info, Position>,
+
+    #[account(
+        seeds = [b"tick", pool.key().as_ref(), &position.tick_lower.to_le_bytes()],
+        bump = lower_tick.bump,
+    )]
+    pub lower_tick: Account<'info, Tick>,
+
+    #[account(
+        seeds = [b"tick", pool.key().as_ref(), &position.tick_upper.to_le_bytes()],
+        bump = upper_tick.bump,
+    )]
+    pub upper_tick: Account<'info, Tick>,
+
+    // Fill record of a range order, a filled order earned fees and rewards up to its fill
+    #[account(
+        mut,
+        seeds = [
+            b"range_order_fill",
+            pool.key().as_ref(),
+            &position.range_order_fill_tick().to_le_bytes(),
+            &[position.range_order_sells_a as u8],
+            &position.range_order_fills.to_le_bytes(),
+        ],
+        bump = range_order_fill.bump,
+    )]
+    pub range_order_fill: Option<Account<'info, RangeOrderFill>>,
+
+    #[account(
+        mut,
+        address = pool.reward_infos[reward_index as usize].vault @ Errors::InvalidRewardIndex,
+        token::token_program = token_program,
+    )]
+    pub reward_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        address = pool.reward_infos[reward_index as usize].mint @ Errors::InvalidRewardIndex,
+        constraint = config.is_transfer_hook_allowed(get_transfer_hook_program_id(&reward_mint)) @ Errors::TransferHookNotAllowed,
+    )]
+    pub reward_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    // Rewards are always paid to the position owner, even when a delegate collects
+    #[account(
+        mut,
+        token::mint = reward_vault.mint,
+        token::authority = position.owner,
+        token::token_program = token_program,
+    )]
+    pub owner_reward_token: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    // Position owner or delegate
+    pub authority: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+pub fn collect_reward_handler<'info>(ctx: Context<'_, '_, '_, 'info, CollectReward<'info>>, reward_index: u8) -> Result<()> {
+    // A position cannot close with rewards owed, so collecting them follows the withdrawal rules to keep exits open
+    ctx.accounts.config.check_not_paused(&ctx.accounts.pool, PAUSE_WITHDRAWALS)?;
+
+    let pool = &mut ctx.accounts.pool;
+    let position = &mut ctx.accounts.position;
+    let index = reward_index as usize;
+
+    // Credit rewards accrued since the last checkpoint, a filled range order earned them up to its fill
+    update_pool_rewards(pool, Clock::get()?.unix_timestamp)?;
+    if position.is_range_order_filled(&ctx.accounts.lower_tick, &ctx.accounts.upper_tick) {
+        let fill_tick = if position.range_order_sells_a { &ctx.accounts.upper_tick } else { &ctx.accounts.lower_tick };
+        let range_order_fill = ctx.accounts.range_order_fill.as_mut().ok_or(Errors::InvalidRangeOrderFill)?;
+        range_order_fill.archive(fill_tick)?;
+        update_position_rewards(position, range_order_fill.reward_growths_inside)?;
+    } else {
+        let reward_growths_inside = get_reward_growths_inside(
+            &ctx.accounts.lower_tick,
+            &ctx.accounts.upper_tick,
+            pool.tick_current,
+            get_reward_growths_global(pool),
+        );
+        update_position_rewards(position, reward_growths_inside)?;
+    }
+
+    // Pay out what the vault holds, the rest stays owed until the vault is topped up
+    let amount = position.reward_infos[index]
+        .amount_owed
+        .min(ctx.accounts.reward_vault.amount);
+    position.reward_infos[index].amount_owed -= amount;
+
+    transfer_from_vault(
+        pool,
+        &ctx.accounts.reward_vault,
+        ctx.accounts.owner_reward_token.to_account_info(),
+        &ctx.accounts.reward_mint,
+        &ctx.accounts.token_program,
+        ctx.remaining_accounts,
+        amount,
+    )?;
+
+    emit_cpi!(RewardCollected {
+        pool: pool.key(),
+        position: position.key(),
+        owner: position.owner,
+        reward_index,
+        amount,
+    });
+
+    Ok(())
+}
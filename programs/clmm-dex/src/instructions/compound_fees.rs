@@ -0,0 +1,160 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::Errors;
+use crate::state::{Config, Observation, Pool, Position, PositionBundle, Tick, PAUSE_DEPOSITS};
+use crate::math::compute_amount::*;
+use crate::math::fee_math::*;
+use crate::math::oracle_math::write_observation;
+use crate::math::reward_math::*;
+use crate::math::tick_math::update_tick_liquidity;
+use crate::events::FeesCompounded;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CompoundFees<'info> {
+    #[account(mut,
+        seeds = [
+            b"pool",
+            pool.token_a_mint.as_ref(),
+            pool.token_b_mint.as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut,
+        seeds = [b"observation", pool.key().as_ref()],
+        bump = observation.load()?.bump,
+    )]
+    pub observation: AccountLoader<'info, Observation>,
+
+    #[account(
+        seeds = [
+            b"position_bundle",
+            pool.key().as_ref(),
+            position_bundle.owner.as_ref(),
+        ],
+        bump = position_bundle.bump,
+    )]
+    pub position_bundle: Account<'info, PositionBundle>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"position",
+            position_bundle.key().as_ref(),
+            position.bundle_index.to_le_bytes().as_ref(),
+        ],
+        bump = position.bump,
+        constraint = position.is_authorized(&authority.key()) @ Errors::Unauthorized,
+        constraint = !position.range_order @ Errors::InvalidRangeOrder,
+    )]
+    pub position: Account<'info, Position>,
+
+    #[account(mut,
+        seeds = [b"tick", pool.key().as_ref(), &position.tick_lower.to_le_bytes()],
+        bump = lower_tick.bump,
+    )]
+    pub lower_tick: Account<'info, Tick>,
+
+    #[account(mut,
+        seeds = [b"tick", pool.key().as_ref(), &position.tick_upper.to_le_bytes()],
+        bump = upper_tick.bump,
+    )]
+    pub upper_tick: Account<'info, Tick>,
+
+    // Position owner or delegate
+    pub authority: Signer<'info>,
+}
+
+// Reinvests the accrued fees into the same range, the fees already sit in the vaults so nothing is transferred
+pub fn compound_fees_handler(ctx: Context<CompoundFees>) -> Result<()> {
+    ctx.accounts.config.check_not_paused(&ctx.accounts.pool, PAUSE_DEPOSITS)?;
+
+    let pool = &mut ctx.accounts.pool;
+    let position = &mut ctx.accounts.position;
+    let lower_tick = &mut ctx.accounts.lower_tick;
+    let upper_tick = &mut ctx.accounts.upper_tick;
+
+    // Credit fees accrued since the last checkpoint
+    let (fee_growth_inside_a, fee_growth_inside_b) = get_fee_growth_inside(
+        lower_tick,
+        upper_tick,
+        pool.tick_current,
+        pool.fee_growth_global_a,
+        pool.fee_growth_global_b,
+    );
+    update_position_fees(position, fee_growth_inside_a, fee_growth_inside_b)?;
+
+    // Record the tick and liquidity in effect until now, before the pool liquidity changes
+    let timestamp = Clock::get()?.unix_timestamp;
+    write_observation(&ctx.accounts.observation, timestamp, pool.tick_current, pool.liquidity)?;
+
+    // Credit rewards before the position liquidity changes
+    update_pool_rewards(pool, timestamp)?;
+    let reward_growths_inside = get_reward_growths_inside(
+        lower_tick,
+        upper_tick,
+        pool.tick_current,
+        get_reward_growths_global(pool),
+    );
+    update_position_rewards(position, reward_growths_inside)?;
+
+    // Maximum liquidity the owed fees can add at the current price
+    let liquidity = get_liquidity_for_amounts(
+        position.token_a_earned as u128,
+        position.token_b_earned as u128,
+        position.tick_upper,
+        position.tick_lower,
+        pool.sqrt_price,
+    )?;
+    require!(liquidity > 0, Errors::InvalidAmount);
+
+    let (amount_a, amount_b) = compute_deposit_amount(
+        liquidity,
+        position.tick_upper,
+        position.tick_lower,
+        pool.sqrt_price,
+    )?;
+
+    let amount_a = u64::try_from(amount_a).map_err(|_| Errors::TokenMaxExceeded)?;
+    let amount_b = u64::try_from(amount_b).map_err(|_| Errors::TokenMaxExceeded)?;
+
+    // Whatever the new liquidity does not use stays credited as owed
+    position.token_a_earned = position
+        .token_a_earned
+        .checked_sub(amount_a)
+        .ok_or(Errors::MathError)?;
+    position.token_b_earned = position
+        .token_b_earned
+        .checked_sub(amount_b)
+        .ok_or(Errors::MathError)?;
+
+    position.liquidity = position
+        .liquidity
+        .checked_add(liquidity)
+        .ok_or(Errors::MultiplicationOverflow)?;
+
+    update_tick_liquidity(lower_tick, upper_tick, liquidity as i128)?;
+
+    // Update pool liquidity if position is active at current tick
+    if position.tick_lower <= pool.tick_current && pool.tick_current < position.tick_upper {
+        pool.liquidity = pool
+            .liquidity
+            .checked_add(liquidity)
+            .ok_or(Errors::MultiplicationOverflow)?;
+    }
+
+    emit_cpi!(FeesCompounded {
+        pool: pool.key(),
+        position: position.key(),
+        liquidity,
+        amount_a,
+        amount_b,
+    });
+
+    Ok(())
+}
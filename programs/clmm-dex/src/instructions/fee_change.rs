@@ -0,0 +1,115 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::Errors;
+use crate::events::{DynamicFeeSet, FeeChangeApplied, FeeChangeProposed};
+use crate::state::{Config, DynamicFee, Pool};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ProposeFeeChange<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut,
+        seeds = [
+            b"pool",
+            pool.token_a_mint.as_ref(),
+            pool.token_b_mint.as_ref(),
+        ],
+        bump = pool.bump,
+        has_one = pool_authority,
+    )]
+    pub pool: Account<'info, Pool>,
+
+    pub pool_authority: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ApplyFeeChange<'info> {
+    #[account(mut,
+        seeds = [
+            b"pool",
+            pool.token_a_mint.as_ref(),
+            pool.token_b_mint.as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool: Account<'info, Pool>,
+}
+
+// Records a pending fee that takes effect once the configured delay has passed, replacing any earlier proposal
+pub fn propose_fee_change_handler(ctx: Context<ProposeFeeChange>, new_fee: u8) -> Result<()> {
+    let pool = &mut ctx.accounts.pool;
+    // The pending changes may apply in either order, so the fee has to fit the dynamic fee now and the one pending
+    pool.dynamic_fee.validate(new_fee)?;
+    if pool.pending_dynamic_fee_activation != 0 {
+        pool.pending_dynamic_fee.validate(new_fee)?;
+    }
+    let activation_timestamp = Clock::get()?
+        .unix_timestamp
+        .checked_add(ctx.accounts.config.fee_change_delay)
+        .ok_or(Errors::MathError)?;
+
+    pool.pending_fee = new_fee;
+    pool.pending_fee_activation = activation_timestamp;
+
+    emit_cpi!(FeeChangeProposed {
+        pool: pool.key(),
+        current_fee: pool.fee,
+        pending_fee: new_fee,
+        activation_timestamp,
+    });
+
+    Ok(())
+}
+
+// Commits the pending base fee and dynamic fee changes whose activation time has passed, anyone may crank it
+pub fn apply_fee_change_handler(ctx: Context<ApplyFeeChange>) -> Result<()> {
+    let pool = &mut ctx.accounts.pool;
+    require!(
+        pool.pending_fee_activation != 0 || pool.pending_dynamic_fee_activation != 0,
+        Errors::NoPendingFeeChange
+    );
+
+    let timestamp = Clock::get()?.unix_timestamp;
+    let fee_active = pool.pending_fee_activation != 0 && timestamp >= pool.pending_fee_activation;
+    let dynamic_fee_active = pool.pending_dynamic_fee_activation != 0 && timestamp >= pool.pending_dynamic_fee_activation;
+    require!(fee_active || dynamic_fee_active, Errors::FeeChangeNotActive);
+
+    if fee_active {
+        let old_fee = pool.fee;
+        pool.fee = pool.pending_fee;
+        pool.pending_fee = 0;
+        pool.pending_fee_activation = 0;
+
+        emit_cpi!(FeeChangeApplied {
+            pool: pool.key(),
+            old_fee,
+            new_fee: pool.fee,
+        });
+    }
+
+    if dynamic_fee_active {
+        // The volatility state starts fresh with the new parameters
+        let tick_current = pool.tick_current;
+        pool.dynamic_fee = DynamicFee {
+            tick_reference: tick_current,
+            last_update_timestamp: timestamp,
+            ..pool.pending_dynamic_fee
+        };
+        pool.pending_dynamic_fee = DynamicFee::default();
+        pool.pending_dynamic_fee_activation = 0;
+
+        emit_cpi!(DynamicFeeSet {
+            pool: pool.key(),
+            enabled: pool.dynamic_fee.enabled,
+            max_fee: pool.dynamic_fee.max_fee,
+        });
+    }
+
+    // Holds for every order of the pending changes since each was checked against the other at proposal
+    pool.dynamic_fee.validate(pool.fee)?;
+
+    Ok(())
+}
@@ -0,0 +1,264 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::instructions::{load_current_index_checked, load_instruction_at_checked};
+use anchor_lang::Discriminator;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::errors::Errors;
+use crate::math::dynamic_fee_math::get_active_fee;
+use crate::math::fee_math::{get_fee_growth_delta, get_flash_loan_fee};
+use crate::state::{Config, Pool, PAUSE_FLASH_LOANS};
+use crate::utils::{get_transfer_fee_included_amount, get_transfer_hook_program_id, transfer_from_user, transfer_from_vault};
+use crate::events::{FlashLoanRepaid, FlashLoanTaken};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct FlashLoan<'info> {
+    #[account(mut,
+        seeds = [
+            b"pool",
+            pool.token_a_mint.as_ref(),
+            pool.token_b_mint.as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut, address = pool.token_a_vault)]
+    pub token_a_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut, address = pool.token_b_vault)]
+    pub token_b_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        token::mint = pool.token_a_mint,
+        token::token_program = token_program_a,
+    )]
+    pub borrower_token_a: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        token::mint = pool.token_b_mint,
+        token::token_program = token_program_b,
+    )]
+    pub borrower_token_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub borrower: Signer<'info>,
+    #[account(
+        address = pool.token_a_mint,
+        constraint = config.is_transfer_hook_allowed(get_transfer_hook_program_id(&token_mint_a)) @ Errors::TransferHookNotAllowed,
+    )]
+    pub token_mint_a: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        address = pool.token_b_mint,
+        constraint = config.is_transfer_hook_allowed(get_transfer_hook_program_id(&token_mint_b)) @ Errors::TransferHookNotAllowed,
+    )]
+    pub token_mint_b: Box<InterfaceAccount<'info, Mint>>,
+    pub token_program_a: Interface<'info, TokenInterface>,
+    pub token_program_b: Interface<'info, TokenInterface>,
+
+    /// CHECK: instructions sysvar, used to find the flash_repay later in the transaction
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+}
+
+// The pool must stay the first account, flash_loan matches the repayment by it
+#[event_cpi]
+#[derive(Accounts)]
+pub struct FlashRepay<'info> {
+    #[account(mut,
+        seeds = [
+            b"pool",
+            pool.token_a_mint.as_ref(),
+            pool.token_b_mint.as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut, address = pool.token_a_vault)]
+    pub token_a_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut, address = pool.token_b_vault)]
+    pub token_b_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        token::mint = pool.token_a_mint,
+        token::authority = repayer,
+        token::token_program = token_program_a,
+    )]
+    pub repayer_token_a: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        token::mint = pool.token_b_mint,
+        token::authority = repayer,
+        token::token_program = token_program_b,
+    )]
+    pub repayer_token_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub repayer: Signer<'info>,
+    #[account(
+        address = pool.token_a_mint,
+        constraint = config.is_transfer_hook_allowed(get_transfer_hook_program_id(&token_mint_a)) @ Errors::TransferHookNotAllowed,
+    )]
+    pub token_mint_a: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        address = pool.token_b_mint,
+        constraint = config.is_transfer_hook_allowed(get_transfer_hook_program_id(&token_mint_b)) @ Errors::TransferHookNotAllowed,
+    )]
+    pub token_mint_b: Box<InterfaceAccount<'info, Mint>>,
+    pub token_program_a: Interface<'info, TokenInterface>,
+    pub token_program_b: Interface<'info, TokenInterface>,
+}
+
+// Lends vault tokens for the rest of the transaction, a flash_repay for the same pool and amounts must follow it
+pub fn flash_loan_handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, FlashLoan<'info>>,
+    amount_a: u64,
+    amount_b: u64,
+) -> Result<()> {
+    ctx.accounts.config.check_not_paused(&ctx.accounts.pool, PAUSE_FLASH_LOANS)?;
+    require!(amount_a > 0 || amount_b > 0, Errors::InvalidAmount);
+
+    let pool = &mut ctx.accounts.pool;
+    require!(
+        pool.flash_loan_amount_a == 0 && pool.flash_loan_amount_b == 0,
+        Errors::FlashLoanInProgress
+    );
+    // The fee is paid to in-range liquidity, without any it would be stranded in the vaults
+    require!(pool.liquidity > 0, Errors::InsufficientLiquidity);
+
+    require_flash_repay(&ctx.accounts.instructions, pool.key(), amount_a, amount_b)?;
+
+    // Fees are fixed now so swaps or fee changes inside the loan do not alter what is owed
+    let fee_rate = get_active_fee(pool, Clock::get()?.unix_timestamp);
+    pool.flash_loan_amount_a = amount_a;
+    pool.flash_loan_amount_b = amount_b;
+    pool.flash_loan_fee_a = get_flash_loan_fee(amount_a, fee_rate)?;
+    pool.flash_loan_fee_b = get_flash_loan_fee(amount_b, fee_rate)?;
+
+    transfer_from_vault(
+        pool,
+        &ctx.accounts.token_a_vault,
+        ctx.accounts.borrower_token_a.to_account_info(),
+        &ctx.accounts.token_mint_a,
+        &ctx.accounts.token_program_a,
+        ctx.remaining_accounts,
+        amount_a,
+    )?;
+    transfer_from_vault(
+        pool,
+        &ctx.accounts.token_b_vault,
+        ctx.accounts.borrower_token_b.to_account_info(),
+        &ctx.accounts.token_mint_b,
+        &ctx.accounts.token_program_b,
+        ctx.remaining_accounts,
+        amount_b,
+    )?;
+
+    emit_cpi!(FlashLoanTaken {
+        pool: pool.key(),
+        borrower: ctx.accounts.borrower.key(),
+        amount_a,
+        amount_b,
+        fee_a: pool.flash_loan_fee_a,
+        fee_b: pool.flash_loan_fee_b,
+    });
+
+    Ok(())
+}
+
+// Returns the principal plus fee of the outstanding loan and credits the fee to LPs through fee growth
+pub fn flash_repay_handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, FlashRepay<'info>>,
+    amount_a: u64,
+    amount_b: u64,
+) -> Result<()> {
+    let pool = &mut ctx.accounts.pool;
+    // A flash swap callback must not settle the loan the pool is being swapped against
+    require!(!pool.locked, Errors::PoolLocked);
+    require!(
+        pool.flash_loan_amount_a == amount_a && pool.flash_loan_amount_b == amount_b,
+        Errors::InvalidFlashRepay
+    );
+    require!(amount_a > 0 || amount_b > 0, Errors::InvalidFlashRepay);
+
+    let fee_a = pool.flash_loan_fee_a;
+    let fee_b = pool.flash_loan_fee_b;
+    pool.fee_growth_global_a = pool
+        .fee_growth_global_a
+        .wrapping_add(get_fee_growth_delta(fee_a as u128, pool.liquidity)?);
+    pool.fee_growth_global_b = pool
+        .fee_growth_global_b
+        .wrapping_add(get_fee_growth_delta(fee_b as u128, pool.liquidity)?);
+
+    pool.flash_loan_amount_a = 0;
+    pool.flash_loan_amount_b = 0;
+    pool.flash_loan_fee_a = 0;
+    pool.flash_loan_fee_b = 0;
+
+    // The vaults must receive the full amount owed, so mint transfer fees are added on top
+    let owed_a = amount_a.checked_add(fee_a).ok_or(Errors::TokenMaxExceeded)?;
+    let owed_b = amount_b.checked_add(fee_b).ok_or(Errors::TokenMaxExceeded)?;
+    let owed_a = get_transfer_fee_included_amount(&ctx.accounts.token_mint_a, owed_a)?;
+    let owed_b = get_transfer_fee_included_amount(&ctx.accounts.token_mint_b, owed_b)?;
+
+    transfer_from_user(
+        &ctx.accounts.repayer,
+        ctx.accounts.repayer_token_a.to_account_info(),
+        &ctx.accounts.token_a_vault,
+        &ctx.accounts.token_mint_a,
+        &ctx.accounts.token_program_a,
+        ctx.remaining_accounts,
+        owed_a,
+    )?;
+    transfer_from_user(
+        &ctx.accounts.repayer,
+        ctx.accounts.repayer_token_b.to_account_info(),
+        &ctx.accounts.token_b_vault,
+        &ctx.accounts.token_mint_b,
+        &ctx.accounts.token_program_b,
+        ctx.remaining_accounts,
+        owed_b,
+    )?;
+
+    emit_cpi!(FlashLoanRepaid {
+        pool: ctx.accounts.pool.key(),
+        repayer: ctx.accounts.repayer.key(),
+        amount_a,
+        amount_b,
+        fee_a,
+        fee_b,
+    });
+
+    Ok(())
+}
+
+// Checks the transaction calls flash_repay for this pool and these amounts after the current instruction
+fn require_flash_repay(instructions: &AccountInfo, pool_key: Pubkey, amount_a: u64, amount_b: u64) -> Result<()> {
+    let current_index = load_current_index_checked(instructions)? as usize;
+
+    // Called through CPI the current instruction belongs to another program, and the loan could outlive the check
+    let current_instruction = load_instruction_at_checked(current_index, instructions)?;
+    require_keys_eq!(current_instruction.program_id, crate::ID, Errors::FlashRepayMissing);
+
+    let mut index = current_index + 1;
+    while let Ok(instruction) = load_instruction_at_checked(index, instructions) {
+        if instruction.program_id == crate::ID
+            && instruction.data.starts_with(crate::instruction::FlashRepay::DISCRIMINATOR)
+            && instruction.accounts.first().map(|meta| meta.pubkey) == Some(pool_key)
+        {
+            let args = crate::instruction::FlashRepay::try_from_slice(
+                &instruction.data[crate::instruction::FlashRepay::DISCRIMINATOR.len()..],
+            )?;
+            if args.amount_a == amount_a && args.amount_b == amount_b {
+                return Ok(());
+            }
+        }
+        index += 1;
+    }
+
+    Err(Errors::FlashRepayMissing.into())
+}
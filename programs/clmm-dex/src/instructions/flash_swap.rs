@@ -0,0 +1,210 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hash;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::state::*;
+use crate::errors::*;
+use crate::math::oracle_math::write_observation;
+use crate::math::reward_math::*;
+use crate::math::dynamic_fee_math::*;
+use crate::instructions::swap::swap_internal;
+use crate::utils::{get_transfer_hook_program_id, transfer_from_vault};
+use crate::events::Swapped;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct FlashSwap<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        token::mint = pool.token_a_mint,
+        token::token_program = token_program_a,
+    )]
+    pub user_token_a_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        token::mint = pool.token_b_mint,
+        token::token_program = token_program_b,
+    )]
+    pub user_token_b_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut,
+        seeds = [
+            b"pool",
+            pool.token_a_mint.as_ref(),
+            pool.token_b_mint.as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool: Account<'info, Pool>,
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(mut,
+        seeds = [b"observation", pool.key().as_ref()],
+        bump = observation.load()?.bump,
+    )]
+    pub observation: AccountLoader<'info, Observation>,
+    #[account(mut, address = pool.token_a_vault)]
+    pub pool_token_a_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut, address = pool.token_b_vault)]
+    pub pool_token_b_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        address = pool.token_a_mint,
+        constraint = config.is_transfer_hook_allowed(get_transfer_hook_program_id(&token_mint_a)) @ Errors::TransferHookNotAllowed,
+    )]
+    pub token_mint_a: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        address = pool.token_b_mint,
+        constraint = config.is_transfer_hook_allowed(get_transfer_hook_program_id(&token_mint_b)) @ Errors::TransferHookNotAllowed,
+    )]
+    pub token_mint_b: Box<InterfaceAccount<'info, Mint>>,
+    pub token_program_a: Interface<'info, TokenInterface>,
+    pub token_program_b: Interface<'info, TokenInterface>,
+    /// CHECK: arbitrary receiver program chosen by the caller, only invoked
+    #[account(executable, constraint = callback_program.key() != crate::ID @ Errors::InvalidCallbackProgram)]
+    pub callback_program: UncheckedAccount<'info>,
+}
+
+// Sends the output first, then calls flash_swap_callback(amount_owed: u64, data: Vec<u8>) on the callback program,
+// which has to pay amount_owed into the input vault before returning.
+// The callback receives [user, input vault, input mint, input token program] followed by remaining_accounts,
+// where crossed ticks come first like in swap.
+pub fn flash_swap_handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, FlashSwap<'info>>,
+    amount_in: u64,
+    a_to_b: bool,
+    data: Vec<u8>,
+) -> Result<()> {
+    ctx.accounts.config.check_not_paused(&ctx.accounts.pool, PAUSE_SWAPS)?;
+    // Payment is checked on the input vault balance, which a loan repaid during the callback would also raise
+    require!(
+        ctx.accounts.pool.flash_loan_amount_a == 0 && ctx.accounts.pool.flash_loan_amount_b == 0,
+        Errors::FlashLoanInProgress
+    );
+
+    let timestamp = Clock::get()?.unix_timestamp;
+
+    write_observation(
+        &ctx.accounts.observation,
+        timestamp,
+        ctx.accounts.pool.tick_current,
+        ctx.accounts.pool.liquidity,
+    )?;
+    update_pool_rewards(&mut ctx.accounts.pool, timestamp)?;
+    update_volatility_accumulator(&mut ctx.accounts.pool, timestamp)?;
+
+    let sqrt_price_before = ctx.accounts.pool.sqrt_price;
+    let tick_before = ctx.accounts.pool.tick_current;
+    let liquidity_before = ctx.accounts.pool.liquidity;
+
+    let (amount_consumed, total_amount_out, fee_amount) = swap_internal(
+        &mut ctx.accounts.pool,
+        ctx.remaining_accounts,
+        amount_in,
+        a_to_b,
+        timestamp,
+    )?;
+    let amount_owed = u64::try_from(amount_consumed).map_err(|_| Errors::TokenMaxExceeded)?;
+    let amount_out = u64::try_from(total_amount_out).map_err(|_| Errors::TokenMaxExceeded)?;
+    let fee_amount = u64::try_from(fee_amount).map_err(|_| Errors::TokenMaxExceeded)?;
+    let (sqrt_price_after, tick_after, liquidity_after) = (
+        ctx.accounts.pool.sqrt_price,
+        ctx.accounts.pool.tick_current,
+        ctx.accounts.pool.liquidity,
+    );
+
+    // Output goes out before anything is paid
+    if a_to_b {
+        transfer_from_vault(
+            &ctx.accounts.pool,
+            &ctx.accounts.pool_token_b_vault,
+            ctx.accounts.user_token_b_account.to_account_info(),
+            &ctx.accounts.token_mint_b,
+            &ctx.accounts.token_program_b,
+            ctx.remaining_accounts,
+            amount_out,
+        )?;
+    } else {
+        transfer_from_vault(
+            &ctx.accounts.pool,
+            &ctx.accounts.pool_token_a_vault,
+            ctx.accounts.user_token_a_account.to_account_info(),
+            &ctx.accounts.token_mint_a,
+            &ctx.accounts.token_program_a,
+            ctx.remaining_accounts,
+            amount_out,
+        )?;
+    }
+
+    let (vault_in, mint_in, token_program_in) = if a_to_b {
+        (&ctx.accounts.pool_token_a_vault, &ctx.accounts.token_mint_a, &ctx.accounts.token_program_a)
+    } else {
+        (&ctx.accounts.pool_token_b_vault, &ctx.accounts.token_mint_b, &ctx.accounts.token_program_b)
+    };
+    let vault_in_before = vault_in.amount;
+
+    // Persist the swapped and locked pool so the callback sees the new price and cannot reenter
+    ctx.accounts.pool.locked = true;
+    ctx.accounts.pool.exit(&crate::ID)?;
+
+    let mut accounts = vec![
+        AccountMeta::new(ctx.accounts.user.key(), true),
+        AccountMeta::new(vault_in.key(), false),
+        AccountMeta::new_readonly(mint_in.key(), false),
+        AccountMeta::new_readonly(token_program_in.key(), false),
+    ];
+    let mut account_infos = vec![
+        ctx.accounts.user.to_account_info(),
+        vault_in.to_account_info(),
+        mint_in.to_account_info(),
+        token_program_in.to_account_info(),
+    ];
+    for account in ctx.remaining_accounts {
+        accounts.push(AccountMeta {
+            pubkey: account.key(),
+            is_signer: account.is_signer,
+            is_writable: account.is_writable,
+        });
+        account_infos.push(account.clone());
+    }
+
+    let mut callback_data = hash(b"global:flash_swap_callback").to_bytes()[..8].to_vec();
+    (amount_owed, data).serialize(&mut callback_data)?;
+
+    let callback = Instruction {
+        program_id: ctx.accounts.callback_program.key(),
+        accounts,
+        data: callback_data,
+    };
+    invoke(&callback, &account_infos)?;
+
+    // The input vault must have grown by at least what the swap consumed
+    let vault_in = if a_to_b { &mut ctx.accounts.pool_token_a_vault } else { &mut ctx.accounts.pool_token_b_vault };
+    vault_in.reload()?;
+    let vault_in_required = vault_in_before.checked_add(amount_owed).ok_or(Errors::TokenMaxExceeded)?;
+    require!(vault_in.amount >= vault_in_required, Errors::FlashSwapNotPaid);
+
+    // Pick up whatever the callback changed through other instructions before unlocking
+    ctx.accounts.pool.reload()?;
+    ctx.accounts.pool.locked = false;
+
+    // Price fields describe this swap, not what the callback may have done afterwards
+    emit_cpi!(Swapped {
+        pool: ctx.accounts.pool.key(),
+        user: ctx.accounts.user.key(),
+        a_to_b,
+        amount_in: amount_owed,
+        amount_out,
+        fee_amount,
+        sqrt_price_before,
+        sqrt_price_after,
+        tick_before,
+        tick_after,
+        liquidity_before,
+        liquidity_after,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,59 @@
+use anchor_lang::prelude::*;
+use crate::state::{Observation, Pool};
+use crate::events::ObservationCardinalityIncreased;
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(cardinality_next: u16)]
+pub struct IncreaseObservationCardinality<'info> {
+    #[account(
+        seeds = [
+            b"pool",
+            pool.token_a_mint.as_ref(),
+            pool.token_b_mint.as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool: Account<'info, Pool>,
+
+    // Never shrinks, a smaller cardinality_next leaves the account as is. Grows by at most
+    // Observation::MAX_CARDINALITY_INCREASE slots per call
+    #[account(mut,
+        seeds = [b"observation", pool.key().as_ref()],
+        bump = observation.load()?.bump,
+        realloc = grown_space(&observation, cardinality_next)?,
+        realloc::payer = payer,
+        realloc::zero = false,
+    )]
+    pub observation: AccountLoader<'info, Observation>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+// Room for cardinality_next slots, never less than the account holds. The header is only borrowed here, the realloc
+// needs the account data free
+fn grown_space(observation: &AccountLoader<Observation>, cardinality_next: u16) -> Result<usize> {
+    Ok(Observation::space(cardinality_next.max(observation.load()?.cardinality_next)))
+}
+
+// Pays for more observation slots, they are filled in on later writes so the TWAP window grows over time
+pub fn increase_observation_cardinality_handler(
+    ctx: Context<IncreaseObservationCardinality>,
+    cardinality_next: u16,
+) -> Result<()> {
+    let mut observation = ctx.accounts.observation.load_mut()?;
+    let cardinality_next_old = observation.cardinality_next;
+    if cardinality_next > observation.cardinality_next {
+        observation.cardinality_next = cardinality_next;
+    }
+
+    emit_cpi!(ObservationCardinalityIncreased {
+        pool: observation.pool,
+        cardinality_next_old,
+        cardinality_next_new: observation.cardinality_next,
+    });
+
+    Ok(())
+}
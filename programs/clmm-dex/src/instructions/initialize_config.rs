@@ -0,0 +1,57 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::bpf_loader_upgradeable;
+use crate::state::{Config, MAX_TRANSFER_HOOK_PROGRAMS};
+use crate::errors::Errors;
+use crate::events::ConfigInitialized;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(
+        init,
+        payer = admin,
+        seeds = [b"config"],
+        bump,
+        space = 8 + 32 + 32 + 1 + 1 + 8 + 4 + MAX_TRANSFER_HOOK_PROGRAMS * 32,
+    )]
+    pub config: Account<'info, Config>,
+
+    // Only the upgrade authority of the program may create the config
+    #[account(
+        seeds = [crate::ID.as_ref()],
+        bump,
+        seeds::program = bpf_loader_upgradeable::ID,
+        constraint = program_data.upgrade_authority_address == Some(admin.key()),
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn initialize_config_handler(
+    ctx: Context<InitializeConfig>,
+    pause_authority: Pubkey,
+    fee_change_delay: i64,
+) -> Result<()> {
+    require!(fee_change_delay >= 0, Errors::InvalidAmount);
+
+    let config = &mut ctx.accounts.config;
+
+    config.admin = ctx.accounts.admin.key();
+    config.pause_authority = pause_authority;
+    config.global_paused = false;
+    config.bump = ctx.bumps.config;
+    config.fee_change_delay = fee_change_delay;
+    config.transfer_hook_programs = Vec::new();
+
+    emit_cpi!(ConfigInitialized {
+        config: config.key(),
+        admin: config.admin,
+        pause_authority,
+        fee_change_delay,
+    });
+
+    Ok(())
+}
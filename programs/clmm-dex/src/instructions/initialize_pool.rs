@@ -1,14 +1,18 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{Token, Mint, TokenAccount};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 use crate::state::pool::*; 
-use crate::math::tick_math::*;
+use crate::state::observation::*;
+use crate::events::PoolInitialized;
 
 // Sqrt_price = sqrt(price) * 2^64
+#[event_cpi]
 #[derive(Accounts)]
 #[instruction(sqrt_price_x64: u128, tick_spacing: u16)]
 pub struct InitializePool<'info> {
-    pub token_mint_a: Account<'info, Mint>,
-    pub token_mint_b: Account<'info, Mint>,
+    #[account(mint::token_program = token_program_a)]
+    pub token_mint_a: Box<InterfaceAccount<'info, Mint>>,
+    #[account(mint::token_program = token_program_b)]
+    pub token_mint_b: Box<InterfaceAccount<'info, Mint>>,
 
     #[account(
         init,
@@ -19,30 +23,43 @@ pub struct InitializePool<'info> {
             token_mint_b.key().as_ref(),
         ],
         bump,
-        space = 8 + 32 + 32 + 32 + 32 + 32 + 16 + 4 + 16 + 2 + 1 + 16 + 16 + 16 + 16 + 1,
+        space = 8 + Pool::INIT_SPACE,
     )]
     pub pool: Account<'info, Pool>,
 
+    #[account(
+        init,
+        payer = admin,
+        seeds = [b"observation", pool.key().as_ref()],
+        bump,
+        space = Observation::space(Observation::DEFAULT_CARDINALITY),
+    )]
+    pub observation: AccountLoader<'info, Observation>,
+
     #[account(
         init,
         payer = admin,
         token::mint = token_mint_a,
         token::authority = pool,
+        token::token_program = token_program_a,
     )]
-    pub token_vault_a: Account<'info, TokenAccount>,
+    pub token_vault_a: Box<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
         init,
         payer = admin,
         token::mint = token_mint_b,
         token::authority = pool,
+        token::token_program = token_program_b,
     )]
-    pub token_vault_b: Account<'info, TokenAccount>,
+    pub token_vault_b: Box<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(mut)]
     pub admin: Signer<'info>,
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
+    // Each vault lives under the program that owns its mint, so pairs can mix SPL Token and Token-2022
+    pub token_program_a: Interface<'info, TokenInterface>,
+    pub token_program_b: Interface<'info, TokenInterface>,
     pub rent: Sysvar<'info, Rent>,
 }
 
@@ -62,18 +79,37 @@ pub fn initialize_pool_handler(
     pool.token_b_mint = ctx.accounts.token_mint_b.key();
     pool.token_a_vault = ctx.accounts.token_vault_a.key();
     pool.token_b_vault = ctx.accounts.token_vault_b.key();
-    pool.sqrt_price = sqrt_price_x64;
-    pool.tick_current = get_tick_from_sqrt_price(sqrt_price_x64)?;
-    pool.liquidity = 0;
-    pool.tick_spacing = tick_spacing;
-    pool.fee = fee;
+    pool.bump = ctx.bumps.pool;
+    pool.initialize(sqrt_price_x64, tick_spacing, fee, Clock::get()?.unix_timestamp)?;
     pool.fee_growth_global_a = fee_growth_global_a;
     pool.fee_growth_global_b = fee_growth_global_b;
-    pool.protocol_fee_a = 0;
-    pool.protocol_fee_b = 0;
-    pool.bump = ctx.bumps.pool;
-    pool.initialized_ticks = vec![pool.tick_current];
 
+    // Seed the oracle with the first observation
+    let mut data = ctx.accounts.observation.as_ref().try_borrow_mut_data()?;
+    let (observation, observations) = Observation::split_mut(&mut data);
+    observation.pool = pool.key();
+    observation.observation_index = 0;
+    observation.cardinality = Observation::DEFAULT_CARDINALITY;
+    observation.cardinality_next = Observation::DEFAULT_CARDINALITY;
+    observations[0] = ObservationEntry {
+        timestamp: pool.reward_last_updated_timestamp,
+        tick_cumulative: 0,
+        seconds_per_liquidity_cumulative: 0,
+        initialized: true,
+    };
+    observation.bump = ctx.bumps.observation;
+
+    emit_cpi!(PoolInitialized {
+        pool: pool.key(),
+        token_a_mint: pool.token_a_mint,
+        token_b_mint: pool.token_b_mint,
+        token_a_vault: pool.token_a_vault,
+        token_b_vault: pool.token_b_vault,
+        tick_spacing,
+        fee,
+        sqrt_price: sqrt_price_x64,
+        tick_current: pool.tick_current,
+    });
 
     Ok(())
 }
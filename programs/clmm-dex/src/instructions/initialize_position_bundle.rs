@@ -0,0 +1,51 @@
+use anchor_lang::prelude::*;
+use crate::state::{Pool, PositionBundle};
+use crate::events::PositionBundleInitialized;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct InitializePositionBundle<'info> {
+    #[account(
+        seeds = [
+            b"pool",
+            pool.token_a_mint.as_ref(),
+            pool.token_b_mint.as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        init,
+        payer = owner,
+        seeds = [
+            b"position_bundle",
+            pool.key().as_ref(),
+            owner.key().as_ref(),
+        ],
+        bump,
+        space = 8 + 32 + 32 + 32 + 1,
+    )]
+    pub position_bundle: Account<'info, PositionBundle>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn initialize_position_bundle_handler(ctx: Context<InitializePositionBundle>) -> Result<()> {
+    let position_bundle = &mut ctx.accounts.position_bundle;
+
+    position_bundle.pool = ctx.accounts.pool.key();
+    position_bundle.owner = ctx.accounts.owner.key();
+    position_bundle.position_bitmap = [0; 32];
+    position_bundle.bump = ctx.bumps.position_bundle;
+
+    emit_cpi!(PositionBundleInitialized {
+        pool: position_bundle.pool,
+        position_bundle: position_bundle.key(),
+        owner: position_bundle.owner,
+    });
+
+    Ok(())
+}
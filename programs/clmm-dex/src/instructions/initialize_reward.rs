@@ -0,0 +1,65 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::errors::Errors;
+use crate::state::{Pool, NUM_REWARDS};
+use crate::events::RewardInitialized;
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(reward_index: u8)]
+pub struct InitializeReward<'info> {
+    #[account(mut,
+        seeds = [
+            b"pool",
+            pool.token_a_mint.as_ref(),
+            pool.token_b_mint.as_ref(),
+        ],
+        bump = pool.bump,
+        has_one = pool_authority,
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(mint::token_program = token_program)]
+    pub reward_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        init,
+        payer = pool_authority,
+        seeds = [b"reward_vault", pool.key().as_ref(), reward_mint.key().as_ref()],
+        bump,
+        token::mint = reward_mint,
+        token::authority = pool,
+        token::token_program = token_program,
+    )]
+    pub reward_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub pool_authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+// Registers a reward token in an empty slot, emissions start once set with set_reward_emissions
+pub fn initialize_reward_handler(ctx: Context<InitializeReward>, reward_index: u8) -> Result<()> {
+    let index = reward_index as usize;
+    require!(index < NUM_REWARDS, Errors::InvalidRewardIndex);
+
+    let reward_info = &mut ctx.accounts.pool.reward_infos[index];
+    require!(!reward_info.initialized(), Errors::InvalidRewardIndex);
+
+    reward_info.mint = ctx.accounts.reward_mint.key();
+    reward_info.vault = ctx.accounts.reward_vault.key();
+    reward_info.emissions_per_second_x64 = 0;
+    reward_info.growth_global_x64 = 0;
+
+    emit_cpi!(RewardInitialized {
+        pool: ctx.accounts.pool.key(),
+        reward_index,
+        mint: ctx.accounts.reward_mint.key(),
+        vault: ctx.accounts.reward_vault.key(),
+    });
+
+    Ok(())
+}
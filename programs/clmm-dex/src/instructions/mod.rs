@@ -1,9 +1,53 @@
+pub mod claim_range_order;
+pub mod collect_fees;
+pub mod collect_reward;
+pub mod compound_fees;
+pub mod increase_observation_cardinality;
+pub mod fee_change;
+pub mod flash_loan;
+pub mod flash_swap;
+pub mod initialize_config;
 pub mod initialize_pool;
+pub mod initialize_position_bundle;
+pub mod initialize_reward;
+pub mod observe;
 pub mod provide_liquidity;
+pub mod quote_swap;
+pub mod rebalance_position;
 pub mod remove_liquidity;
+pub mod set_dynamic_fee;
+pub mod set_pause;
+pub mod set_pause_authority;
+pub mod set_position_delegate;
+pub mod set_reward_emissions;
+pub mod set_transfer_hook_allowlist;
 pub mod swap;
+pub mod swap_route;
+pub mod transfer_position;
 
+pub use claim_range_order::*;
+pub use collect_fees::*;
+pub use collect_reward::*;
+pub use compound_fees::*;
+pub use increase_observation_cardinality::*;
+pub use fee_change::*;
+pub use flash_loan::*;
+pub use flash_swap::*;
+pub use initialize_config::*;
 pub use initialize_pool::*;
+pub use initialize_position_bundle::*;
+pub use initialize_reward::*;
+pub use observe::*;
 pub use provide_liquidity::*;
+pub use quote_swap::*;
+pub use rebalance_position::*;
 pub use remove_liquidity::*;
-pub use swap::*;
\ No newline at end of file
+pub use set_dynamic_fee::*;
+pub use set_pause::*;
+pub use set_pause_authority::*;
+pub use set_position_delegate::*;
+pub use set_reward_emissions::*;
+pub use set_transfer_hook_allowlist::*;
+pub use swap::*;
+pub use swap_route::*;
+pub use transfer_position::*;
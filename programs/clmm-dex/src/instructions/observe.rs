@@ -0,0 +1,58 @@
+use anchor_lang::prelude::*;
+use crate::state::{Observation, Pool};
+use crate::math::oracle_math::observe_single;
+
+#[derive(Accounts)]
+pub struct Observe<'info> {
+    #[account(
+        seeds = [
+            b"pool",
+            pool.token_a_mint.as_ref(),
+            pool.token_b_mint.as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        seeds = [b"observation", pool.key().as_ref()],
+        bump = observation.load()?.bump,
+    )]
+    pub observation: AccountLoader<'info, Observation>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ObserveResult {
+    pub tick_cumulatives: Vec<i64>,
+    pub seconds_per_liquidity_cumulatives: Vec<u128>,
+}
+
+// Returns the cumulatives as of each seconds_ago, the TWAP between two points is
+// (tick_cumulative_1 - tick_cumulative_0) / (seconds_ago_0 - seconds_ago_1).
+// The result is passed back through return data so programs can read it by CPI.
+pub fn observe_handler(ctx: Context<Observe>, seconds_agos: Vec<u32>) -> Result<ObserveResult> {
+    let pool = &ctx.accounts.pool;
+    let data = ctx.accounts.observation.as_ref().try_borrow_data()?;
+    let (observation, observations) = Observation::split(&data);
+    let timestamp = Clock::get()?.unix_timestamp;
+
+    let mut tick_cumulatives = Vec::with_capacity(seconds_agos.len());
+    let mut seconds_per_liquidity_cumulatives = Vec::with_capacity(seconds_agos.len());
+    for seconds_ago in seconds_agos {
+        let (tick_cumulative, seconds_per_liquidity_cumulative) = observe_single(
+            observation,
+            observations,
+            timestamp,
+            seconds_ago,
+            pool.tick_current,
+            pool.liquidity,
+        )?;
+        tick_cumulatives.push(tick_cumulative);
+        seconds_per_liquidity_cumulatives.push(seconds_per_liquidity_cumulative);
+    }
+
+    Ok(ObserveResult {
+        tick_cumulatives,
+        seconds_per_liquidity_cumulatives,
+    })
+}
@@ -1,13 +1,25 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Transfer, Token, TokenAccount};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 
-use crate::state::{Pool, Position, Tick};
+use crate::state::{
+    Config, Observation, Pool, Position, PositionBundle, PositionRewardInfo, RangeOrderFill, Tick, PAUSE_DEPOSITS,
+};
 use crate::math::compute_amount::*;
+use crate::math::fee_math::*;
+use crate::math::tick_math::*;
+use crate::math::oracle_math::write_observation;
+use crate::math::reward_math::*;
 use crate::errors::{ Errors };
+use crate::utils::{
+    get_transfer_fee_included_amount, get_transfer_hook_program_id, get_user_token_account, transfer_from_user,
+    unwrap_native_sol,
+};
+use crate::events::LiquidityIncreased;
 
 
+#[event_cpi]
 #[derive(Accounts)]
-#[instruction(tick_lower: i32, tick_upper: i32, liquidity: u64)]
+#[instruction(tick_lower: i32, tick_upper: i32, liquidity: u128, bundle_index: u16, range_order: bool)]
 pub struct ProvideLiquidity<'info> {
     #[account(mut,
         seeds = [
@@ -19,79 +31,188 @@ pub struct ProvideLiquidity<'info> {
     )]
     pub pool: Account<'info, Pool>,
 
-    #[account(init, payer = owner,
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut,
+        seeds = [b"observation", pool.key().as_ref()],
+        bump = observation.load()?.bump,
+    )]
+    pub observation: AccountLoader<'info, Observation>,
+
+    #[account(mut,
         seeds = [
-            b"position",
+            b"position_bundle",
             pool.key().as_ref(),
             owner.key().as_ref(),
-            tick_lower.to_le_bytes().as_ref(),
-            tick_upper.to_le_bytes().as_ref(),
+        ],
+        bump = position_bundle.bump,
+    )]
+    pub position_bundle: Account<'info, PositionBundle>,
+
+    #[account(init, payer = owner,
+        seeds = [
+            b"position",
+            position_bundle.key().as_ref(),
+            bundle_index.to_le_bytes().as_ref(),
         ],
         bump,
-        space = 8 + 32 + 32 + 32 + 32 + 16 + 4 + 8 + 2 + 1, // Position account size
+        space = 8 + Position::INIT_SPACE,
     )]
     pub position: Account<'info, Position>,
 
     #[account(
         mut,
+        address = pool.token_a_vault,
         token::mint = pool.token_a_mint,
         token::authority = pool,
+        token::token_program = token_program_a,
     )]
-    pub token_a_vault: Account<'info, TokenAccount>,
+    pub token_a_vault: Box<InterfaceAccount<'info, TokenAccount>>,
     #[account(
         mut,
+        address = pool.token_b_vault,
         token::mint = pool.token_b_mint,
         token::authority = pool,
+        token::token_program = token_program_b,
     )]
-    pub token_b_vault: Account<'info, TokenAccount>,
+    pub token_b_vault: Box<InterfaceAccount<'info, TokenAccount>>,
 
+    // Either owner token account may be omitted on a native SOL side, lamports are then wrapped in temp_wsol_account
     #[account(
         mut,
         token::mint = pool.token_a_mint,
         token::authority = owner,
+        token::token_program = token_program_a,
     )]
-    pub owner_token_a: Account<'info, TokenAccount>,
+    pub owner_token_a: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
 
-    #[account(mut, 
-    token::mint = pool.token_b_mint,
-    token::authority = owner,
+    #[account(
+        mut,
+        token::mint = pool.token_b_mint,
+        token::authority = owner,
+        token::token_program = token_program_b,
     )]
-    pub owner_token_b: Account<'info, TokenAccount>,
+    pub owner_token_b: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
 
-    #[account(mut,
-    seeds = [b"tick", pool.key().as_ref(), &tick_lower.to_le_bytes()],
-    bump,
+    // Tick accounts are created by the first position using them, the handler initializes them
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + Tick::INIT_SPACE,
+        seeds = [b"tick", pool.key().as_ref(), &tick_lower.to_le_bytes()],
+        bump,
     )]
     pub lower_tick: Account<'info, Tick>,
 
-    #[account(mut,
-    seeds = [b"tick", pool.key().as_ref(), &tick_upper.to_le_bytes()],
-    bump,
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + Tick::INIT_SPACE,
+        seeds = [b"tick", pool.key().as_ref(), &tick_upper.to_le_bytes()],
+        bump,
     )]
     pub upper_tick: Account<'info, Tick>,
 
-    
+    // Range orders join the record of the orders placed on their fill tick since its last fill, the first one
+    // creates it
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + RangeOrderFill::INIT_SPACE,
+        seeds = [
+            b"range_order_fill",
+            pool.key().as_ref(),
+            &range_order_fill_key(&pool, tick_lower, tick_upper, &lower_tick, &upper_tick).0.to_le_bytes(),
+            &[range_order_fill_key(&pool, tick_lower, tick_upper, &lower_tick, &upper_tick).1 as u8],
+            &range_order_fill_key(&pool, tick_lower, tick_upper, &lower_tick, &upper_tick).2.to_le_bytes(),
+        ],
+        bump,
+    )]
+    pub range_order_fill: Option<Account<'info, RangeOrderFill>>,
+
+    /// CHECK: record of the orders the fill tick filled last, gone once they are all claimed. Address checked by seeds
+    #[account(
+        mut,
+        seeds = [
+            b"range_order_fill",
+            pool.key().as_ref(),
+            &range_order_fill_key(&pool, tick_lower, tick_upper, &lower_tick, &upper_tick).0.to_le_bytes(),
+            &[range_order_fill_key(&pool, tick_lower, tick_upper, &lower_tick, &upper_tick).1 as u8],
+            &range_order_fill_key(&pool, tick_lower, tick_upper, &lower_tick, &upper_tick).2.wrapping_sub(1).to_le_bytes(),
+        ],
+        bump,
+    )]
+    pub previous_range_order_fill: Option<UncheckedAccount<'info>>,
+
     #[account(mut)]
     pub owner: Signer<'info>,
-    pub token_program: Program<'info, Token>,
+    #[account(
+        address = pool.token_a_mint,
+        constraint = config.is_transfer_hook_allowed(get_transfer_hook_program_id(&token_mint_a)) @ Errors::TransferHookNotAllowed,
+    )]
+    pub token_mint_a: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        address = pool.token_b_mint,
+        constraint = config.is_transfer_hook_allowed(get_transfer_hook_program_id(&token_mint_b)) @ Errors::TransferHookNotAllowed,
+    )]
+    pub token_mint_b: Box<InterfaceAccount<'info, Mint>>,
+    /// CHECK: created and closed by the handler, address checked by seeds
+    #[account(mut, seeds = [b"wsol", owner.key().as_ref()], bump)]
+    pub temp_wsol_account: Option<UncheckedAccount<'info>>,
+    pub token_program_a: Interface<'info, TokenInterface>,
+    pub token_program_b: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
     pub rent: Sysvar<'info, Rent>,
         
 }
 
+// Fill tick, side and fill count keying the record a range order over [tick_lower, tick_upper] joins. Orders above
+// the price sell A and fill on their upper tick, the handler rejects ranges around the price.
+fn range_order_fill_key(pool: &Pool, tick_lower: i32, tick_upper: i32, lower_tick: &Tick, upper_tick: &Tick) -> (i32, bool, u64) {
+    if pool.tick_current < tick_lower {
+        (tick_upper, true, upper_tick.range_order_fills_up)
+    } else {
+        (tick_lower, false, lower_tick.range_order_fills_down)
+    }
+}
 
-pub fn provide_liquidity_handler(
-    ctx: Context<ProvideLiquidity>,
+pub fn provide_liquidity_handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, ProvideLiquidity<'info>>,
     tick_lower: i32,
     tick_upper: i32,
     liquidity: u128,
+    bundle_index: u16,
+    range_order: bool,
 ) -> Result<()> {
+    ctx.accounts.config.check_not_paused(&ctx.accounts.pool, PAUSE_DEPOSITS)?;
+
+    require!(tick_lower < tick_upper, Errors::InvalidTickRange);
+    let tick_spacing = ctx.accounts.pool.tick_spacing as i32;
+    require!(
+        tick_lower % tick_spacing == 0 && tick_upper % tick_spacing == 0,
+        Errors::InvalidTickSpacing
+    );
+    require!(liquidity > 0, Errors::InvalidAmount);
+
     let pool = &mut ctx.accounts.pool;
+    let position_bundle = &mut ctx.accounts.position_bundle;
     let position = &mut ctx.accounts.position;
     let owner = &ctx.accounts.owner;
     let lower_tick = &mut ctx.accounts.lower_tick;
     let upper_tick = &mut ctx.accounts.upper_tick;
 
+    // Record the tick and liquidity in effect until now, before the pool liquidity changes
+    let timestamp = Clock::get()?.unix_timestamp;
+    write_observation(&ctx.accounts.observation, timestamp, pool.tick_current, pool.liquidity)?;
+
+    // Accrue rewards before the pool liquidity changes
+    update_pool_rewards(pool, timestamp)?;
+    let reward_growths_global = get_reward_growths_global(pool);
+
+    // Claim the slot in the owner's bundle
+    position_bundle.occupy_slot(bundle_index)?;
+
     // Initialize position
     position.owner = owner.key();
     position.tick_lower = tick_lower;
@@ -101,43 +222,124 @@ pub fn provide_liquidity_handler(
     position.token_b_earned = 0;
     position.fee_growth_inside_a = 0;
     position.fee_growth_inside_b = 0;
+    position.delegate = None;
+    position.bundle_index = bundle_index;
+    position.range_order = false;
+    position.range_order_sells_a = false;
+    position.range_order_fills = 0;
     position.bump = ctx.bumps.position;
 
     // Initialize lower tick if not already
     if !lower_tick.initialized {
-        lower_tick.tick_index = tick_lower;
-        lower_tick.liquidity_net = 0;
-        lower_tick.fee_growth_outside_a = 0;
-        lower_tick.fee_growth_outside_b = 0;
-        lower_tick.initialized = true;
-        lower_tick.bump = ctx.bumps.lower_tick;
-        if !pool.initialized_ticks.contains(&tick_lower) {
-            pool.initialized_ticks.push(tick_lower);
-            pool.initialized_ticks.sort();
-        }
+        initialize_tick(
+            lower_tick,
+            tick_lower,
+            ctx.bumps.lower_tick,
+            pool.tick_current,
+            pool.fee_growth_global_a,
+            pool.fee_growth_global_b,
+            reward_growths_global,
+        );
+        pool.insert_initialized_tick(tick_lower)?;
     }
-    lower_tick.liquidity_net = lower_tick
-        .liquidity_net
-        .checked_add(liquidity as i128)
-        .ok_or(Errors::MultiplicationOverflow)?;
 
     // Initialize upper tick if not already
     if !upper_tick.initialized {
-        upper_tick.tick_index = tick_upper;
-        upper_tick.liquidity_net = 0;
-        upper_tick.fee_growth_outside_a = 0;
-        upper_tick.fee_growth_outside_b = 0;
-        upper_tick.initialized = true;
-        upper_tick.bump = ctx.bumps.upper_tick;
-        if !pool.initialized_ticks.contains(&tick_upper) {
-            pool.initialized_ticks.push(tick_upper);
-            pool.initialized_ticks.sort();
+        initialize_tick(
+            upper_tick,
+            tick_upper,
+            ctx.bumps.upper_tick,
+            pool.tick_current,
+            pool.fee_growth_global_a,
+            pool.fee_growth_global_b,
+            reward_growths_global,
+        );
+        pool.insert_initialized_tick(tick_upper)?;
+    }
+    update_tick_liquidity(lower_tick, upper_tick, liquidity as i128)?;
+
+    // A range order spans one tick spacing entirely on one side of the price and is registered on its fill tick
+    if range_order {
+        require!(
+            tick_upper - tick_lower == pool.tick_spacing as i32,
+            Errors::InvalidRangeOrder
+        );
+        position.range_order = true;
+        if pool.tick_current < tick_lower {
+            // All token A, filled when price crosses the upper tick upward
+            position.range_order_sells_a = true;
+            position.range_order_fills = upper_tick.range_order_fills_up;
+            upper_tick.range_order_liquidity_up = upper_tick
+                .range_order_liquidity_up
+                .checked_add(liquidity)
+                .ok_or(Errors::MultiplicationOverflow)?;
+        } else if pool.tick_current >= tick_upper {
+            // All token B, filled when price crosses the lower tick downward
+            position.range_order_sells_a = false;
+            position.range_order_fills = lower_tick.range_order_fills_down;
+            lower_tick.range_order_liquidity_down = lower_tick
+                .range_order_liquidity_down
+                .checked_add(liquidity)
+                .ok_or(Errors::MultiplicationOverflow)?;
+        } else {
+            return Err(Errors::InvalidRangeOrder.into());
+        }
+
+        let (fill_tick_index, sells_a, fills) =
+            range_order_fill_key(pool, tick_lower, tick_upper, lower_tick, upper_tick);
+        let fill_tick: &Tick = if sells_a { upper_tick } else { lower_tick };
+        let range_order_fill = ctx
+            .accounts
+            .range_order_fill
+            .as_mut()
+            .ok_or(Errors::InvalidRangeOrderFill)?;
+        if range_order_fill.pool == Pubkey::default() {
+            range_order_fill.pool = pool.key();
+            range_order_fill.tick_index = fill_tick_index;
+            range_order_fill.sells_a = sells_a;
+            range_order_fill.fills = fills;
+            range_order_fill.bump = ctx.bumps.range_order_fill.ok_or(Errors::InvalidRangeOrderFill)?;
+        }
+        range_order_fill.liquidity = range_order_fill
+            .liquidity
+            .checked_add(liquidity)
+            .ok_or(Errors::MultiplicationOverflow)?;
+
+        // This order lets the tick fill again, which overwrites the growth it kept from its last fill. Archive it
+        // for the orders filled then first, unless they were all claimed already.
+        let previous_range_order_fill = ctx
+            .accounts
+            .previous_range_order_fill
+            .as_ref()
+            .ok_or(Errors::InvalidRangeOrderFill)?;
+        if fills > 0 && *previous_range_order_fill.owner == crate::ID {
+            let mut data = previous_range_order_fill.try_borrow_mut_data()?;
+            let mut previous_fill = RangeOrderFill::try_deserialize(&mut &data[..])?;
+            previous_fill.archive(fill_tick)?;
+            previous_fill.try_serialize(&mut &mut data[..])?;
         }
     }
-    upper_tick.liquidity_net = upper_tick
-        .liquidity_net
-        .checked_sub(liquidity as i128)
-        .ok_or(Errors::MultiplicationOverflow)?;
+
+    // Checkpoint fee growth inside the range so the position only earns fees from now on
+    let (fee_growth_inside_a, fee_growth_inside_b) = get_fee_growth_inside(
+        lower_tick,
+        upper_tick,
+        pool.tick_current,
+        pool.fee_growth_global_a,
+        pool.fee_growth_global_b,
+    );
+    position.fee_growth_inside_a = fee_growth_inside_a;
+    position.fee_growth_inside_b = fee_growth_inside_b;
+    let reward_growths_inside = get_reward_growths_inside(
+        lower_tick,
+        upper_tick,
+        pool.tick_current,
+        reward_growths_global,
+    );
+    position.reward_infos = reward_growths_inside.map(|growth_inside| PositionRewardInfo {
+        growth_inside_checkpoint: growth_inside,
+        amount_owed: 0,
+    });
 
     // Update pool liquidity if position is active at current tick
     if tick_lower <= pool.tick_current && pool.tick_current < tick_upper {
@@ -148,37 +350,83 @@ pub fn provide_liquidity_handler(
     }
 
     // Compute required amounts for adding liquidity
-    let (amount_a, amount_b) = compute_amount(
+    let (amount_a, amount_b) = compute_deposit_amount(
         liquidity,
         tick_upper,
         tick_lower,
-        pool.tick_current,
+        pool.sqrt_price,
     )?;
 
     let amount_a_u64 = u64::try_from(amount_a).map_err(|_| Errors::TokenMaxExceeded)?;
     let amount_b_u64 = u64::try_from(amount_b).map_err(|_| Errors::TokenMaxExceeded)?;
 
+    // Gross up by the mint transfer fees so the vaults receive the full amounts backing the liquidity
+    let amount_a_u64 = get_transfer_fee_included_amount(&ctx.accounts.token_mint_a, amount_a_u64)?;
+    let amount_b_u64 = get_transfer_fee_included_amount(&ctx.accounts.token_mint_b, amount_b_u64)?;
+
+    // Wrap the lamports deposited on a native SOL side
+    let (owner_token_a, wrapped_a) = get_user_token_account(
+        &ctx.accounts.owner_token_a,
+        &ctx.accounts.owner,
+        &ctx.accounts.temp_wsol_account,
+        ctx.bumps.temp_wsol_account,
+        &ctx.accounts.token_mint_a,
+        &ctx.accounts.token_program_a,
+        &ctx.accounts.system_program,
+        amount_a_u64,
+    )?;
+    let (owner_token_b, wrapped_b) = get_user_token_account(
+        &ctx.accounts.owner_token_b,
+        &ctx.accounts.owner,
+        &ctx.accounts.temp_wsol_account,
+        ctx.bumps.temp_wsol_account,
+        &ctx.accounts.token_mint_b,
+        &ctx.accounts.token_program_b,
+        &ctx.accounts.system_program,
+        amount_b_u64,
+    )?;
+
     // Transfer token A
-    let cpi_accounts = Transfer {
-        from: ctx.accounts.owner_token_a.to_account_info(),
-        to: ctx.accounts.token_a_vault.to_account_info(),
-        authority: owner.to_account_info(),
-    };
-    token::transfer(
-        CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts),
+    transfer_from_user(
+        owner,
+        owner_token_a.clone(),
+        &ctx.accounts.token_a_vault,
+        &ctx.accounts.token_mint_a,
+        &ctx.accounts.token_program_a,
+        ctx.remaining_accounts,
         amount_a_u64,
     )?;
 
     // Transfer token B
-    let cpi_accounts = Transfer {
-        from: ctx.accounts.owner_token_b.to_account_info(),
-        to: ctx.accounts.token_b_vault.to_account_info(),
-        authority: owner.to_account_info(),
-    };
-    token::transfer(
-        CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts),
+    transfer_from_user(
+        owner,
+        owner_token_b.clone(),
+        &ctx.accounts.token_b_vault,
+        &ctx.accounts.token_mint_b,
+        &ctx.accounts.token_program_b,
+        ctx.remaining_accounts,
         amount_b_u64,
     )?;
 
+    // Close the temporary wSOL account so the owner is left with lamports
+    if wrapped_a {
+        unwrap_native_sol(&ctx.accounts.owner, &owner_token_a, &ctx.accounts.token_program_a)?;
+    }
+    if wrapped_b {
+        unwrap_native_sol(&ctx.accounts.owner, &owner_token_b, &ctx.accounts.token_program_b)?;
+    }
+
+    emit_cpi!(LiquidityIncreased {
+        pool: ctx.accounts.pool.key(),
+        position: ctx.accounts.position.key(),
+        owner: ctx.accounts.owner.key(),
+        tick_lower,
+        tick_upper,
+        liquidity,
+        amount_a: amount_a_u64,
+        amount_b: amount_b_u64,
+        range_order,
+    });
+
     Ok(())
 }
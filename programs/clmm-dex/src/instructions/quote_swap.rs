@@ -0,0 +1,102 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+
+use crate::state::Pool;
+use crate::errors::Errors;
+use crate::math::dynamic_fee_math::*;
+use crate::instructions::swap::swap_steps;
+use crate::utils::{get_transfer_fee_excluded_amount, get_transfer_fee_included_amount};
+
+#[derive(Accounts)]
+pub struct QuoteSwap<'info> {
+    #[account(
+        seeds = [
+            b"pool",
+            pool.token_a_mint.as_ref(),
+            pool.token_b_mint.as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool: Account<'info, Pool>,
+    #[account(address = pool.token_a_mint)]
+    pub token_mint_a: Box<InterfaceAccount<'info, Mint>>,
+    #[account(address = pool.token_b_mint)]
+    pub token_mint_b: Box<InterfaceAccount<'info, Mint>>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct SwapQuote {
+    pub amount_in: u64, // Sent by the user, including the input mint transfer fee
+    pub amount_out: u64, // Received by the user, after the output mint transfer fee
+    pub fee_amount: u64, // Swap fee paid to the pool
+    pub sqrt_price: u128, // Pool price after the swap
+    pub tick_current: i32,
+    pub ticks_crossed: u32,
+}
+
+// Runs the swap loop on a copy of the pool and returns the result through return data, nothing is written.
+// Tick accounts are passed like for swap, and the quote fails with the same error as the swap would.
+// A quote stops early at sqrt_price_limit (0 for none), in which case amount_in or amount_out is less than requested.
+pub fn quote_swap_handler(
+    ctx: Context<QuoteSwap>,
+    amount: u64,
+    a_to_b: bool,
+    exact_in: bool,
+    sqrt_price_limit: u128,
+) -> Result<SwapQuote> {
+    let timestamp = Clock::get()?.unix_timestamp;
+    let (mint_in, mint_out) = if a_to_b {
+        (&ctx.accounts.token_mint_a, &ctx.accounts.token_mint_b)
+    } else {
+        (&ctx.accounts.token_mint_b, &ctx.accounts.token_mint_a)
+    };
+
+    // The fee of this swap depends on the volatility it would record first
+    let mut pool: Pool = (*ctx.accounts.pool).clone();
+    update_volatility_accumulator(&mut pool, timestamp)?;
+
+    if sqrt_price_limit != 0 {
+        require!(
+            if a_to_b { sqrt_price_limit < pool.sqrt_price } else { sqrt_price_limit > pool.sqrt_price },
+            Errors::InvalidSqrtPriceLimit
+        );
+    }
+
+    // Pool side amounts: what reaches the vault for exact input, what has to leave it for exact output
+    let amount_specified = if exact_in {
+        get_transfer_fee_excluded_amount(mint_in, amount)?
+    } else {
+        get_transfer_fee_included_amount(mint_out, amount)?
+    };
+
+    // The same loop as swap, so the quote fails wherever the swap would
+    let steps = swap_steps(
+        &mut pool,
+        &ctx.accounts.pool.key(),
+        ctx.remaining_accounts,
+        amount_specified,
+        a_to_b,
+        exact_in,
+        sqrt_price_limit,
+        timestamp,
+        false,
+    )?;
+
+    let amount_in = u64::try_from(steps.amount_in).map_err(|_| Errors::TokenMaxExceeded)?;
+    let amount_out = u64::try_from(steps.amount_out).map_err(|_| Errors::TokenMaxExceeded)?;
+    let stopped_at_limit = sqrt_price_limit != 0 && pool.sqrt_price == sqrt_price_limit;
+
+    Ok(SwapQuote {
+        // An exact input swap the price limit did not stop takes the whole amount, rounding dust included
+        amount_in: if exact_in && (steps.amount_remaining == 0 || !stopped_at_limit) {
+            amount
+        } else {
+            get_transfer_fee_included_amount(mint_in, amount_in)?
+        },
+        amount_out: get_transfer_fee_excluded_amount(mint_out, amount_out)?,
+        fee_amount: u64::try_from(steps.fee_amount).map_err(|_| Errors::TokenMaxExceeded)?,
+        sqrt_price: pool.sqrt_price,
+        tick_current: pool.tick_current,
+        ticks_crossed: steps.ticks_crossed,
+    })
+}
@@ -0,0 +1,334 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::Errors;
+use crate::state::{
+    Config, Observation, Pool, Position, PositionBundle, Tick, PAUSE_DEPOSITS, PAUSE_SWAPS, PAUSE_WITHDRAWALS,
+};
+use crate::math::compute_amount::*;
+use crate::math::fee_math::*;
+use crate::math::tick_math::*;
+use crate::math::oracle_math::write_observation;
+use crate::math::reward_math::*;
+use crate::math::dynamic_fee_math::update_volatility_accumulator;
+use crate::instructions::swap::swap_internal;
+use crate::events::{PositionRebalanced, Swapped};
+
+// Old ticks are declared before new ticks, so when a range shares a tick the new tick's state is written last
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(new_tick_lower: i32, new_tick_upper: i32)]
+pub struct RebalancePosition<'info> {
+    #[account(mut,
+        seeds = [
+            b"pool",
+            pool.token_a_mint.as_ref(),
+            pool.token_b_mint.as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut,
+        seeds = [b"observation", pool.key().as_ref()],
+        bump = observation.load()?.bump,
+    )]
+    pub observation: AccountLoader<'info, Observation>,
+
+    #[account(
+        seeds = [
+            b"position_bundle",
+            pool.key().as_ref(),
+            position_bundle.owner.as_ref(),
+        ],
+        bump = position_bundle.bump,
+    )]
+    pub position_bundle: Account<'info, PositionBundle>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"position",
+            position_bundle.key().as_ref(),
+            position.bundle_index.to_le_bytes().as_ref(),
+        ],
+        bump = position.bump,
+        constraint = position.is_authorized(&authority.key()) @ Errors::Unauthorized,
+        constraint = !position.range_order @ Errors::InvalidRangeOrder,
+    )]
+    pub position: Account<'info, Position>,
+
+    #[account(mut,
+        seeds = [b"tick", pool.key().as_ref(), &position.tick_lower.to_le_bytes()],
+        bump = lower_tick.bump,
+    )]
+    pub lower_tick: Account<'info, Tick>,
+
+    #[account(mut,
+        seeds = [b"tick", pool.key().as_ref(), &position.tick_upper.to_le_bytes()],
+        bump = upper_tick.bump,
+    )]
+    pub upper_tick: Account<'info, Tick>,
+
+    // New ticks are created if no position uses them yet, paid by the authority
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + Tick::INIT_SPACE,
+        seeds = [b"tick", pool.key().as_ref(), &new_tick_lower.to_le_bytes()],
+        bump,
+    )]
+    pub new_lower_tick: Account<'info, Tick>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + Tick::INIT_SPACE,
+        seeds = [b"tick", pool.key().as_ref(), &new_tick_upper.to_le_bytes()],
+        bump,
+    )]
+    pub new_upper_tick: Account<'info, Tick>,
+
+    // Position owner or delegate
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+// Moves the position's liquidity to a new range without tokens leaving the vaults.
+// Tick accounts crossed by the optional surplus swap are passed in remaining_accounts, only the owner can swap.
+pub fn rebalance_position_handler(
+    ctx: Context<RebalancePosition>,
+    new_tick_lower: i32,
+    new_tick_upper: i32,
+    min_liquidity: u128,
+    swap_amount_in: u64,
+    a_to_b: bool,
+) -> Result<()> {
+    // The liquidity is withdrawn from the old range and deposited into the new one
+    ctx.accounts.config.check_not_paused(&ctx.accounts.pool, PAUSE_WITHDRAWALS)?;
+    ctx.accounts.config.check_not_paused(&ctx.accounts.pool, PAUSE_DEPOSITS)?;
+    if swap_amount_in > 0 {
+        ctx.accounts.config.check_not_paused(&ctx.accounts.pool, PAUSE_SWAPS)?;
+        // The swap has no price limit, a delegate could sandwich it at the owner's expense
+        require!(ctx.accounts.authority.key() == ctx.accounts.position.owner, Errors::Unauthorized);
+    }
+
+    require!(new_tick_lower < new_tick_upper, Errors::InvalidTickRange);
+    let tick_spacing = ctx.accounts.pool.tick_spacing as i32;
+    require!(
+        new_tick_lower % tick_spacing == 0 && new_tick_upper % tick_spacing == 0,
+        Errors::InvalidTickSpacing
+    );
+
+    let pool = &mut ctx.accounts.pool;
+    let position = &mut ctx.accounts.position;
+
+    // Credit fees accrued in the old range, they stay owed to the position
+    let (fee_growth_inside_a, fee_growth_inside_b) = get_fee_growth_inside(
+        &ctx.accounts.lower_tick,
+        &ctx.accounts.upper_tick,
+        pool.tick_current,
+        pool.fee_growth_global_a,
+        pool.fee_growth_global_b,
+    );
+    update_position_fees(position, fee_growth_inside_a, fee_growth_inside_b)?;
+
+    // Credit rewards earned in the old range
+    let timestamp = Clock::get()?.unix_timestamp;
+    update_pool_rewards(pool, timestamp)?;
+    let reward_growths_inside = get_reward_growths_inside(
+        &ctx.accounts.lower_tick,
+        &ctx.accounts.upper_tick,
+        pool.tick_current,
+        get_reward_growths_global(pool),
+    );
+    update_position_rewards(position, reward_growths_inside)?;
+
+    // Record the tick and liquidity in effect until now, before the withdrawal and the swap change them
+    write_observation(
+        &ctx.accounts.observation,
+        timestamp,
+        pool.tick_current,
+        pool.liquidity,
+    )?;
+
+    // Withdraw the old range
+    let old_tick_lower = position.tick_lower;
+    let old_tick_upper = position.tick_upper;
+    let old_liquidity = position.liquidity;
+    let (amount_a, amount_b) = compute_withdraw_amount(
+        old_liquidity,
+        position.tick_upper,
+        position.tick_lower,
+        pool.sqrt_price,
+    )?;
+    update_tick_liquidity(
+        &mut ctx.accounts.lower_tick,
+        &mut ctx.accounts.upper_tick,
+        -(old_liquidity as i128),
+    )?;
+    if position.tick_lower <= pool.tick_current && pool.tick_current < position.tick_upper {
+        pool.liquidity = pool
+            .liquidity
+            .checked_sub(old_liquidity)
+            .ok_or(Errors::MathError)?;
+    }
+    position.liquidity = 0;
+
+    // Persist the old ticks before the swap reads tick accounts from account data
+    ctx.accounts.lower_tick.exit(&crate::ID)?;
+    ctx.accounts.upper_tick.exit(&crate::ID)?;
+
+    // Swap the surplus token through the pool, both sides stay in the vaults
+    let (mut amount_a, mut amount_b) = (amount_a, amount_b);
+    if swap_amount_in > 0 {
+        let available = if a_to_b { amount_a } else { amount_b };
+        require!(swap_amount_in as u128 <= available, Errors::InvalidAmount);
+
+        update_volatility_accumulator(pool, timestamp)?;
+
+        let sqrt_price_before = pool.sqrt_price;
+        let tick_before = pool.tick_current;
+        let liquidity_before = pool.liquidity;
+
+        let (swap_in, swap_out, fee_amount) = swap_internal(pool, ctx.remaining_accounts, swap_amount_in, a_to_b, timestamp)?;
+        if a_to_b {
+            amount_a = amount_a.checked_sub(swap_in).ok_or(Errors::MathError)?;
+            amount_b = amount_b.checked_add(swap_out).ok_or(Errors::MathError)?;
+        } else {
+            amount_b = amount_b.checked_sub(swap_in).ok_or(Errors::MathError)?;
+            amount_a = amount_a.checked_add(swap_out).ok_or(Errors::MathError)?;
+        }
+
+        emit_cpi!(Swapped {
+            pool: pool.key(),
+            user: ctx.accounts.authority.key(),
+            a_to_b,
+            amount_in: u64::try_from(swap_in).map_err(|_| Errors::TokenMaxExceeded)?,
+            amount_out: u64::try_from(swap_out).map_err(|_| Errors::TokenMaxExceeded)?,
+            fee_amount: u64::try_from(fee_amount).map_err(|_| Errors::TokenMaxExceeded)?,
+            sqrt_price_before,
+            sqrt_price_after: pool.sqrt_price,
+            tick_before,
+            tick_after: pool.tick_current,
+            liquidity_before,
+            liquidity_after: pool.liquidity,
+        });
+    }
+
+    // The swap may have crossed any of the range ticks. A new tick created by this instruction has no data yet,
+    // ticks are never uninitialized so an existing one is always initialized
+    ctx.accounts.lower_tick.reload()?;
+    ctx.accounts.upper_tick.reload()?;
+    if ctx.accounts.new_lower_tick.initialized {
+        ctx.accounts.new_lower_tick.reload()?;
+    }
+    if ctx.accounts.new_upper_tick.initialized {
+        ctx.accounts.new_upper_tick.reload()?;
+    }
+
+    let new_lower_tick = &mut ctx.accounts.new_lower_tick;
+    let new_upper_tick = &mut ctx.accounts.new_upper_tick;
+    let reward_growths_global = get_reward_growths_global(pool);
+
+    // Initialize new ticks if not already
+    if !new_lower_tick.initialized {
+        initialize_tick(
+            new_lower_tick,
+            new_tick_lower,
+            ctx.bumps.new_lower_tick,
+            pool.tick_current,
+            pool.fee_growth_global_a,
+            pool.fee_growth_global_b,
+            reward_growths_global,
+        );
+        pool.insert_initialized_tick(new_tick_lower)?;
+    }
+    if !new_upper_tick.initialized {
+        initialize_tick(
+            new_upper_tick,
+            new_tick_upper,
+            ctx.bumps.new_upper_tick,
+            pool.tick_current,
+            pool.fee_growth_global_a,
+            pool.fee_growth_global_b,
+            reward_growths_global,
+        );
+        pool.insert_initialized_tick(new_tick_upper)?;
+    }
+
+    // Deposit into the new range with the slippage bound
+    let new_liquidity = get_liquidity_for_amounts(
+        amount_a,
+        amount_b,
+        new_tick_upper,
+        new_tick_lower,
+        pool.sqrt_price,
+    )?;
+    require!(new_liquidity > 0 && new_liquidity >= min_liquidity, Errors::SlippageExceeded);
+
+    let (used_a, used_b) = compute_deposit_amount(
+        new_liquidity,
+        new_tick_upper,
+        new_tick_lower,
+        pool.sqrt_price,
+    )?;
+    update_tick_liquidity(new_lower_tick, new_upper_tick, new_liquidity as i128)?;
+    if new_tick_lower <= pool.tick_current && pool.tick_current < new_tick_upper {
+        pool.liquidity = pool
+            .liquidity
+            .checked_add(new_liquidity)
+            .ok_or(Errors::MultiplicationOverflow)?;
+    }
+
+    // Unused tokens are credited as owed, the owner withdraws them with collect_fees
+    let remainder_a = amount_a.checked_sub(used_a).ok_or(Errors::MathError)?;
+    let remainder_b = amount_b.checked_sub(used_b).ok_or(Errors::MathError)?;
+    position.token_a_earned = position
+        .token_a_earned
+        .checked_add(u64::try_from(remainder_a).map_err(|_| Errors::TokenMaxExceeded)?)
+        .ok_or(Errors::TokenMaxExceeded)?;
+    position.token_b_earned = position
+        .token_b_earned
+        .checked_add(u64::try_from(remainder_b).map_err(|_| Errors::TokenMaxExceeded)?)
+        .ok_or(Errors::TokenMaxExceeded)?;
+
+    // Move the position and checkpoint fee growth in the new range
+    let (fee_growth_inside_a, fee_growth_inside_b) = get_fee_growth_inside(
+        new_lower_tick,
+        new_upper_tick,
+        pool.tick_current,
+        pool.fee_growth_global_a,
+        pool.fee_growth_global_b,
+    );
+    position.tick_lower = new_tick_lower;
+    position.tick_upper = new_tick_upper;
+    position.liquidity = new_liquidity;
+    position.fee_growth_inside_a = fee_growth_inside_a;
+    position.fee_growth_inside_b = fee_growth_inside_b;
+    let reward_growths_inside = get_reward_growths_inside(
+        new_lower_tick,
+        new_upper_tick,
+        pool.tick_current,
+        reward_growths_global,
+    );
+    for (reward_info, growth_inside) in position.reward_infos.iter_mut().zip(reward_growths_inside) {
+        reward_info.growth_inside_checkpoint = growth_inside;
+    }
+
+    emit_cpi!(PositionRebalanced {
+        pool: pool.key(),
+        position: position.key(),
+        old_tick_lower,
+        old_tick_upper,
+        old_liquidity,
+        new_tick_lower,
+        new_tick_upper,
+        new_liquidity,
+    });
+
+    Ok(())
+}
@@ -1,10 +1,17 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 
 use crate::errors::{ Errors };
-use crate::state::{Pool, Position};
+use crate::state::{Config, Observation, Pool, Position, PositionBundle, Tick, PAUSE_WITHDRAWALS};
 use crate::math::compute_amount::*;
+use crate::math::fee_math::*;
+use crate::math::oracle_math::write_observation;
+use crate::math::reward_math::*;
+use crate::math::tick_math::update_tick_liquidity;
+use crate::utils::{get_transfer_hook_program_id, get_user_token_account, transfer_from_vault, unwrap_native_sol};
+use crate::events::LiquidityDecreased;
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct RemoveLiquidity<'info> {
     #[account(
@@ -17,95 +24,228 @@ pub struct RemoveLiquidity<'info> {
         bump = pool.bump,
     )]
     pub pool: Account<'info, Pool>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(mut,
+        seeds = [b"observation", pool.key().as_ref()],
+        bump = observation.load()?.bump,
+    )]
+    pub observation: AccountLoader<'info, Observation>,
     #[account(
         mut,
         seeds = [
-            b"position",
+            b"position_bundle",
             pool.key().as_ref(),
             owner.key().as_ref(),
-            position.tick_lower.to_le_bytes().as_ref(),
-            position.tick_upper.to_le_bytes().as_ref(),
+        ],
+        bump = position_bundle.bump,
+    )]
+    pub position_bundle: Account<'info, PositionBundle>,
+    #[account(
+        mut,
+        seeds = [
+            b"position",
+            position_bundle.key().as_ref(),
+            position.bundle_index.to_le_bytes().as_ref(),
         ],
         close = owner,
         bump = position.bump,
+        constraint = !position.range_order @ Errors::InvalidRangeOrder,
     )]
     pub position: Account<'info, Position>,
 
+    #[account(mut,
+        seeds = [b"tick", pool.key().as_ref(), &position.tick_lower.to_le_bytes()],
+        bump = lower_tick.bump,
+    )]
+    pub lower_tick: Account<'info, Tick>,
+
+    #[account(mut,
+        seeds = [b"tick", pool.key().as_ref(), &position.tick_upper.to_le_bytes()],
+        bump = upper_tick.bump,
+    )]
+    pub upper_tick: Account<'info, Tick>,
+
     #[account(
         mut,
+        address = pool.token_a_vault,
         token::mint = pool.token_a_mint,
         token::authority = pool,
+        token::token_program = token_program_a,
     )]
-    pub token_a_vault: Account<'info, TokenAccount>,
+    pub token_a_vault: Box<InterfaceAccount<'info, TokenAccount>>,
     #[account(
         mut,
+        address = pool.token_b_vault,
         token::mint = pool.token_b_mint,
         token::authority = pool,
+        token::token_program = token_program_b,
     )]
-    pub token_b_vault: Account<'info, TokenAccount>,
+    pub token_b_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    // Either owner token account may be omitted on a native SOL side, lamports are then wrapped in temp_wsol_account
     #[account(
         mut,
         token::mint = pool.token_a_mint,
         token::authority = owner,
+        token::token_program = token_program_a,
     )]
-    pub owner_token_a: Account<'info, TokenAccount>,
+    pub owner_token_a: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
     #[account(
         mut,
         token::mint = pool.token_b_mint,
         token::authority = owner,
+        token::token_program = token_program_b,
     )]
-    pub owner_token_b: Account<'info, TokenAccount>,
+    pub owner_token_b: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
 
     #[account(mut)]
     pub owner: Signer<'info>,
-    pub token_program: Program<'info, Token>,
+    #[account(
+        address = pool.token_a_mint,
+        constraint = config.is_transfer_hook_allowed(get_transfer_hook_program_id(&token_mint_a)) @ Errors::TransferHookNotAllowed,
+    )]
+    pub token_mint_a: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        address = pool.token_b_mint,
+        constraint = config.is_transfer_hook_allowed(get_transfer_hook_program_id(&token_mint_b)) @ Errors::TransferHookNotAllowed,
+    )]
+    pub token_mint_b: Box<InterfaceAccount<'info, Mint>>,
+    /// CHECK: created and closed by the handler, address checked by seeds
+    #[account(mut, seeds = [b"wsol", owner.key().as_ref()], bump)]
+    pub temp_wsol_account: Option<UncheckedAccount<'info>>,
+    pub token_program_a: Interface<'info, TokenInterface>,
+    pub token_program_b: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
     pub rent: Sysvar<'info, Rent>,
 }
 
-pub fn remove_liquidity_handler( ctx: Context<RemoveLiquidity>,
+pub fn remove_liquidity_handler<'info>( ctx: Context<'_, '_, '_, 'info, RemoveLiquidity<'info>>,
 ) -> Result<()> {
+    ctx.accounts.config.check_not_paused(&ctx.accounts.pool, PAUSE_WITHDRAWALS)?;
+
     let pool = &mut ctx.accounts.pool;
     let position = &mut ctx.accounts.position;
+    let lower_tick = &mut ctx.accounts.lower_tick;
+    let upper_tick = &mut ctx.accounts.upper_tick;
+
+    // Credit fees accrued since the last checkpoint, they are paid out with the principal
+    let (fee_growth_inside_a, fee_growth_inside_b) = get_fee_growth_inside(
+        lower_tick,
+        upper_tick,
+        pool.tick_current,
+        pool.fee_growth_global_a,
+        pool.fee_growth_global_b,
+    );
+    update_position_fees(position, fee_growth_inside_a, fee_growth_inside_b)?;
+
+    // Record the tick and liquidity in effect until now, before the pool liquidity changes
+    let timestamp = Clock::get()?.unix_timestamp;
+    write_observation(&ctx.accounts.observation, timestamp, pool.tick_current, pool.liquidity)?;
+
+    // Accrue rewards before the pool liquidity changes, rewards owed have to be collected before closing
+    update_pool_rewards(pool, timestamp)?;
+    let reward_growths_inside = get_reward_growths_inside(
+        lower_tick,
+        upper_tick,
+        pool.tick_current,
+        get_reward_growths_global(pool),
+    );
+    update_position_rewards(position, reward_growths_inside)?;
+    check_rewards_collected(pool, position, ctx.remaining_accounts)?;
+
+    // Release the slot so it can be reused by a new position
+    ctx.accounts.position_bundle.free_slot(position.bundle_index)?;
 
     // Compute amount A and B to remove
-    let (amount_a, amount_b) = compute_amount(
+    let (amount_a, amount_b) = compute_withdraw_amount(
         position.liquidity,
-        position.tick_lower,
         position.tick_upper,
-        pool.tick_current,
+        position.tick_lower,
+        pool.sqrt_price,
     )?;
 
-    let seeds = &[
-    b"pool",
-    pool.token_a_mint.as_ref(),
-    pool.token_b_mint.as_ref(),
-    &[pool.bump]];
+    // Take the liquidity out of the range ticks and, when in range, out of the pool
+    update_tick_liquidity(lower_tick, upper_tick, -(position.liquidity as i128))?;
+    if position.tick_lower <= pool.tick_current && pool.tick_current < position.tick_upper {
+        pool.liquidity = pool
+            .liquidity
+            .checked_sub(position.liquidity)
+            .ok_or(Errors::MathError)?;
+    }
 
-    let signer = &[&seeds[..]];
+    // Principal plus fees still owed to the position
+    let amount_a_u64 = u64::try_from(amount_a)
+        .ok()
+        .and_then(|amount| amount.checked_add(position.token_a_earned))
+        .ok_or(Errors::TokenMaxExceeded)?;
+    let amount_b_u64 = u64::try_from(amount_b)
+        .ok()
+        .and_then(|amount| amount.checked_add(position.token_b_earned))
+        .ok_or(Errors::TokenMaxExceeded)?;
+    position.token_a_earned = 0;
+    position.token_b_earned = 0;
 
-let amount_a_u64 = u64::try_from(amount_a).map_err(|_| Errors::TokenMaxExceeded)?;
-    let amount_b_u64 = u64::try_from(amount_b).map_err(|_| Errors::TokenMaxExceeded)?;
+    // Open an empty account to receive into on a native SOL side
+    let (owner_token_a, wrapped_a) = get_user_token_account(
+        &ctx.accounts.owner_token_a,
+        &ctx.accounts.owner,
+        &ctx.accounts.temp_wsol_account,
+        ctx.bumps.temp_wsol_account,
+        &ctx.accounts.token_mint_a,
+        &ctx.accounts.token_program_a,
+        &ctx.accounts.system_program,
+        0,
+    )?;
+    let (owner_token_b, wrapped_b) = get_user_token_account(
+        &ctx.accounts.owner_token_b,
+        &ctx.accounts.owner,
+        &ctx.accounts.temp_wsol_account,
+        ctx.bumps.temp_wsol_account,
+        &ctx.accounts.token_mint_b,
+        &ctx.accounts.token_program_b,
+        &ctx.accounts.system_program,
+        0,
+    )?;
 
     // Transfer tokens to user
-    let cpi_accounts = Transfer {
-        from: ctx.accounts.token_a_vault.to_account_info(),
-        to: ctx.accounts.owner_token_a.to_account_info(),
-        authority: pool.to_account_info(),
-    };
-    let cpi_program = ctx.accounts.token_program.to_account_info();
-    let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
-    token::transfer(cpi_ctx, amount_a_u64)?;
-
-    let cpi_accounts = Transfer {
-        from: ctx.accounts.token_b_vault.to_account_info(),
-        to: ctx.accounts.owner_token_b.to_account_info(),
-        authority: pool.to_account_info(),
-    };  
-    let cpi_program = ctx.accounts.token_program.to_account_info();
-    let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
-    token::transfer(cpi_ctx, amount_b_u64)?;
-
-    pool.liquidity = pool.liquidity.checked_sub(position.liquidity).ok_or(Errors::MathError)?;
+    transfer_from_vault(
+        pool,
+        &ctx.accounts.token_a_vault,
+        owner_token_a.clone(),
+        &ctx.accounts.token_mint_a,
+        &ctx.accounts.token_program_a,
+        ctx.remaining_accounts,
+        amount_a_u64,
+    )?;
+    transfer_from_vault(
+        pool,
+        &ctx.accounts.token_b_vault,
+        owner_token_b.clone(),
+        &ctx.accounts.token_mint_b,
+        &ctx.accounts.token_program_b,
+        ctx.remaining_accounts,
+        amount_b_u64,
+    )?;
+
+    // Close the temporary wSOL account so the owner is left with lamports
+    if wrapped_a {
+        unwrap_native_sol(&ctx.accounts.owner, &owner_token_a, &ctx.accounts.token_program_a)?;
+    }
+    if wrapped_b {
+        unwrap_native_sol(&ctx.accounts.owner, &owner_token_b, &ctx.accounts.token_program_b)?;
+    }
+
+    emit_cpi!(LiquidityDecreased {
+        pool: pool.key(),
+        position: position.key(),
+        owner: position.owner,
+        tick_lower: position.tick_lower,
+        tick_upper: position.tick_upper,
+        liquidity: position.liquidity,
+        amount_a: amount_a_u64,
+        amount_b: amount_b_u64,
+    });
+
     Ok(())
 }
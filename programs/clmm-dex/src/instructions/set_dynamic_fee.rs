@@ -0,0 +1,75 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::Errors;
+use crate::state::{Config, DynamicFee, Pool};
+use crate::events::DynamicFeeProposed;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetDynamicFee<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut,
+        seeds = [
+            b"pool",
+            pool.token_a_mint.as_ref(),
+            pool.token_b_mint.as_ref(),
+        ],
+        bump = pool.bump,
+        has_one = pool_authority,
+    )]
+    pub pool: Account<'info, Pool>,
+
+    pub pool_authority: Signer<'info>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct DynamicFeeParams {
+    pub enabled: bool,
+    pub filter_period: u32,
+    pub decay_period: u32,
+    pub reduction_factor: u16,
+    pub variable_fee_control: u32,
+    pub max_volatility_accumulator: u32,
+    pub max_fee: u32,
+}
+
+// Proposes turning the dynamic fee on or off with new parameters, timelocked like a base fee change.
+// apply_fee_change commits them once the configured delay has passed, replacing any earlier proposal.
+pub fn set_dynamic_fee_handler(ctx: Context<SetDynamicFee>, params: DynamicFeeParams) -> Result<()> {
+    let pool = &mut ctx.accounts.pool;
+
+    let pending_dynamic_fee = DynamicFee {
+        enabled: params.enabled,
+        filter_period: params.filter_period,
+        decay_period: params.decay_period,
+        reduction_factor: params.reduction_factor,
+        variable_fee_control: params.variable_fee_control,
+        max_volatility_accumulator: params.max_volatility_accumulator,
+        max_fee: params.max_fee,
+        ..DynamicFee::default()
+    };
+    // The pending changes may apply in either order, so the parameters have to fit the base fee now and the one pending
+    pending_dynamic_fee.validate(pool.fee)?;
+    if pool.pending_fee_activation != 0 {
+        pending_dynamic_fee.validate(pool.pending_fee)?;
+    }
+
+    let activation_timestamp = Clock::get()?
+        .unix_timestamp
+        .checked_add(ctx.accounts.config.fee_change_delay)
+        .ok_or(Errors::MathError)?;
+
+    pool.pending_dynamic_fee = pending_dynamic_fee;
+    pool.pending_dynamic_fee_activation = activation_timestamp;
+
+    emit_cpi!(DynamicFeeProposed {
+        pool: pool.key(),
+        enabled: params.enabled,
+        max_fee: params.max_fee,
+        activation_timestamp,
+    });
+
+    Ok(())
+}
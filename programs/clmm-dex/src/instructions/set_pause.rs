@@ -0,0 +1,63 @@
+use anchor_lang::prelude::*;
+use crate::state::{Config, Pool};
+use crate::events::{GlobalPauseSet, PoolPauseFlagsSet};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetGlobalPause<'info> {
+    #[account(mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = pause_authority,
+    )]
+    pub config: Account<'info, Config>,
+
+    pub pause_authority: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetPoolPauseFlags<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = pause_authority,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(mut,
+        seeds = [
+            b"pool",
+            pool.token_a_mint.as_ref(),
+            pool.token_b_mint.as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool: Account<'info, Pool>,
+
+    pub pause_authority: Signer<'info>,
+}
+
+// Flips the kill switch, withdrawals stay open while it is on
+pub fn set_global_pause_handler(ctx: Context<SetGlobalPause>, paused: bool) -> Result<()> {
+    ctx.accounts.config.global_paused = paused;
+
+    emit_cpi!(GlobalPauseSet {
+        config: ctx.accounts.config.key(),
+        paused,
+    });
+
+    Ok(())
+}
+
+// Sets the frozen operations of a pool as a bitmask of the PAUSE_* flags
+pub fn set_pool_pause_flags_handler(ctx: Context<SetPoolPauseFlags>, pause_flags: u8) -> Result<()> {
+    ctx.accounts.pool.pause_flags = pause_flags;
+
+    emit_cpi!(PoolPauseFlagsSet {
+        pool: ctx.accounts.pool.key(),
+        pause_flags,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,27 @@
+use anchor_lang::prelude::*;
+use crate::state::Config;
+use crate::events::PauseAuthoritySet;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetPauseAuthority<'info> {
+    #[account(mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin,
+    )]
+    pub config: Account<'info, Config>,
+
+    pub admin: Signer<'info>,
+}
+
+pub fn set_pause_authority_handler(ctx: Context<SetPauseAuthority>, pause_authority: Pubkey) -> Result<()> {
+    ctx.accounts.config.pause_authority = pause_authority;
+
+    emit_cpi!(PauseAuthoritySet {
+        config: ctx.accounts.config.key(),
+        pause_authority,
+    });
+
+    Ok(())
+}
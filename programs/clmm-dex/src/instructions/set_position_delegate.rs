@@ -0,0 +1,56 @@
+use anchor_lang::prelude::*;
+use crate::state::{Pool, Position, PositionBundle};
+use crate::events::PositionDelegateSet;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetPositionDelegate<'info> {
+    #[account(
+        seeds = [
+            b"pool",
+            pool.token_a_mint.as_ref(),
+            pool.token_b_mint.as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        seeds = [
+            b"position_bundle",
+            pool.key().as_ref(),
+            owner.key().as_ref(),
+        ],
+        bump = position_bundle.bump,
+    )]
+    pub position_bundle: Account<'info, PositionBundle>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"position",
+            position_bundle.key().as_ref(),
+            position.bundle_index.to_le_bytes().as_ref(),
+        ],
+        bump = position.bump,
+        has_one = owner,
+    )]
+    pub position: Account<'info, Position>,
+
+    pub owner: Signer<'info>,
+}
+
+// Sets or clears the operator allowed to collect fees and rebalance the position
+pub fn set_position_delegate_handler(
+    ctx: Context<SetPositionDelegate>,
+    delegate: Option<Pubkey>,
+) -> Result<()> {
+    ctx.accounts.position.delegate = delegate;
+
+    emit_cpi!(PositionDelegateSet {
+        position: ctx.accounts.position.key(),
+        delegate,
+    });
+
+    Ok(())
+}
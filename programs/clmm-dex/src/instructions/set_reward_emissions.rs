@@ -0,0 +1,47 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::Errors;
+use crate::state::{Pool, NUM_REWARDS};
+use crate::math::reward_math::update_pool_rewards;
+use crate::events::RewardEmissionsSet;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetRewardEmissions<'info> {
+    #[account(mut,
+        seeds = [
+            b"pool",
+            pool.token_a_mint.as_ref(),
+            pool.token_b_mint.as_ref(),
+        ],
+        bump = pool.bump,
+        has_one = pool_authority,
+    )]
+    pub pool: Account<'info, Pool>,
+
+    pub pool_authority: Signer<'info>,
+}
+
+// Sets the emission rate in Q64.64 tokens per second, the reward vault is funded with plain token transfers
+pub fn set_reward_emissions_handler(
+    ctx: Context<SetRewardEmissions>,
+    reward_index: u8,
+    emissions_per_second_x64: u128,
+) -> Result<()> {
+    let index = reward_index as usize;
+    require!(index < NUM_REWARDS, Errors::InvalidRewardIndex);
+    require!(ctx.accounts.pool.reward_infos[index].initialized(), Errors::InvalidRewardIndex);
+
+    // Emissions so far accrue at the old rate
+    let pool = &mut ctx.accounts.pool;
+    update_pool_rewards(pool, Clock::get()?.unix_timestamp)?;
+    pool.reward_infos[index].emissions_per_second_x64 = emissions_per_second_x64;
+
+    emit_cpi!(RewardEmissionsSet {
+        pool: pool.key(),
+        reward_index,
+        emissions_per_second_x64,
+    });
+
+    Ok(())
+}
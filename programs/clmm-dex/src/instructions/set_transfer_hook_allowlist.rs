@@ -0,0 +1,36 @@
+use anchor_lang::prelude::*;
+use crate::state::{Config, MAX_TRANSFER_HOOK_PROGRAMS};
+use crate::errors::Errors;
+use crate::events::TransferHookAllowlistSet;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetTransferHookAllowlist<'info> {
+    #[account(mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin,
+    )]
+    pub config: Account<'info, Config>,
+
+    pub admin: Signer<'info>,
+}
+
+// Replaces the list of transfer hook programs that pool mints may use
+pub fn set_transfer_hook_allowlist_handler(
+    ctx: Context<SetTransferHookAllowlist>,
+    transfer_hook_programs: Vec<Pubkey>,
+) -> Result<()> {
+    require!(
+        transfer_hook_programs.len() <= MAX_TRANSFER_HOOK_PROGRAMS,
+        Errors::InvalidAmount
+    );
+    ctx.accounts.config.transfer_hook_programs = transfer_hook_programs.clone();
+
+    emit_cpi!(TransferHookAllowlistSet {
+        config: ctx.accounts.config.key(),
+        transfer_hook_programs,
+    });
+
+    Ok(())
+}
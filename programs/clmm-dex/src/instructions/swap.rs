@@ -1,120 +1,396 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Transfer, Token, TokenAccount};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 use crate::state::*;
 use crate::errors::*;
 use crate::math::tick_math::*;
 use crate::math::compute_swap_step::*;
+use crate::math::fee_math::get_fee_growth_delta;
+use crate::math::liquidity_math::add_liquidity_delta;
+use crate::math::oracle_math::write_observation;
+use crate::math::reward_math::*;
+use crate::math::dynamic_fee_math::*;
+use crate::utils::{
+    get_transfer_fee_excluded_amount, get_transfer_hook_program_id, get_user_token_account, transfer_from_user,
+    transfer_from_vault, unwrap_native_sol,
+};
+use crate::events::Swapped;
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct Swap<'info> {
     #[account(mut)]
     pub user: Signer<'info>,
+    // Either user token account may be omitted on a native SOL side, lamports are then wrapped in temp_wsol_account
+    #[account(
+        mut,
+        token::mint = pool.token_a_mint,
+        token::authority = user,
+        token::token_program = token_program_a,
+    )]
+    pub user_token_a_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    #[account(
+        mut,
+        token::mint = pool.token_b_mint,
+        token::authority = user,
+        token::token_program = token_program_b,
+    )]
+    pub user_token_b_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
     #[account(mut,
         seeds = [
-            b"position",
-            pool.key().as_ref(),
-            user.key().as_ref(),
-            position.tick_lower.to_le_bytes().as_ref(),
-            position.tick_upper.to_le_bytes().as_ref(),
+            b"pool",
+            pool.token_a_mint.as_ref(),
+            pool.token_b_mint.as_ref(),
         ],
-        bump = position.bump,
+        bump = pool.bump,
     )]
-    pub position: Account<'info, Position>,
-    #[account(mut)]
-    pub user_token_a_account: Account<'info, TokenAccount>,
-    #[account(mut)]
-    pub user_token_b_account: Account<'info, TokenAccount>,
-    #[account(mut)]
     pub pool: Account<'info, Pool>,
-    #[account(mut)]
-    pub pool_token_a_vault: Account<'info, TokenAccount>,
-    #[account(mut)]
-    pub pool_token_b_vault: Account<'info, TokenAccount>,
-    pub pool_authority: AccountInfo<'info>,
-    pub token_program: Program<'info, Token>,
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(mut,
+        seeds = [b"observation", pool.key().as_ref()],
+        bump = observation.load()?.bump,
+    )]
+    pub observation: AccountLoader<'info, Observation>,
+    #[account(mut, address = pool.token_a_vault)]
+    pub pool_token_a_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut, address = pool.token_b_vault)]
+    pub pool_token_b_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        address = pool.token_a_mint,
+        constraint = config.is_transfer_hook_allowed(get_transfer_hook_program_id(&token_mint_a)) @ Errors::TransferHookNotAllowed,
+    )]
+    pub token_mint_a: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        address = pool.token_b_mint,
+        constraint = config.is_transfer_hook_allowed(get_transfer_hook_program_id(&token_mint_b)) @ Errors::TransferHookNotAllowed,
+    )]
+    pub token_mint_b: Box<InterfaceAccount<'info, Mint>>,
+    /// CHECK: created and closed by the handler, address checked by seeds
+    #[account(mut, seeds = [b"wsol", user.key().as_ref()], bump)]
+    pub temp_wsol_account: Option<UncheckedAccount<'info>>,
+    pub token_program_a: Interface<'info, TokenInterface>,
+    pub token_program_b: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn swap_handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, Swap<'info>>,
+    amount_in: u64,
+    min_amount_out: u64,
+    a_to_b: bool,
+) -> Result<()> {
+    ctx.accounts.config.check_not_paused(&ctx.accounts.pool, PAUSE_SWAPS)?;
+
+    let timestamp = Clock::get()?.unix_timestamp;
+
+    // Record the price in effect until now before the swap moves it
+    write_observation(
+        &ctx.accounts.observation,
+        timestamp,
+        ctx.accounts.pool.tick_current,
+        ctx.accounts.pool.liquidity,
+    )?;
+
+    // Accrue rewards before the swap moves liquidity in and out of range
+    update_pool_rewards(&mut ctx.accounts.pool, timestamp)?;
+
+    // Volatility since the previous swaps sets the variable fee of this one
+    update_volatility_accumulator(&mut ctx.accounts.pool, timestamp)?;
+
+    // Only what reaches the vault after the input mint's transfer fee is swapped
+    let mint_in = if a_to_b { &ctx.accounts.token_mint_a } else { &ctx.accounts.token_mint_b };
+    let amount_in_after_fee = get_transfer_fee_excluded_amount(mint_in, amount_in)?;
+
+    let sqrt_price_before = ctx.accounts.pool.sqrt_price;
+    let tick_before = ctx.accounts.pool.tick_current;
+    let liquidity_before = ctx.accounts.pool.liquidity;
+
+    // Crossed ticks come first in remaining_accounts, transfer hook accounts follow them
+    let (amount_consumed, total_amount_out, fee_amount) = swap_internal(
+        &mut ctx.accounts.pool,
+        ctx.remaining_accounts,
+        amount_in_after_fee,
+        a_to_b,
+        timestamp,
+    )?;
+
+    // The vault sends the full output, the output mint's transfer fee comes out of what the user receives
+    let amount_out = u64::try_from(total_amount_out).map_err(|_| Errors::TokenMaxExceeded)?;
+
+    // Slippage is checked on what the user actually receives
+    let mint_out = if a_to_b { &ctx.accounts.token_mint_b } else { &ctx.accounts.token_mint_a };
+    let amount_received = get_transfer_fee_excluded_amount(mint_out, amount_out)?;
+    require!(amount_received >= min_amount_out, Errors::SlippageExceeded);
+
+    // Wrap the lamports paid in, or open an empty account to receive into, on a native SOL side
+    let (user_token_a, wrapped_a) = get_user_token_account(
+        &ctx.accounts.user_token_a_account,
+        &ctx.accounts.user,
+        &ctx.accounts.temp_wsol_account,
+        ctx.bumps.temp_wsol_account,
+        &ctx.accounts.token_mint_a,
+        &ctx.accounts.token_program_a,
+        &ctx.accounts.system_program,
+        if a_to_b { amount_in } else { 0 },
+    )?;
+    let (user_token_b, wrapped_b) = get_user_token_account(
+        &ctx.accounts.user_token_b_account,
+        &ctx.accounts.user,
+        &ctx.accounts.temp_wsol_account,
+        ctx.bumps.temp_wsol_account,
+        &ctx.accounts.token_mint_b,
+        &ctx.accounts.token_program_b,
+        &ctx.accounts.system_program,
+        if a_to_b { 0 } else { amount_in },
+    )?;
+
+    // Perform token transfers
+    if a_to_b {
+        // user pays A, receives B
+        transfer_from_user(
+            &ctx.accounts.user,
+            user_token_a.clone(),
+            &ctx.accounts.pool_token_a_vault,
+            &ctx.accounts.token_mint_a,
+            &ctx.accounts.token_program_a,
+            ctx.remaining_accounts,
+            amount_in,
+        )?;
+        transfer_from_vault(
+            &ctx.accounts.pool,
+            &ctx.accounts.pool_token_b_vault,
+            user_token_b.clone(),
+            &ctx.accounts.token_mint_b,
+            &ctx.accounts.token_program_b,
+            ctx.remaining_accounts,
+            amount_out,
+        )?;
+    } else {
+        // user pays B, receives A
+        transfer_from_user(
+            &ctx.accounts.user,
+            user_token_b.clone(),
+            &ctx.accounts.pool_token_b_vault,
+            &ctx.accounts.token_mint_b,
+            &ctx.accounts.token_program_b,
+            ctx.remaining_accounts,
+            amount_in,
+        )?;
+        transfer_from_vault(
+            &ctx.accounts.pool,
+            &ctx.accounts.pool_token_a_vault,
+            user_token_a.clone(),
+            &ctx.accounts.token_mint_a,
+            &ctx.accounts.token_program_a,
+            ctx.remaining_accounts,
+            amount_out,
+        )?;
+    }
+
+    // Close the temporary wSOL account so the user is left with lamports
+    if wrapped_a {
+        unwrap_native_sol(&ctx.accounts.user, &user_token_a, &ctx.accounts.token_program_a)?;
+    }
+    if wrapped_b {
+        unwrap_native_sol(&ctx.accounts.user, &user_token_b, &ctx.accounts.token_program_b)?;
+    }
+
+    emit_cpi!(Swapped {
+        pool: ctx.accounts.pool.key(),
+        user: ctx.accounts.user.key(),
+        a_to_b,
+        amount_in: u64::try_from(amount_consumed).map_err(|_| Errors::TokenMaxExceeded)?,
+        amount_out,
+        fee_amount: u64::try_from(fee_amount).map_err(|_| Errors::TokenMaxExceeded)?,
+        sqrt_price_before,
+        sqrt_price_after: ctx.accounts.pool.sqrt_price,
+        tick_before,
+        tick_after: ctx.accounts.pool.tick_current,
+        liquidity_before,
+        liquidity_after: ctx.accounts.pool.liquidity,
+    });
+
+    Ok(())
+}
+
+// Loads a tick account passed in remaining_accounts and checks it is the pool's tick at tick_index
+pub(crate) fn load_tick(tick_account_info: &AccountInfo, pool_key: &Pubkey, tick_index: i32) -> Result<Tick> {
+    require_keys_eq!(*tick_account_info.owner, crate::ID, Errors::InvalidTick);
+    let tick_account = Tick::try_deserialize(&mut &tick_account_info.data.borrow()[..])?;
+
+    require_eq!(tick_account.tick_index, tick_index, Errors::InvalidTick);
+    let tick_address = Pubkey::create_program_address(
+        &[b"tick", pool_key.as_ref(), &tick_index.to_le_bytes(), &[tick_account.bump]],
+        &crate::ID,
+    )
+    .map_err(|_| Errors::InvalidTick)?;
+    require_keys_eq!(tick_address, tick_account_info.key(), Errors::InvalidTick);
+
+    Ok(tick_account)
 }
 
+// Walks the initialized ticks and updates pool state, returns (amount_in consumed, amount_out, swap fee)
+// Tick accounts must be passed in the order they are crossed, each followed by its partner tick when it fills range orders
+pub fn swap_internal(
+    pool: &mut Account<Pool>,
+    tick_accounts: &[AccountInfo],
+    amount_in: u64,
+    a_to_b: bool,
+    timestamp: i64,
+) -> Result<(u128, u128, u128)> {
+    let pool_key = pool.key();
+    let steps = swap_steps(pool, &pool_key, tick_accounts, amount_in, a_to_b, true, 0, timestamp, true)?;
 
-pub fn swap_handler(ctx: Context<Swap>, amount_in: u64, a_to_b: bool) -> Result<()> {
-    let initialized_ticks = &ctx.accounts.pool.initialized_ticks.clone();
-    let pool = &mut ctx.accounts.pool;
+    Ok((steps.amount_in, steps.amount_out, steps.fee_amount))
+}
+
+// Totals of a run of the swap loop, amount_in includes the swap fee
+pub(crate) struct SwapSteps {
+    pub amount_in: u128,
+    pub amount_out: u128,
+    pub fee_amount: u128,
+    pub amount_remaining: u128,
+    pub ticks_crossed: u32,
+}
+
+// The swap loop shared by swap_internal and quote_swap. It moves the pool to where the swap ends and only writes
+// crossed ticks back to their accounts when write_ticks is set, a quote runs it on a copy of the pool.
+// amount is the input for exact_in and the output otherwise, the loop stops early at sqrt_price_limit (0 for none).
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn swap_steps(
+    pool: &mut Pool,
+    pool_key: &Pubkey,
+    tick_accounts: &[AccountInfo],
+    amount: u64,
+    a_to_b: bool,
+    exact_in: bool,
+    sqrt_price_limit: u128,
+    timestamp: i64,
+    write_ticks: bool,
+) -> Result<SwapSteps> {
+    let initialized_ticks = &pool.initialized_ticks.clone();
     let mut tick_current = pool.tick_current;
     let mut sqrt_price = pool.sqrt_price;
-    let mut liquidity = pool.liquidity as u128;
-    let mut amount_remaining = amount_in as u128;
+    let mut liquidity = pool.liquidity;
+    let mut amount_remaining = amount as u128;
+    let mut total_amount_in: u128 = 0;
     let mut total_amount_out: u128 = 0;
-    let fee_rate = pool.fee as u128;
+    let mut total_fee: u128 = 0;
+    let mut ticks_crossed: u32 = 0;
+    let mut accounts_used: usize = 0;
+    let fee_rate = get_effective_fee(pool, timestamp);
+    let tick_spacing = pool.tick_spacing as i32;
 
-    // Iterate through ticks until we exhaust the amount_in
+    // Iterate through ticks until we exhaust the amount
     while amount_remaining > 0 {
         // Get next tick
         let next_tick = get_next_tick(tick_current, initialized_ticks, a_to_b)?;
-        // Get next sqrt price
+        // Get next sqrt price, or stop at the price limit when it comes first
         let next_sqrt_price = get_sqrt_price_from_tick(next_tick)?;
+        let limit_reached = sqrt_price_limit != 0
+            && if a_to_b { sqrt_price_limit > next_sqrt_price } else { sqrt_price_limit < next_sqrt_price };
+        let target_sqrt_price = if limit_reached { sqrt_price_limit } else { next_sqrt_price };
 
         // Calculate swap step and how much amount_in, out, and fees we need to pay
         let (step_in, step_out, next_price, fee_amount) = compute_swap_step(
             sqrt_price,
-            next_sqrt_price,
+            target_sqrt_price,
             liquidity,
             amount_remaining,
             fee_rate,
-            true,
+            exact_in,
         )?;
-        // Update amount remaining and total amount out
+        // Update amount remaining and totals, step_in already includes the fee
+        let step_used = if exact_in { step_in } else { step_out };
         amount_remaining = amount_remaining
-            .checked_sub(step_in + fee_amount)
+            .checked_sub(step_used)
             .ok_or(Errors::MathError)?;
 
+        total_amount_in = total_amount_in.checked_add(step_in).ok_or(Errors::MathError)?;
         total_amount_out = total_amount_out
             .checked_add(step_out)
             .ok_or(Errors::MathError)?;
+        total_fee = total_fee.checked_add(fee_amount).ok_or(Errors::MathError)?;
 
         sqrt_price = next_price;
-        tick_current = next_tick;
 
         // Update fee growth global for a and b based on direction of swap
+        let fee_growth_delta = get_fee_growth_delta(fee_amount, liquidity)?;
         if a_to_b {
-            pool.fee_growth_global_a = pool.fee_growth_global_a
-                .checked_add(fee_amount)
-                .ok_or(Errors::MathError)?;
+            pool.fee_growth_global_a = pool.fee_growth_global_a.wrapping_add(fee_growth_delta);
         } else {
-            pool.fee_growth_global_b = pool.fee_growth_global_b
-                .checked_add(fee_amount)
-                .ok_or(Errors::MathError)?;
+            pool.fee_growth_global_b = pool.fee_growth_global_b.wrapping_add(fee_growth_delta);
         }
 
         if sqrt_price == next_sqrt_price {
-            // We're crossing a tick → update liquidity
-            // Use the account info directly without storing it
-            let tick_account_info = &ctx.remaining_accounts[0];
+            // We're crossing a tick → update liquidity. Moving down leaves the price on the crossed tick's lower
+            // side, so tick_current is one below it, as fee growth inside and range order fills expect
+            tick_current = if a_to_b { next_tick - 1 } else { next_tick };
+            let tick_account_info = tick_accounts
+                .get(accounts_used)
+                .ok_or(Errors::TickNotFound)?;
+            accounts_used += 1;
+            ticks_crossed += 1;
 
-            // Deserialize tick account from account data
-            let mut tick_account = Tick::try_deserialize(&mut &tick_account_info.data.borrow()[..])?;
-            
-            // Validate that this is the correct tick account
-            require_eq!(tick_account.tick_index, next_tick, Errors::InvalidTick);
-            
+            let mut tick_account = load_tick(tick_account_info, pool_key, next_tick)?;
 
             let fee_growth_global_a = pool.fee_growth_global_a;
             let fee_growth_global_b = pool.fee_growth_global_b;
+            let reward_growths_global = get_reward_growths_global(pool);
 
-            // Update tick account in terms of fee 
-            cross_tick(&mut tick_account,fee_growth_global_a, fee_growth_global_b, a_to_b)?;
+            // Update tick account in terms of fee and rewards
+            cross_tick(&mut tick_account, fee_growth_global_a, fee_growth_global_b, reward_growths_global)?;
 
-            // Serialize tick account back to account data
-            tick_account.serialize(&mut &mut tick_account_info.data.borrow_mut()[..])?;
-
-            // Update liquidity
+            // Liquidity applied when crossing, before filled range orders are taken out of the ticks
             let liquidity_net = tick_account.liquidity_net;
-            liquidity = if a_to_b {
-                liquidity.checked_sub(liquidity_net as u128)
+
+            // Settle range orders filled by this crossing, their partner tick is the next account
+            let range_order_liquidity = if a_to_b {
+                tick_account.range_order_liquidity_down
             } else {
-                liquidity.checked_add(liquidity_net as u128)
+                tick_account.range_order_liquidity_up
+            };
+            if range_order_liquidity > 0 {
+                let partner_tick = if a_to_b {
+                    next_tick.checked_add(tick_spacing)
+                } else {
+                    next_tick.checked_sub(tick_spacing)
+                }
+                .ok_or(Errors::MathError)?;
+                let partner_account_info = tick_accounts
+                    .get(accounts_used)
+                    .ok_or(Errors::TickNotFound)?;
+                accounts_used += 1;
+
+                let mut partner_tick_account = load_tick(partner_account_info, pool_key, partner_tick)?;
+                fill_range_orders(
+                    &mut tick_account,
+                    &mut partner_tick_account,
+                    a_to_b,
+                    fee_growth_global_a,
+                    fee_growth_global_b,
+                    reward_growths_global,
+                )?;
+                if write_ticks {
+                    partner_tick_account.try_serialize(&mut &mut partner_account_info.data.borrow_mut()[..])?;
+                }
             }
-            .ok_or(Errors::MathError)?;
+
+            // Serialize tick account back to account data
+            if write_ticks {
+                tick_account.try_serialize(&mut &mut tick_account_info.data.borrow_mut()[..])?;
+            }
+
+            // Update liquidity, moving down through a tick removes its net liquidity
+            let liquidity_net = if a_to_b {
+                liquidity_net.checked_neg().ok_or(Errors::MathError)?
+            } else {
+                liquidity_net
+            };
+            liquidity = add_liquidity_delta(liquidity, liquidity_net)?;
         } else {
-            // Swap is done before crossing the tick
+            // Swap is done before crossing the tick, the amount ran out or the price limit was hit
+            tick_current = get_tick_from_sqrt_price(sqrt_price)?;
             break;
         }
     }
@@ -124,52 +400,11 @@ pub fn swap_handler(ctx: Context<Swap>, amount_in: u64, a_to_b: bool) -> Result<
     pool.sqrt_price = sqrt_price;
     pool.liquidity = liquidity;
 
-    // Perform token transfers
-    if a_to_b {
-        // user pays A, receives B
-        let cpi_accounts = Transfer {
-            from: ctx.accounts.user_token_a_account.to_account_info(),
-            to: ctx.accounts.pool_token_a_vault.to_account_info(),
-            authority: ctx.accounts.user.to_account_info(),
-        };
-        token::transfer(
-            CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts),
-            amount_in,
-        )?;
-
-        let cpi_accounts = Transfer {
-            from: ctx.accounts.pool_token_b_vault.to_account_info(),
-            to: ctx.accounts.user_token_b_account.to_account_info(),
-            authority: ctx.accounts.pool_authority.to_account_info(),
-        };
-        token::transfer(
-            CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts),
-            total_amount_out as u64,
-            )?;
-
-    } else {
-        // user pays B, receives A
-        let cpi_accounts = Transfer {
-            from: ctx.accounts.user_token_b_account.to_account_info(),
-            to: ctx.accounts.pool_token_b_vault.to_account_info(),
-            authority: ctx.accounts.user.to_account_info(),
-        };
-        token::transfer(
-            CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts),
-            amount_in,
-            )?;
-
-            let cpi_accounts = Transfer {
-                from: ctx.accounts.pool_token_a_vault.to_account_info(),
-                to: ctx.accounts.user_token_a_account.to_account_info(),
-                authority: ctx.accounts.pool_authority.to_account_info(),
-            };
-            token::transfer(
-                CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts),
-                total_amount_out as u64,
-                )?;
-        }
-    
-    Ok(())
+    Ok(SwapSteps {
+        amount_in: total_amount_in,
+        amount_out: total_amount_out,
+        fee_amount: total_fee,
+        amount_remaining,
+        ticks_crossed,
+    })
 }
-
@@ -0,0 +1,224 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::state::*;
+use crate::errors::*;
+use crate::math::oracle_math::write_observation;
+use crate::math::reward_math::*;
+use crate::math::dynamic_fee_math::*;
+use crate::instructions::swap::swap_internal;
+use crate::utils::{get_transfer_fee_excluded_amount, get_transfer_hook_program_id, transfer_from_user, transfer_from_vault};
+use crate::events::Swapped;
+
+// Accounts every hop passes in remaining_accounts ahead of its tick accounts:
+// pool, observation, token_a_vault, token_b_vault, token_mint_a, token_mint_b, token_program_a, token_program_b
+pub const ACCOUNTS_PER_HOP: usize = 8;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct RouteHop {
+    pub a_to_b: bool,
+    pub tick_account_count: u8, // Tick accounts crossed in this hop, passed after its fixed accounts
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SwapRoute<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(mut, token::authority = user)]
+    pub user_token_in: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut)]
+    pub user_token_out: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+}
+
+// Accounts of one hop, loaded from remaining_accounts and checked against the pool
+struct HopAccounts<'info> {
+    pool: Account<'info, Pool>,
+    observation: AccountLoader<'info, Observation>,
+    vault_in: InterfaceAccount<'info, TokenAccount>,
+    vault_out: InterfaceAccount<'info, TokenAccount>,
+    mint_in: InterfaceAccount<'info, Mint>,
+    mint_out: InterfaceAccount<'info, Mint>,
+    token_program_in: Interface<'info, TokenInterface>,
+    token_program_out: Interface<'info, TokenInterface>,
+}
+
+// Chains exact-input swaps through the given pools, intermediate amounts move straight from vault to vault.
+// After the hops, remaining_accounts may carry transfer hook accounts for any mint on the route.
+pub fn swap_route_handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, SwapRoute<'info>>,
+    amount_in: u64,
+    min_amount_out: u64,
+    hops: Vec<RouteHop>,
+) -> Result<()> {
+    require!(!hops.is_empty(), Errors::InvalidRoute);
+
+    let timestamp = Clock::get()?.unix_timestamp;
+    let remaining_accounts = ctx.remaining_accounts;
+    let mut offset = 0;
+    let mut previous: Option<(HopAccounts<'info>, u64)> = None;
+
+    for hop in hops.iter() {
+        let fixed_end = offset + ACCOUNTS_PER_HOP;
+        let ticks_end = fixed_end + hop.tick_account_count as usize;
+        require!(ticks_end <= remaining_accounts.len(), Errors::InvalidRoute);
+        let mut accounts = load_hop(&remaining_accounts[offset..fixed_end], hop.a_to_b, &ctx.accounts.config)?;
+        let tick_accounts = &remaining_accounts[fixed_end..ticks_end];
+        offset = ticks_end;
+
+        // Bring the input of this hop into its vault, from the user or from the previous pool
+        let amount_sent = match &previous {
+            None => {
+                require_keys_eq!(ctx.accounts.user_token_in.mint, accounts.mint_in.key(), Errors::InvalidRoute);
+                transfer_from_user(
+                    &ctx.accounts.user,
+                    ctx.accounts.user_token_in.to_account_info(),
+                    &accounts.vault_in,
+                    &accounts.mint_in,
+                    &accounts.token_program_in,
+                    remaining_accounts,
+                    amount_in,
+                )?;
+                amount_in
+            }
+            Some((previous_accounts, previous_amount_out)) => {
+                require_keys_eq!(previous_accounts.mint_out.key(), accounts.mint_in.key(), Errors::InvalidRoute);
+                transfer_from_vault(
+                    &previous_accounts.pool,
+                    &previous_accounts.vault_out,
+                    accounts.vault_in.to_account_info(),
+                    &previous_accounts.mint_out,
+                    &previous_accounts.token_program_out,
+                    remaining_accounts,
+                    *previous_amount_out,
+                )?;
+                *previous_amount_out
+            }
+        };
+        let amount_received = get_transfer_fee_excluded_amount(&accounts.mint_in, amount_sent)?;
+
+        write_observation(
+            &accounts.observation,
+            timestamp,
+            accounts.pool.tick_current,
+            accounts.pool.liquidity,
+        )?;
+        update_pool_rewards(&mut accounts.pool, timestamp)?;
+        update_volatility_accumulator(&mut accounts.pool, timestamp)?;
+
+        let sqrt_price_before = accounts.pool.sqrt_price;
+        let tick_before = accounts.pool.tick_current;
+        let liquidity_before = accounts.pool.liquidity;
+
+        let (amount_consumed, amount_out, fee_amount) = swap_internal(
+            &mut accounts.pool,
+            tick_accounts,
+            amount_received,
+            hop.a_to_b,
+            timestamp,
+        )?;
+        let amount_out = u64::try_from(amount_out).map_err(|_| Errors::TokenMaxExceeded)?;
+
+        // One event per hop, as if each pool had been swapped on its own
+        emit_cpi!(Swapped {
+            pool: accounts.pool.key(),
+            user: ctx.accounts.user.key(),
+            a_to_b: hop.a_to_b,
+            amount_in: u64::try_from(amount_consumed).map_err(|_| Errors::TokenMaxExceeded)?,
+            amount_out,
+            fee_amount: u64::try_from(fee_amount).map_err(|_| Errors::TokenMaxExceeded)?,
+            sqrt_price_before,
+            sqrt_price_after: accounts.pool.sqrt_price,
+            tick_before,
+            tick_after: accounts.pool.tick_current,
+            liquidity_before,
+            liquidity_after: accounts.pool.liquidity,
+        });
+
+        // Hop accounts are not part of the context, so they are written back here, before a later hop can load the same
+        // pool. The observation is zero copy and already written
+        accounts.pool.exit(&crate::ID)?;
+
+        previous = Some((accounts, amount_out));
+    }
+
+    let (last_accounts, amount_out) = previous.ok_or(Errors::InvalidRoute)?;
+    require_keys_eq!(ctx.accounts.user_token_out.mint, last_accounts.mint_out.key(), Errors::InvalidRoute);
+
+    // Slippage is checked on what the user actually receives
+    let amount_received = get_transfer_fee_excluded_amount(&last_accounts.mint_out, amount_out)?;
+    require!(amount_received >= min_amount_out, Errors::SlippageExceeded);
+
+    transfer_from_vault(
+        &last_accounts.pool,
+        &last_accounts.vault_out,
+        ctx.accounts.user_token_out.to_account_info(),
+        &last_accounts.mint_out,
+        &last_accounts.token_program_out,
+        remaining_accounts,
+        amount_out,
+    )?;
+
+    Ok(())
+}
+
+// Loads the fixed accounts of a hop and applies the checks the Swap context does through constraints
+fn load_hop<'info>(accounts: &'info [AccountInfo<'info>], a_to_b: bool, config: &Config) -> Result<HopAccounts<'info>> {
+    let pool = Account::<Pool>::try_from(&accounts[0])?;
+    let pool_address = Pubkey::create_program_address(
+        &[b"pool", pool.token_a_mint.as_ref(), pool.token_b_mint.as_ref(), &[pool.bump]],
+        &crate::ID,
+    )
+    .map_err(|_| Errors::InvalidRoute)?;
+    require_keys_eq!(pool_address, pool.key(), Errors::InvalidRoute);
+    config.check_not_paused(&pool, PAUSE_SWAPS)?;
+
+    let observation = AccountLoader::<Observation>::try_from(&accounts[1])?;
+    require_keys_eq!(observation.load()?.pool, pool.key(), Errors::InvalidRoute);
+
+    let token_a_vault = InterfaceAccount::<TokenAccount>::try_from(&accounts[2])?;
+    let token_b_vault = InterfaceAccount::<TokenAccount>::try_from(&accounts[3])?;
+    require_keys_eq!(token_a_vault.key(), pool.token_a_vault, Errors::InvalidRoute);
+    require_keys_eq!(token_b_vault.key(), pool.token_b_vault, Errors::InvalidRoute);
+
+    let token_mint_a = InterfaceAccount::<Mint>::try_from(&accounts[4])?;
+    let token_mint_b = InterfaceAccount::<Mint>::try_from(&accounts[5])?;
+    require_keys_eq!(token_mint_a.key(), pool.token_a_mint, Errors::InvalidRoute);
+    require_keys_eq!(token_mint_b.key(), pool.token_b_mint, Errors::InvalidRoute);
+    require!(
+        config.is_transfer_hook_allowed(get_transfer_hook_program_id(&token_mint_a))
+            && config.is_transfer_hook_allowed(get_transfer_hook_program_id(&token_mint_b)),
+        Errors::TransferHookNotAllowed
+    );
+
+    let token_program_a = Interface::<TokenInterface>::try_from(&accounts[6])?;
+    let token_program_b = Interface::<TokenInterface>::try_from(&accounts[7])?;
+    require_keys_eq!(*token_mint_a.to_account_info().owner, token_program_a.key(), Errors::InvalidRoute);
+    require_keys_eq!(*token_mint_b.to_account_info().owner, token_program_b.key(), Errors::InvalidRoute);
+
+    Ok(if a_to_b {
+        HopAccounts {
+            pool,
+            observation,
+            vault_in: token_a_vault,
+            vault_out: token_b_vault,
+            mint_in: token_mint_a,
+            mint_out: token_mint_b,
+            token_program_in: token_program_a,
+            token_program_out: token_program_b,
+        }
+    } else {
+        HopAccounts {
+            pool,
+            observation,
+            vault_in: token_b_vault,
+            vault_out: token_a_vault,
+            mint_in: token_mint_b,
+            mint_out: token_mint_a,
+            token_program_in: token_program_b,
+            token_program_out: token_program_a,
+        }
+    })
+}
@@ -0,0 +1,108 @@
+use anchor_lang::prelude::*;
+use crate::state::{Pool, Position, PositionBundle};
+use crate::errors::Errors;
+use crate::events::PositionTransferred;
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(new_bundle_index: u16)]
+pub struct TransferPosition<'info> {
+    #[account(
+        seeds = [
+            b"pool",
+            pool.token_a_mint.as_ref(),
+            pool.token_b_mint.as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"position_bundle",
+            pool.key().as_ref(),
+            owner.key().as_ref(),
+        ],
+        bump = position_bundle.bump,
+    )]
+    pub position_bundle: Account<'info, PositionBundle>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"position",
+            position_bundle.key().as_ref(),
+            position.bundle_index.to_le_bytes().as_ref(),
+        ],
+        bump = position.bump,
+        has_one = owner,
+        close = owner,
+    )]
+    pub position: Account<'info, Position>,
+
+    // Bundle of the recipient, created by the recipient beforehand
+    #[account(
+        mut,
+        seeds = [
+            b"position_bundle",
+            pool.key().as_ref(),
+            new_position_bundle.owner.as_ref(),
+        ],
+        bump = new_position_bundle.bump,
+        constraint = new_position_bundle.key() != position_bundle.key() @ Errors::InvalidPosition,
+    )]
+    pub new_position_bundle: Account<'info, PositionBundle>,
+
+    #[account(init, payer = owner,
+        seeds = [
+            b"position",
+            new_position_bundle.key().as_ref(),
+            new_bundle_index.to_le_bytes().as_ref(),
+        ],
+        bump,
+        space = 8 + Position::INIT_SPACE,
+    )]
+    pub new_position: Account<'info, Position>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+// Moves the position into a slot of the recipient's bundle, the old position account is closed
+pub fn transfer_position_handler(ctx: Context<TransferPosition>, new_bundle_index: u16) -> Result<()> {
+    let position = &ctx.accounts.position;
+    let new_position = &mut ctx.accounts.new_position;
+    let new_position_bundle = &mut ctx.accounts.new_position_bundle;
+
+    ctx.accounts.position_bundle.free_slot(position.bundle_index)?;
+    new_position_bundle.occupy_slot(new_bundle_index)?;
+
+    new_position.owner = new_position_bundle.owner;
+    new_position.tick_lower = position.tick_lower;
+    new_position.tick_upper = position.tick_upper;
+    new_position.liquidity = position.liquidity;
+    new_position.fee_growth_inside_a = position.fee_growth_inside_a;
+    new_position.fee_growth_inside_b = position.fee_growth_inside_b;
+    new_position.token_a_earned = position.token_a_earned;
+    new_position.token_b_earned = position.token_b_earned;
+    new_position.bundle_index = new_bundle_index;
+    new_position.range_order = position.range_order;
+    new_position.range_order_sells_a = position.range_order_sells_a;
+    new_position.range_order_fills = position.range_order_fills;
+    new_position.reward_infos = position.reward_infos;
+    // The previous owner's operator has no say over the new owner's position
+    new_position.delegate = None;
+    new_position.bump = ctx.bumps.new_position;
+
+    emit_cpi!(PositionTransferred {
+        pool: ctx.accounts.pool.key(),
+        position: position.key(),
+        new_position: new_position.key(),
+        old_owner: position.owner,
+        new_owner: new_position.owner,
+    });
+
+    Ok(())
+}
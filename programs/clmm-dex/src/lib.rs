@@ -6,6 +6,8 @@ pub mod instructions;
 pub mod state;
 pub mod math;
 pub mod errors;
+pub mod events;
+pub mod utils;
 
 use instructions::*;
 
@@ -26,28 +28,209 @@ pub mod clmm_dex {
             initialize_pool_handler(ctx, sqrt_price_x64, tick_spacing, fee, fee_growth_global_a, fee_growth_global_b)
     }
 
+    // Initialize position bundle
+    pub fn initialize_position_bundle(ctx: Context<InitializePositionBundle>) -> Result<()> {
+        initialize_position_bundle_handler(ctx)
+    }
+
 // Provide liquidity
-    pub fn provide_liquidity(ctx: Context<ProvideLiquidity>, 
+    pub fn provide_liquidity<'info>(ctx: Context<'_, '_, '_, 'info, ProvideLiquidity<'info>>, 
         tick_lower: i32, 
         tick_upper: i32, 
-        liquidity: u128
+        liquidity: u128,
+        bundle_index: u16,
+        range_order: bool,
     ) -> Result<()> {
-        provide_liquidity_handler(ctx, tick_lower, tick_upper, liquidity)
+        provide_liquidity_handler(ctx, tick_lower, tick_upper, liquidity, bundle_index, range_order)
     }   
 
     // Remove liquidity
-    pub fn remove_liquidity(ctx: Context<RemoveLiquidity>
+    pub fn remove_liquidity<'info>(ctx: Context<'_, '_, '_, 'info, RemoveLiquidity<'info>>
     ) -> Result<()> {
         remove_liquidity_handler(ctx)
     }
 
     // Swap
-    pub fn swap(ctx: Context<Swap>, 
+    pub fn swap<'info>(ctx: Context<'_, '_, '_, 'info, Swap<'info>>, 
         amount_in: u64, 
+        min_amount_out: u64,
         a_to_b: bool
     ) -> Result<()> {
-        swap_handler(ctx, amount_in, a_to_b)
+        swap_handler(ctx, amount_in, min_amount_out, a_to_b)
+    }
+
+    // Set or clear the position delegate
+    pub fn set_position_delegate(ctx: Context<SetPositionDelegate>,
+        delegate: Option<Pubkey>
+    ) -> Result<()> {
+        set_position_delegate_handler(ctx, delegate)
+    }
+
+    // Transfer position to another owner
+    pub fn transfer_position(ctx: Context<TransferPosition>,
+        new_bundle_index: u16
+    ) -> Result<()> {
+        transfer_position_handler(ctx, new_bundle_index)
+    }
+
+    // Collect fees
+    pub fn collect_fees<'info>(ctx: Context<'_, '_, '_, 'info, CollectFees<'info>>) -> Result<()> {
+        collect_fees_handler(ctx)
+    }
+
+    // Compound fees
+    pub fn compound_fees(ctx: Context<CompoundFees>) -> Result<()> {
+        compound_fees_handler(ctx)
+    }
+
+    // Rebalance position to a new tick range
+    pub fn rebalance_position(ctx: Context<RebalancePosition>,
+        new_tick_lower: i32,
+        new_tick_upper: i32,
+        min_liquidity: u128,
+        swap_amount_in: u64,
+        a_to_b: bool,
+    ) -> Result<()> {
+        rebalance_position_handler(ctx, new_tick_lower, new_tick_upper, min_liquidity, swap_amount_in, a_to_b)
+    }
+
+    // Claim or cancel a range order
+    pub fn claim_range_order<'info>(ctx: Context<'_, '_, '_, 'info, ClaimRangeOrder<'info>>) -> Result<()> {
+        claim_range_order_handler(ctx)
+    }
+
+    // Observe oracle cumulatives, returned through return data
+    pub fn observe(ctx: Context<Observe>,
+        seconds_agos: Vec<u32>
+    ) -> Result<ObserveResult> {
+        observe_handler(ctx, seconds_agos)
+    }
+
+    // Increase observation cardinality
+    pub fn increase_observation_cardinality(ctx: Context<IncreaseObservationCardinality>,
+        cardinality_next: u16
+    ) -> Result<()> {
+        increase_observation_cardinality_handler(ctx, cardinality_next)
+    }
+
+    // Initialize reward
+    pub fn initialize_reward(ctx: Context<InitializeReward>,
+        reward_index: u8
+    ) -> Result<()> {
+        initialize_reward_handler(ctx, reward_index)
+    }
+
+    // Set reward emissions
+    pub fn set_reward_emissions(ctx: Context<SetRewardEmissions>,
+        reward_index: u8,
+        emissions_per_second_x64: u128,
+    ) -> Result<()> {
+        set_reward_emissions_handler(ctx, reward_index, emissions_per_second_x64)
+    }
+
+    // Collect reward
+    pub fn collect_reward<'info>(ctx: Context<'_, '_, '_, 'info, CollectReward<'info>>,
+        reward_index: u8
+    ) -> Result<()> {
+        collect_reward_handler(ctx, reward_index)
+    }
+
+    // Set dynamic fee parameters
+    pub fn set_dynamic_fee(ctx: Context<SetDynamicFee>,
+        params: DynamicFeeParams
+    ) -> Result<()> {
+        set_dynamic_fee_handler(ctx, params)
+    }
+
+    // Initialize config
+    pub fn initialize_config(ctx: Context<InitializeConfig>,
+        pause_authority: Pubkey,
+        fee_change_delay: i64,
+    ) -> Result<()> {
+        initialize_config_handler(ctx, pause_authority, fee_change_delay)
+    }
+
+    // Set pause authority
+    pub fn set_pause_authority(ctx: Context<SetPauseAuthority>,
+        pause_authority: Pubkey
+    ) -> Result<()> {
+        set_pause_authority_handler(ctx, pause_authority)
+    }
+
+    // Set transfer hook allowlist
+    pub fn set_transfer_hook_allowlist(ctx: Context<SetTransferHookAllowlist>,
+        transfer_hook_programs: Vec<Pubkey>
+    ) -> Result<()> {
+        set_transfer_hook_allowlist_handler(ctx, transfer_hook_programs)
+    }
+
+    // Set global kill switch
+    pub fn set_global_pause(ctx: Context<SetGlobalPause>,
+        paused: bool
+    ) -> Result<()> {
+        set_global_pause_handler(ctx, paused)
+    }
+
+    // Set pool pause flags
+    pub fn set_pool_pause_flags(ctx: Context<SetPoolPauseFlags>,
+        pause_flags: u8
+    ) -> Result<()> {
+        set_pool_pause_flags_handler(ctx, pause_flags)
+    }
+
+    // Propose fee change
+    pub fn propose_fee_change(ctx: Context<ProposeFeeChange>,
+        new_fee: u8
+    ) -> Result<()> {
+        propose_fee_change_handler(ctx, new_fee)
     }
-}
 
+    // Apply fee change
+    pub fn apply_fee_change(ctx: Context<ApplyFeeChange>) -> Result<()> {
+        apply_fee_change_handler(ctx)
+    }
+
+    // Flash loan
+    pub fn flash_loan<'info>(ctx: Context<'_, '_, '_, 'info, FlashLoan<'info>>,
+        amount_a: u64,
+        amount_b: u64
+    ) -> Result<()> {
+        flash_loan_handler(ctx, amount_a, amount_b)
+    }
+
+    // Flash repay
+    pub fn flash_repay<'info>(ctx: Context<'_, '_, '_, 'info, FlashRepay<'info>>,
+        amount_a: u64,
+        amount_b: u64
+    ) -> Result<()> {
+        flash_repay_handler(ctx, amount_a, amount_b)
+    }
 
+    // Flash swap
+    pub fn flash_swap<'info>(ctx: Context<'_, '_, '_, 'info, FlashSwap<'info>>,
+        amount_in: u64,
+        a_to_b: bool,
+        data: Vec<u8>
+    ) -> Result<()> {
+        flash_swap_handler(ctx, amount_in, a_to_b, data)
+    }
+
+    // Swap along a route of pools
+    pub fn swap_route<'info>(ctx: Context<'_, '_, 'info, 'info, SwapRoute<'info>>,
+        amount_in: u64,
+        min_amount_out: u64,
+        hops: Vec<RouteHop>
+    ) -> Result<()> {
+        swap_route_handler(ctx, amount_in, min_amount_out, hops)
+    }
+
+    // Quote a swap without executing it
+    pub fn quote_swap(ctx: Context<QuoteSwap>,
+        amount: u64,
+        a_to_b: bool,
+        exact_in: bool,
+        sqrt_price_limit: u128
+    ) -> Result<SwapQuote> {
+        quote_swap_handler(ctx, amount, a_to_b, exact_in, sqrt_price_limit)
+    }
+}
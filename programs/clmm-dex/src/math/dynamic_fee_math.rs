@@ -0,0 +1,63 @@
+use anchor_lang::prelude::*;
+use crate::state::{DynamicFee, Pool};
+use crate::errors::Errors;
+
+// Updates the volatility accumulator from the tick movement since the reference, before a swap.
+// After the filter period the reference moves to the current tick and the old volatility decays.
+pub fn update_volatility_accumulator(pool: &mut Pool, timestamp: i64) -> Result<()> {
+    let tick_current = pool.tick_current;
+    let tick_spacing = pool.tick_spacing as i64;
+    let dynamic_fee = &mut pool.dynamic_fee;
+    if !dynamic_fee.enabled {
+        return Ok(());
+    }
+
+    let elapsed = timestamp
+        .checked_sub(dynamic_fee.last_update_timestamp)
+        .ok_or(Errors::MathError)?;
+    if elapsed >= dynamic_fee.filter_period as i64 {
+        dynamic_fee.tick_reference = tick_current;
+        dynamic_fee.volatility_reference = if elapsed < dynamic_fee.decay_period as i64 {
+            (dynamic_fee.volatility_accumulator as u64 * dynamic_fee.reduction_factor as u64 / 10_000) as u32
+        } else {
+            0
+        };
+    }
+
+    // Tick spacings moved since the reference, scaled
+    let tick_movement = ((tick_current as i64 - dynamic_fee.tick_reference as i64).unsigned_abs()
+        / tick_spacing.max(1) as u64)
+        .saturating_mul(DynamicFee::VOLATILITY_SCALE as u64);
+    dynamic_fee.volatility_accumulator = (dynamic_fee.volatility_reference as u64)
+        .saturating_add(tick_movement)
+        .min(dynamic_fee.max_volatility_accumulator as u64) as u32;
+    dynamic_fee.last_update_timestamp = timestamp;
+
+    Ok(())
+}
+
+// Base fee in effect at the timestamp, a pending fee change applies once its activation time has passed
+pub fn get_active_fee(pool: &Pool, timestamp: i64) -> u8 {
+    if pool.pending_fee_activation != 0 && timestamp >= pool.pending_fee_activation {
+        pool.pending_fee
+    } else {
+        pool.fee
+    }
+}
+
+// Fee rate passed into compute_swap_step, base fee plus the variable component capped by max_fee
+pub fn get_effective_fee(pool: &Pool, timestamp: i64) -> u128 {
+    let base_fee = get_active_fee(pool, timestamp) as u128;
+    let dynamic_fee = &pool.dynamic_fee;
+    if !dynamic_fee.enabled {
+        return base_fee;
+    }
+
+    let accumulator = dynamic_fee.volatility_accumulator as u128;
+    let scale = DynamicFee::VOLATILITY_SCALE as u128;
+    let variable_fee = dynamic_fee.variable_fee_control as u128 * accumulator * accumulator / (scale * scale);
+
+    base_fee
+        .saturating_add(variable_fee)
+        .min(dynamic_fee.max_fee as u128)
+}
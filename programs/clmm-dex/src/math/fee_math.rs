@@ -0,0 +1,104 @@
+use anchor_lang::prelude::*;
+use crate::state::{Position, Tick};
+use crate::errors::Errors;
+
+// Fee growth is tracked per unit of liquidity in Q64.64, like sqrt_price
+pub fn get_fee_growth_delta(fee_amount: u128, liquidity: u128) -> Result<u128> {
+    if liquidity == 0 {
+        return Ok(0);
+    }
+    let fee_growth = fee_amount
+        .checked_shl(64)
+        .ok_or(Errors::MathError)?
+        .checked_div(liquidity)
+        .ok_or(Errors::DivisionByZero)?;
+    Ok(fee_growth)
+}
+
+// Flash loan fee at the pool fee rate in millionths, rounded up so a loan is never free
+pub fn get_flash_loan_fee(amount: u64, fee_rate: u8) -> Result<u64> {
+    let fee = (amount as u128)
+        .checked_mul(fee_rate as u128)
+        .ok_or(Errors::MathError)?
+        .div_ceil(1_000_000);
+    u64::try_from(fee).map_err(|_| Errors::TokenMaxExceeded.into())
+}
+
+// Initial fee growth outside of a tick, by convention all growth so far happened below the current tick
+pub fn get_initial_fee_growth_outside(
+    tick_index: i32,
+    tick_current: i32,
+    fee_growth_global_a: u128,
+    fee_growth_global_b: u128,
+) -> (u128, u128) {
+    if tick_index <= tick_current {
+        (fee_growth_global_a, fee_growth_global_b)
+    } else {
+        (0, 0)
+    }
+}
+
+// Calculates the fee growth inside a tick range for token A and B
+pub fn get_fee_growth_inside(
+    lower_tick: &Tick,
+    upper_tick: &Tick,
+    tick_current: i32,
+    fee_growth_global_a: u128,
+    fee_growth_global_b: u128,
+) -> (u128, u128) {
+    // Fee growth below the lower tick
+    let (below_a, below_b) = if tick_current >= lower_tick.tick_index {
+        (lower_tick.fee_growth_outside_a, lower_tick.fee_growth_outside_b)
+    } else {
+        (
+            fee_growth_global_a.wrapping_sub(lower_tick.fee_growth_outside_a),
+            fee_growth_global_b.wrapping_sub(lower_tick.fee_growth_outside_b),
+        )
+    };
+
+    // Fee growth above the upper tick
+    let (above_a, above_b) = if tick_current < upper_tick.tick_index {
+        (upper_tick.fee_growth_outside_a, upper_tick.fee_growth_outside_b)
+    } else {
+        (
+            fee_growth_global_a.wrapping_sub(upper_tick.fee_growth_outside_a),
+            fee_growth_global_b.wrapping_sub(upper_tick.fee_growth_outside_b),
+        )
+    };
+
+    (
+        fee_growth_global_a.wrapping_sub(below_a).wrapping_sub(above_a),
+        fee_growth_global_b.wrapping_sub(below_b).wrapping_sub(above_b),
+    )
+}
+
+// Credits the fees accrued since the last checkpoint to the position and moves the checkpoint
+pub fn update_position_fees(
+    position: &mut Position,
+    fee_growth_inside_a: u128,
+    fee_growth_inside_b: u128,
+) -> Result<()> {
+    let fees_a = fee_growth_inside_a
+        .wrapping_sub(position.fee_growth_inside_a)
+        .checked_mul(position.liquidity)
+        .ok_or(Errors::MathError)?
+        >> 64;
+    let fees_b = fee_growth_inside_b
+        .wrapping_sub(position.fee_growth_inside_b)
+        .checked_mul(position.liquidity)
+        .ok_or(Errors::MathError)?
+        >> 64;
+
+    position.token_a_earned = position
+        .token_a_earned
+        .checked_add(u64::try_from(fees_a).map_err(|_| Errors::TokenMaxExceeded)?)
+        .ok_or(Errors::TokenMaxExceeded)?;
+    position.token_b_earned = position
+        .token_b_earned
+        .checked_add(u64::try_from(fees_b).map_err(|_| Errors::TokenMaxExceeded)?)
+        .ok_or(Errors::TokenMaxExceeded)?;
+    position.fee_growth_inside_a = fee_growth_inside_a;
+    position.fee_growth_inside_b = fee_growth_inside_b;
+
+    Ok(())
+}
@@ -0,0 +1,7 @@
+use anchor_lang::prelude::*;
+use crate::errors::Errors;
+
+// Applies a signed liquidity change, e.g. liquidity_net when crossing a tick
+pub fn add_liquidity_delta(liquidity: u128, liquidity_delta: i128) -> Result<u128> {
+    Ok(clmm_math::add_liquidity_delta(liquidity, liquidity_delta).map_err(Errors::from)?)
+}
@@ -1,7 +1,11 @@
 pub mod tick_math;
 pub mod compute_amount;
-pub mod compute_sqrt_price;
 pub mod compute_swap_step;
+pub mod dynamic_fee_math;
+pub mod fee_math;
+pub mod liquidity_math;
+pub mod oracle_math;
+pub mod reward_math;
 
 // pub use tick_math::*;
-pub use compute_amount::*;
\ No newline at end of file
+pub use compute_amount::*;
@@ -0,0 +1,158 @@
+use anchor_lang::prelude::*;
+use crate::state::{Observation, ObservationEntry};
+use crate::errors::Errors;
+
+// Advances an observation to a later timestamp with the tick and liquidity in effect since then
+pub fn transform(
+    last: &ObservationEntry,
+    timestamp: i64,
+    tick_current: i32,
+    liquidity: u128,
+) -> Result<ObservationEntry> {
+    let delta = timestamp
+        .checked_sub(last.timestamp)
+        .ok_or(Errors::MathError)?;
+    require!(delta >= 0, Errors::MathError);
+
+    let tick_cumulative = last
+        .tick_cumulative
+        .checked_add((tick_current as i64).checked_mul(delta).ok_or(Errors::MathError)?)
+        .ok_or(Errors::MathError)?;
+    let seconds_per_liquidity_cumulative = last
+        .seconds_per_liquidity_cumulative
+        .wrapping_add(((delta as u128) << 64) / liquidity.max(1));
+
+    Ok(ObservationEntry {
+        timestamp,
+        tick_cumulative,
+        seconds_per_liquidity_cumulative,
+        initialized: true,
+    })
+}
+
+// Records the tick and liquidity in effect before they change, at most once per second
+pub fn write_observation(
+    observation: &AccountLoader<Observation>,
+    timestamp: i64,
+    tick_current: i32,
+    liquidity: u128,
+) -> Result<()> {
+    let mut data = observation.as_ref().try_borrow_mut_data()?;
+    let (observation, observations) = Observation::split_mut(&mut data);
+    record_observation(observation, observations, timestamp, tick_current, liquidity)
+}
+
+// Writes the next slot of the ring buffer, growing into the slots paid for once the buffer wraps
+fn record_observation(
+    observation: &mut Observation,
+    observations: &mut [ObservationEntry],
+    timestamp: i64,
+    tick_current: i32,
+    liquidity: u128,
+) -> Result<()> {
+    let last = observations[observation.observation_index as usize];
+    if { last.timestamp } == timestamp {
+        return Ok(());
+    }
+
+    // Grow into the slots paid for once the newest observation sits in the last slot
+    if observation.cardinality_next > observation.cardinality
+        && observation.observation_index == observation.cardinality - 1
+    {
+        observation.cardinality = observation.cardinality_next;
+    }
+
+    let index = (observation.observation_index + 1) % observation.cardinality;
+    observations[index as usize] = transform(&last, timestamp, tick_current, liquidity)?;
+    observation.observation_index = index;
+    Ok(())
+}
+
+// Finds the observations at or around the target timestamp
+fn get_surrounding_observations(
+    observation: &Observation,
+    observations: &[ObservationEntry],
+    target: i64,
+    tick_current: i32,
+    liquidity: u128,
+) -> Result<(ObservationEntry, ObservationEntry)> {
+    let cardinality = observation.cardinality as usize;
+    let index = observation.observation_index as usize;
+
+    // Target is at or after the newest observation
+    let newest = observations[index];
+    if newest.timestamp <= target {
+        if newest.timestamp == target {
+            return Ok((newest, newest));
+        }
+        return Ok((newest, transform(&newest, target, tick_current, liquidity)?));
+    }
+
+    // Target must not be older than the oldest observation
+    let mut oldest = observations[(index + 1) % cardinality];
+    if !oldest.initialized {
+        oldest = observations[0];
+    }
+    require!(oldest.timestamp <= target, Errors::ObservationTooOld);
+
+    // Binary search over the ring buffer, ordered from oldest to newest
+    let mut left = index + 1;
+    let mut right = index + cardinality;
+    loop {
+        let i = (left + right) / 2;
+        let before = observations[i % cardinality];
+        if !before.initialized {
+            left = i + 1;
+            continue;
+        }
+        let at_or_after = observations[(i + 1) % cardinality];
+
+        if before.timestamp <= target && target <= at_or_after.timestamp {
+            return Ok((before, at_or_after));
+        }
+        if before.timestamp < target {
+            left = i + 1;
+        } else {
+            right = i - 1;
+        }
+    }
+}
+
+// Returns the tick and seconds per liquidity cumulatives as of seconds_ago before timestamp
+pub fn observe_single(
+    observation: &Observation,
+    observations: &[ObservationEntry],
+    timestamp: i64,
+    seconds_ago: u32,
+    tick_current: i32,
+    liquidity: u128,
+) -> Result<(i64, u128)> {
+    let target = timestamp
+        .checked_sub(seconds_ago as i64)
+        .ok_or(Errors::MathError)?;
+
+    let (before, after) = get_surrounding_observations(observation, observations, target, tick_current, liquidity)?;
+    if target == before.timestamp {
+        return Ok((before.tick_cumulative, before.seconds_per_liquidity_cumulative));
+    }
+    if target == after.timestamp {
+        return Ok((after.tick_cumulative, after.seconds_per_liquidity_cumulative));
+    }
+
+    // Interpolate between the surrounding observations
+    let observation_time_delta = after.timestamp - before.timestamp;
+    let target_delta = target - before.timestamp;
+
+    let tick_cumulative = before.tick_cumulative
+        + (after.tick_cumulative - before.tick_cumulative) / observation_time_delta * target_delta;
+    let seconds_per_liquidity_cumulative = before.seconds_per_liquidity_cumulative.wrapping_add(
+        after
+            .seconds_per_liquidity_cumulative
+            .wrapping_sub(before.seconds_per_liquidity_cumulative)
+            .checked_mul(target_delta as u128)
+            .ok_or(Errors::MathError)?
+            / observation_time_delta as u128,
+    );
+
+    Ok((tick_cumulative, seconds_per_liquidity_cumulative))
+}
@@ -0,0 +1,118 @@
+use anchor_lang::prelude::*;
+use anchor_lang::CheckOwner;
+use anchor_spl::token_interface::TokenAccount;
+use crate::state::{Pool, Position, Tick, NUM_REWARDS};
+use crate::errors::Errors;
+
+// Accrues emissions since the last update into the global reward growth, before liquidity or the tick changes
+pub fn update_pool_rewards(pool: &mut Pool, timestamp: i64) -> Result<()> {
+    let elapsed = timestamp
+        .checked_sub(pool.reward_last_updated_timestamp)
+        .ok_or(Errors::MathError)?;
+    if elapsed <= 0 {
+        return Ok(());
+    }
+
+    // Nothing is emitted while no liquidity is in range
+    if pool.liquidity > 0 {
+        for reward_info in pool.reward_infos.iter_mut().filter(|r| r.initialized()) {
+            let growth_delta = reward_info
+                .emissions_per_second_x64
+                .checked_mul(elapsed as u128)
+                .ok_or(Errors::MathError)?
+                .checked_div(pool.liquidity)
+                .ok_or(Errors::DivisionByZero)?;
+            reward_info.growth_global_x64 = reward_info.growth_global_x64.wrapping_add(growth_delta);
+        }
+    }
+    pool.reward_last_updated_timestamp = timestamp;
+
+    Ok(())
+}
+
+pub fn get_reward_growths_global(pool: &Pool) -> [u128; NUM_REWARDS] {
+    pool.reward_infos.map(|reward_info| reward_info.growth_global_x64)
+}
+
+// Initial reward growth outside of a tick, mirrors get_initial_fee_growth_outside
+pub fn get_initial_reward_growths_outside(
+    tick_index: i32,
+    tick_current: i32,
+    reward_growths_global: [u128; NUM_REWARDS],
+) -> [u128; NUM_REWARDS] {
+    if tick_index <= tick_current {
+        reward_growths_global
+    } else {
+        [0; NUM_REWARDS]
+    }
+}
+
+// Calculates the reward growth inside a tick range, mirrors get_fee_growth_inside
+pub fn get_reward_growths_inside(
+    lower_tick: &Tick,
+    upper_tick: &Tick,
+    tick_current: i32,
+    reward_growths_global: [u128; NUM_REWARDS],
+) -> [u128; NUM_REWARDS] {
+    let mut reward_growths_inside = [0; NUM_REWARDS];
+    for (i, reward_growth_inside) in reward_growths_inside.iter_mut().enumerate() {
+        let global = reward_growths_global[i];
+
+        // Reward growth below the lower tick
+        let below = if tick_current >= lower_tick.tick_index {
+            lower_tick.reward_growths_outside[i]
+        } else {
+            global.wrapping_sub(lower_tick.reward_growths_outside[i])
+        };
+
+        // Reward growth above the upper tick
+        let above = if tick_current < upper_tick.tick_index {
+            upper_tick.reward_growths_outside[i]
+        } else {
+            global.wrapping_sub(upper_tick.reward_growths_outside[i])
+        };
+
+        *reward_growth_inside = global.wrapping_sub(below).wrapping_sub(above);
+    }
+    reward_growths_inside
+}
+
+// Credits the rewards accrued since the last checkpoint to the position and moves the checkpoint
+pub fn update_position_rewards(
+    position: &mut Position,
+    reward_growths_inside: [u128; NUM_REWARDS],
+) -> Result<()> {
+    for (reward_info, growth_inside) in position.reward_infos.iter_mut().zip(reward_growths_inside) {
+        let amount = growth_inside
+            .wrapping_sub(reward_info.growth_inside_checkpoint)
+            .checked_mul(position.liquidity)
+            .ok_or(Errors::MathError)?
+            >> 64;
+
+        reward_info.amount_owed = reward_info
+            .amount_owed
+            .checked_add(u64::try_from(amount).map_err(|_| Errors::TokenMaxExceeded)?)
+            .ok_or(Errors::TokenMaxExceeded)?;
+        reward_info.growth_inside_checkpoint = growth_inside;
+    }
+    Ok(())
+}
+
+// A position can only close once its rewards are collected, except for rewards whose vault is empty. The vaults of
+// the rewards still owed are looked up by key in remaining_accounts, a vault that is not passed counts as funded
+pub fn check_rewards_collected(pool: &Pool, position: &Position, remaining_accounts: &[AccountInfo]) -> Result<()> {
+    for (reward_info, position_reward_info) in pool.reward_infos.iter().zip(position.reward_infos.iter()) {
+        if position_reward_info.amount_owed == 0 {
+            continue;
+        }
+        let vault_empty = match remaining_accounts.iter().find(|account| account.key() == reward_info.vault) {
+            Some(vault) => {
+                TokenAccount::check_owner(vault.owner)?;
+                TokenAccount::try_deserialize(&mut &vault.try_borrow_data()?[..])?.amount == 0
+            }
+            None => false,
+        };
+        require!(vault_empty, Errors::RewardsOwed);
+    }
+    Ok(())
+}
@@ -1,38 +1,28 @@
 use anchor_lang::prelude::*;
-use crate::state::Tick;
+use crate::state::{Tick, NUM_REWARDS};
 use crate::errors::Errors;
+use crate::math::fee_math::{get_fee_growth_inside, get_initial_fee_growth_outside};
+use crate::math::reward_math::{get_initial_reward_growths_outside, get_reward_growths_inside};
+
+// Price and tick conversions come from clmm-math, these map its errors to program errors
 
 // Calculates the tick that corresponds to a given sqrt price
 pub fn get_tick_from_sqrt_price(sqrt_price_x64: u128) -> Result<i32> {
-    let sqrt_price = sqrt_price_x64 as f64 / (2u64.pow(64) as f64);
-    let log_base = 1.0001_f64.ln();
-    let tick = 2.0 * sqrt_price.ln() / log_base;
-
-    Ok(tick.floor() as i32)
+    Ok(clmm_math::get_tick_from_sqrt_price(sqrt_price_x64).map_err(Errors::from)?)
 }
 
 // Calculates the sqrt price from a tick
 pub fn get_sqrt_price_from_tick(tick: i32) -> Result<u128> {
-    let sqrt_price_x64 =  1.0001_f64.powf(tick as f64 / 2.0) * (2u64.pow(64) as f64);
-    Ok(sqrt_price_x64 as u128)
+    Ok(clmm_math::get_sqrt_price_from_tick(tick).map_err(Errors::from)?)
 }
 
 // Calculates the next tick from a tick
 pub fn get_next_tick(
     tick_current: i32,
-    initialized_ticks: &Vec<i32>,
+    initialized_ticks: &[i32],
     a_to_b: bool
 ) -> Result<i32> {
-    let next_tick = if a_to_b {
-        initialized_ticks.iter().filter(|&&x| x < tick_current).max().copied()
-    } else {
-        initialized_ticks.iter().filter(|&&x| x > tick_current).min().copied()
-    };
-
-    match next_tick {
-        Some(tick) => Ok(tick),
-        None => Err(Errors::TickNotFound.into())
-    }
+    Ok(clmm_math::get_next_tick(tick_current, initialized_ticks, a_to_b).map_err(Errors::from)?)
 }
 
 // Updates the tick account in terms of fees
@@ -40,14 +30,136 @@ pub fn cross_tick(
     tick_account: &mut Tick,
     fee_growth_global_a: u128,
     fee_growth_global_b: u128,
-    a_to_b: bool
+    reward_growths_global: [u128; NUM_REWARDS],
 ) -> Result<()> {
+    // Fee growth outside flips to the other side of the tick for both tokens
+    tick_account.fee_growth_outside_a = fee_growth_global_a.wrapping_sub(tick_account.fee_growth_outside_a);
+    tick_account.fee_growth_outside_b = fee_growth_global_b.wrapping_sub(tick_account.fee_growth_outside_b);
+    // Same for every reward
+    for (reward_growth_outside, reward_growth_global) in tick_account.reward_growths_outside.iter_mut().zip(reward_growths_global) {
+        *reward_growth_outside = reward_growth_global.wrapping_sub(*reward_growth_outside);
+    }
+    Ok(())
+}
+
+// Initializes a tick the first time a position references it
+pub fn initialize_tick(
+    tick_account: &mut Tick,
+    tick_index: i32,
+    bump: u8,
+    tick_current: i32,
+    fee_growth_global_a: u128,
+    fee_growth_global_b: u128,
+    reward_growths_global: [u128; NUM_REWARDS],
+) {
+    let (fee_growth_outside_a, fee_growth_outside_b) = get_initial_fee_growth_outside(
+        tick_index,
+        tick_current,
+        fee_growth_global_a,
+        fee_growth_global_b,
+    );
+    tick_account.tick_index = tick_index;
+    tick_account.liquidity_net = 0;
+    tick_account.fee_growth_outside_a = fee_growth_outside_a;
+    tick_account.fee_growth_outside_b = fee_growth_outside_b;
+    tick_account.initialized = true;
+    tick_account.bump = bump;
+    tick_account.range_order_liquidity_up = 0;
+    tick_account.range_order_liquidity_down = 0;
+    tick_account.range_order_fills_up = 0;
+    tick_account.range_order_fills_down = 0;
+    tick_account.range_order_fee_growth_up_a = 0;
+    tick_account.range_order_fee_growth_up_b = 0;
+    tick_account.range_order_fee_growth_down_a = 0;
+    tick_account.range_order_fee_growth_down_b = 0;
+    tick_account.reward_growths_outside = get_initial_reward_growths_outside(
+        tick_index,
+        tick_current,
+        reward_growths_global,
+    );
+}
+
+// Updates liquidity_net of both range ticks, liquidity enters at the lower tick and leaves at the upper tick
+pub fn update_tick_liquidity(
+    lower_tick: &mut Tick,
+    upper_tick: &mut Tick,
+    liquidity_delta: i128,
+) -> Result<()> {
+    lower_tick.liquidity_net = lower_tick
+        .liquidity_net
+        .checked_add(liquidity_delta)
+        .ok_or(Errors::MultiplicationOverflow)?;
+    upper_tick.liquidity_net = upper_tick
+        .liquidity_net
+        .checked_sub(liquidity_delta)
+        .ok_or(Errors::MultiplicationOverflow)?;
+    Ok(())
+}
+
+// Settles the range orders filled by crossing the tick, their liquidity leaves both range ticks for good
+// so it never converts back. The partner tick is the other edge of the filled range. The tick has already been
+// crossed, and records the fee and reward growth inside the range the orders earned up to the fill.
+pub fn fill_range_orders(
+    tick_account: &mut Tick,
+    partner_tick_account: &mut Tick,
+    a_to_b: bool,
+    fee_growth_global_a: u128,
+    fee_growth_global_b: u128,
+    reward_growths_global: [u128; NUM_REWARDS],
+) -> Result<()> {
+    let filled_liquidity = if a_to_b {
+        tick_account.range_order_liquidity_down
+    } else {
+        tick_account.range_order_liquidity_up
+    };
+    let filled_liquidity = i128::try_from(filled_liquidity).map_err(|_| Errors::MathError)?;
+
     if a_to_b {
-        // Update fee growth global
-        tick_account.fee_growth_outside_a = fee_growth_global_a.checked_sub(tick_account.fee_growth_outside_a).ok_or(Errors::MathError)?;
+        // Orders in [tick, tick + spacing], the tick is their lower edge and the price is now below it
+        let (fee_growth_inside_a, fee_growth_inside_b) = get_fee_growth_inside(
+            tick_account,
+            partner_tick_account,
+            tick_account.tick_index - 1,
+            fee_growth_global_a,
+            fee_growth_global_b,
+        );
+        tick_account.range_order_fee_growth_down_a = fee_growth_inside_a;
+        tick_account.range_order_fee_growth_down_b = fee_growth_inside_b;
+        tick_account.range_order_reward_growths_down = get_reward_growths_inside(
+            tick_account,
+            partner_tick_account,
+            tick_account.tick_index - 1,
+            reward_growths_global,
+        );
+        update_tick_liquidity(tick_account, partner_tick_account, -filled_liquidity)?;
+        tick_account.range_order_liquidity_down = 0;
+        tick_account.range_order_fills_down = tick_account
+            .range_order_fills_down
+            .checked_add(1)
+            .ok_or(Errors::MathError)?;
     } else {
-        // Update fee growth global
-        tick_account.fee_growth_outside_b = fee_growth_global_b.checked_sub(tick_account.fee_growth_outside_b).ok_or(Errors::MathError)?;
+        // Orders in [tick - spacing, tick], the tick is their upper edge and the price is now at or above it
+        let (fee_growth_inside_a, fee_growth_inside_b) = get_fee_growth_inside(
+            partner_tick_account,
+            tick_account,
+            tick_account.tick_index,
+            fee_growth_global_a,
+            fee_growth_global_b,
+        );
+        tick_account.range_order_fee_growth_up_a = fee_growth_inside_a;
+        tick_account.range_order_fee_growth_up_b = fee_growth_inside_b;
+        tick_account.range_order_reward_growths_up = get_reward_growths_inside(
+            partner_tick_account,
+            tick_account,
+            tick_account.tick_index,
+            reward_growths_global,
+        );
+        update_tick_liquidity(partner_tick_account, tick_account, -filled_liquidity)?;
+        tick_account.range_order_liquidity_up = 0;
+        tick_account.range_order_fills_up = tick_account
+            .range_order_fills_up
+            .checked_add(1)
+            .ok_or(Errors::MathError)?;
     }
     Ok(())
-}
+}
\ No newline at end of file
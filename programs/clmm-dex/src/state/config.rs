@@ -0,0 +1,45 @@
+use anchor_lang::prelude::*;
+use crate::state::Pool;
+use crate::errors::Errors;
+
+// Operations that can be frozen per pool
+pub const PAUSE_SWAPS: u8 = 1 << 0;
+pub const PAUSE_DEPOSITS: u8 = 1 << 1;
+pub const PAUSE_WITHDRAWALS: u8 = 1 << 2;
+pub const PAUSE_FEE_COLLECTION: u8 = 1 << 3;
+pub const PAUSE_FLASH_LOANS: u8 = 1 << 4;
+
+// Most transfer hook programs the allowlist can hold
+pub const MAX_TRANSFER_HOOK_PROGRAMS: usize = 8;
+
+// space = 8 + 32 + 32 + 1 + 1 + 8 + 4 + MAX_TRANSFER_HOOK_PROGRAMS * 32;
+#[account]
+pub struct Config {
+    pub admin: Pubkey, // Program upgrade authority at initialization, manages the config
+    pub pause_authority: Pubkey, // May freeze pools and flip the global kill switch
+    pub global_paused: bool, // Kill switch, freezes every operation except withdrawals
+    pub bump: u8, // Bump for PDA derivation
+    pub fee_change_delay: i64, // Seconds between proposing a fee change and it taking effect
+    pub transfer_hook_programs: Vec<Pubkey>, // Transfer hook programs a pool mint may point to
+}
+
+impl Config {
+    // Checked first by every handler that moves funds or liquidity, which also keeps them out of a locked pool
+    pub fn check_not_paused(&self, pool: &Pool, operation: u8) -> Result<()> {
+        require!(!pool.locked, Errors::PoolLocked);
+        // Users can always exit unless withdrawals were frozen explicitly on the pool
+        if self.global_paused && operation != PAUSE_WITHDRAWALS {
+            return Err(Errors::OperationPaused.into());
+        }
+        require!(pool.pause_flags & operation == 0, Errors::OperationPaused);
+        Ok(())
+    }
+
+    // Mints without a transfer hook are always accepted
+    pub fn is_transfer_hook_allowed(&self, hook_program: Option<Pubkey>) -> bool {
+        match hook_program {
+            Some(program) => self.transfer_hook_programs.contains(&program),
+            None => true,
+        }
+    }
+}
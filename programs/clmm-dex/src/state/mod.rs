@@ -1,7 +1,15 @@
+pub mod config;
+pub mod observation;
 pub mod pool;
 pub mod position;
+pub mod position_bundle;
+pub mod range_order_fill;
 pub mod tick;
 
+pub use config::*;
+pub use observation::*;
 pub use pool::*;
 pub use position::*;
-pub use tick::*;
\ No newline at end of file
+pub use position_bundle::*;
+pub use range_order_fill::*;
+pub use tick::*;
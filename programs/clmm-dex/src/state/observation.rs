@@ -0,0 +1,54 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::MAX_PERMITTED_DATA_INCREASE;
+use std::mem::size_of;
+
+#[zero_copy(unsafe)]
+#[repr(C, packed)]
+#[derive(Default)]
+pub struct ObservationEntry {
+    pub timestamp: i64, // Unix timestamp of the observation
+    pub tick_cumulative: i64, // Tick multiplied by seconds elapsed, summed since pool creation
+    pub seconds_per_liquidity_cumulative: u128, // Seconds divided by in-range liquidity in Q64.64
+    pub initialized: bool, // Whether the slot holds an observation
+}
+
+// Slots are read in place like the header, which zero_copy(unsafe) marks the same way. Packed without padding, and
+// slots only ever hold zeroes or entries the program wrote
+unsafe impl bytemuck::Pod for ObservationEntry {}
+unsafe impl bytemuck::Zeroable for ObservationEntry {}
+
+// Zero copy header, followed in the account data by the cardinality_next slots of the ring buffer so the account can
+// grow. A write touches a single slot instead of serializing the whole buffer. Packed for the same layout on chain
+// and off.
+// space = Observation::space(cardinality_next) = 8 + 32 + 2 + 2 + 2 + 1 + cardinality_next * (8 + 8 + 16 + 1);
+#[account(zero_copy(unsafe))]
+#[repr(C, packed)]
+pub struct Observation {
+    pub pool: Pubkey, // Pool the observations belong to
+    pub observation_index: u16, // Slot of the most recent observation
+    pub cardinality: u16, // Number of slots in use by the ring buffer
+    pub cardinality_next: u16, // Number of slots paid for, the buffer grows into them on a later write
+    pub bump: u8, // Bump for PDA derivation
+}
+
+impl Observation {
+    pub const DEFAULT_CARDINALITY: u16 = 64;
+    // Most slots one increase_observation_cardinality can add, an account grows by at most 10 KiB per instruction
+    pub const MAX_CARDINALITY_INCREASE: u16 = (MAX_PERMITTED_DATA_INCREASE / size_of::<ObservationEntry>()) as u16;
+
+    pub fn space(cardinality: u16) -> usize {
+        8 + size_of::<Observation>() + cardinality as usize * size_of::<ObservationEntry>()
+    }
+
+    // Header and slots of an observation account's data, discriminator included. The data is always sized by
+    // Observation::space, so it holds a whole number of slots
+    pub fn split(data: &[u8]) -> (&Observation, &[ObservationEntry]) {
+        let (header, slots) = data[8..].split_at(size_of::<Observation>());
+        (bytemuck::from_bytes(header), bytemuck::cast_slice(slots))
+    }
+
+    pub fn split_mut(data: &mut [u8]) -> (&mut Observation, &mut [ObservationEntry]) {
+        let (header, slots) = data[8..].split_at_mut(size_of::<Observation>());
+        (bytemuck::from_bytes_mut(header), bytemuck::cast_slice_mut(slots))
+    }
+}
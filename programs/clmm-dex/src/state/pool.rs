@@ -1,6 +1,9 @@
 use anchor_lang::prelude::*;
-    // space = 8 + 32 + 32 + 32 + 32 + 32 + 16 + 4 + 16 + 2 + 1 + 16 + 16 + 16 + 16 + 1;
+use crate::errors::Errors;
+use crate::math::tick_math::get_tick_from_sqrt_price;
+    // space = 8 + Pool::INIT_SPACE, sized for MAX_INITIALIZED_TICKS ticks
     #[account]
+    #[derive(InitSpace, Default)]
     pub struct Pool{
     pub pool_authority: Pubkey,
     pub token_a_mint: Pubkey,
@@ -11,12 +14,118 @@ use anchor_lang::prelude::*;
     pub tick_current: i32,
     pub liquidity: u128,
     pub tick_spacing: u16,
-    pub initialized_ticks: Vec<i32>,
+    #[max_len(MAX_INITIALIZED_TICKS)]
+    pub initialized_ticks: Vec<i32>, // Sorted, bounded so the pool account never outgrows its space
     pub fee: u8,
     pub fee_growth_global_a: u128,
     pub fee_growth_global_b: u128,
     pub protocol_fee_a: u128,
     pub protocol_fee_b: u128,
     pub bump: u8,
+    pub reward_last_updated_timestamp: i64,
+    pub reward_infos: [RewardInfo; NUM_REWARDS],
+    pub dynamic_fee: DynamicFee,
+    pub pause_flags: u8,
+    pub pending_fee: u8, // Fee proposed by the pool authority, active from pending_fee_activation
+    pub pending_fee_activation: i64, // Zero while no fee change is pending
+    pub flash_loan_amount_a: u64, // Principal lent out by a flash loan that is not repaid yet
+    pub flash_loan_amount_b: u64,
+    pub flash_loan_fee_a: u64, // Fee owed on top of the principal, credited to LPs on repayment
+    pub flash_loan_fee_b: u64,
+    pub locked: bool, // Set while a flash swap callback runs, blocks reentry into the pool
+    pub pending_dynamic_fee: DynamicFee, // Parameters proposed by set_dynamic_fee, the volatility state is unused
+    pub pending_dynamic_fee_activation: i64, // Zero while no dynamic fee change is pending
 }
 
+pub const NUM_REWARDS: usize = 3;
+
+// Most initialized ticks a pool tracks, the pool account is allocated for all of them up front
+pub const MAX_INITIALIZED_TICKS: usize = 1024;
+
+impl Pool {
+    // State of a pool fresh out of initialize_pool at sqrt_price, the simulator starts from it too. Mints, vaults,
+    // authority and bump are left to the caller
+    pub fn initialize(&mut self, sqrt_price: u128, tick_spacing: u16, fee: u8, timestamp: i64) -> Result<()> {
+        self.sqrt_price = sqrt_price;
+        self.tick_current = get_tick_from_sqrt_price(sqrt_price)?;
+        self.liquidity = 0;
+        self.tick_spacing = tick_spacing;
+        // Only ticks with an account are listed, the starting price has none for a swap to cross
+        self.initialized_ticks = Vec::new();
+        self.fee = fee;
+        self.fee_growth_global_a = 0;
+        self.fee_growth_global_b = 0;
+        self.protocol_fee_a = 0;
+        self.protocol_fee_b = 0;
+        self.reward_last_updated_timestamp = timestamp;
+        self.reward_infos = [RewardInfo::default(); NUM_REWARDS];
+        self.dynamic_fee = DynamicFee::default();
+        self.pause_flags = 0;
+        self.pending_fee = 0;
+        self.pending_fee_activation = 0;
+        self.flash_loan_amount_a = 0;
+        self.flash_loan_amount_b = 0;
+        self.flash_loan_fee_a = 0;
+        self.flash_loan_fee_b = 0;
+        self.locked = false;
+        self.pending_dynamic_fee = DynamicFee::default();
+        self.pending_dynamic_fee_activation = 0;
+        Ok(())
+    }
+
+    // Adds a tick the first time a position uses it, keeping the list sorted
+    pub fn insert_initialized_tick(&mut self, tick_index: i32) -> Result<()> {
+        if let Err(position) = self.initialized_ticks.binary_search(&tick_index) {
+            require!(self.initialized_ticks.len() < MAX_INITIALIZED_TICKS, Errors::TooManyTicks);
+            self.initialized_ticks.insert(position, tick_index);
+        }
+        Ok(())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct RewardInfo {
+    pub mint: Pubkey, // Reward token mint, default while the slot is unused
+    pub vault: Pubkey, // Vault the rewards are paid from
+    pub emissions_per_second_x64: u128, // Reward tokens emitted per second in Q64.64
+    pub growth_global_x64: u128, // Reward growth per unit of liquidity in Q64.64
+}
+
+impl RewardInfo {
+    pub fn initialized(&self) -> bool {
+        self.mint != Pubkey::default()
+    }
+}
+
+// Fees are in millionths like Pool.fee, the accumulator counts tick spacings moved scaled by VOLATILITY_SCALE
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct DynamicFee {
+    pub enabled: bool,
+    pub filter_period: u32, // Seconds during which consecutive swaps keep accumulating volatility
+    pub decay_period: u32, // Seconds after which the volatility reference resets to zero
+    pub reduction_factor: u16, // Share of the accumulator kept as reference after the filter period, in basis points
+    pub variable_fee_control: u32, // Variable fee per squared tick spacing of volatility
+    pub max_volatility_accumulator: u32, // Cap on the volatility accumulator
+    pub max_fee: u32, // Cap on base plus variable fee
+    pub volatility_accumulator: u32,
+    pub volatility_reference: u32,
+    pub tick_reference: i32,
+    pub last_update_timestamp: i64,
+}
+
+impl DynamicFee {
+    pub const VOLATILITY_SCALE: u32 = 10_000;
+
+    // Parameters have to be consistent with each other and with the base fee while enabled
+    pub fn validate(&self, fee: u8) -> Result<()> {
+        if self.enabled {
+            require!(self.filter_period < self.decay_period, Errors::InvalidFeeRate);
+            require!(self.reduction_factor <= 10_000, Errors::InvalidFeeRate);
+            require!(
+                self.max_fee >= fee as u32 && self.max_fee < 1_000_000,
+                Errors::InvalidFeeRate
+            );
+        }
+        Ok(())
+    }
+}
@@ -1,6 +1,9 @@
 use anchor_lang::prelude::*;
+use crate::state::{Tick, NUM_REWARDS};
 
+// space = 8 + Position::INIT_SPACE
 #[account]
+#[derive(InitSpace)]
 pub struct Position{
     pub owner: Pubkey, // Owner of the position
     pub tick_lower: i32, // Lower tick of the position
@@ -10,5 +13,45 @@ pub struct Position{
     pub fee_growth_inside_b: u128, // Fee growth inside for token B
     pub token_a_earned: u64, // Amount of token A earned by the position
     pub token_b_earned: u64, // Amount of token B earned by the position
+    pub bundle_index: u16, // Slot of the position in the owner's position bundle
+    pub delegate: Option<Pubkey>, // Operator allowed to collect fees and rebalance
+    pub range_order: bool, // Single spacing position that settles once price crosses it
+    pub range_order_sells_a: bool, // Range order placed above the price, converting A into B
+    pub range_order_fills: u64, // Fill count of the fill tick when the order was placed
+    pub reward_infos: [PositionRewardInfo; NUM_REWARDS], // Reward checkpoints and amounts owed
     pub bump: u8, // Bump for PDA derivation
-}
\ No newline at end of file
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct PositionRewardInfo {
+    pub growth_inside_checkpoint: u128, // Reward growth inside the range at the last update
+    pub amount_owed: u64, // Amount of reward earned by the position
+}
+
+impl Position {
+    // The owner and the delegate may manage the position, only the owner may withdraw principal
+    pub fn is_authorized(&self, signer: &Pubkey) -> bool {
+        self.owner == *signer || self.delegate == Some(*signer)
+    }
+
+    // A range order is filled once its fill tick was crossed after the order was placed
+    pub fn is_range_order_filled(&self, lower_tick: &Tick, upper_tick: &Tick) -> bool {
+        if !self.range_order {
+            return false;
+        }
+        if self.range_order_sells_a {
+            upper_tick.range_order_fills_up > self.range_order_fills
+        } else {
+            lower_tick.range_order_fills_down > self.range_order_fills
+        }
+    }
+
+    // Tick whose crossing fills the range order, the upper one for orders selling A
+    pub fn range_order_fill_tick(&self) -> i32 {
+        if self.range_order_sells_a {
+            self.tick_upper
+        } else {
+            self.tick_lower
+        }
+    }
+}
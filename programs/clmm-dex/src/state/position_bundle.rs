@@ -0,0 +1,44 @@
+use anchor_lang::prelude::*;
+use crate::errors::Errors;
+
+// space = 8 + 32 + 32 + 32 + 1;
+#[account]
+pub struct PositionBundle {
+    pub pool: Pubkey, // Pool the bundled positions belong to
+    pub owner: Pubkey, // Owner of the bundle
+    pub position_bitmap: [u8; 32], // One bit per slot, set while a position occupies it
+    pub bump: u8, // Bump for PDA derivation
+}
+
+impl PositionBundle {
+    pub const MAX_POSITIONS: u16 = 256;
+
+    pub fn is_slot_occupied(&self, index: u16) -> bool {
+        if index >= Self::MAX_POSITIONS {
+            return false;
+        }
+        self.position_bitmap[(index / 8) as usize] & (1 << (index % 8)) != 0
+    }
+
+    // Marks a slot as taken by a newly opened position
+    pub fn occupy_slot(&mut self, index: u16) -> Result<()> {
+        require!(index < Self::MAX_POSITIONS, Errors::InvalidPositionSlot);
+        require!(!self.is_slot_occupied(index), Errors::PositionSlotOccupied);
+        self.position_bitmap[(index / 8) as usize] |= 1 << (index % 8);
+        Ok(())
+    }
+
+    // Releases a slot once its position is closed
+    pub fn free_slot(&mut self, index: u16) -> Result<()> {
+        require!(self.is_slot_occupied(index), Errors::InvalidPositionSlot);
+        self.position_bitmap[(index / 8) as usize] &= !(1 << (index % 8));
+        Ok(())
+    }
+
+    // Lists the occupied slots, so clients can derive every position PDA of the owner
+    pub fn occupied_slots(&self) -> Vec<u16> {
+        (0..Self::MAX_POSITIONS)
+            .filter(|&index| self.is_slot_occupied(index))
+            .collect()
+    }
+}
@@ -0,0 +1,55 @@
+use anchor_lang::prelude::*;
+use crate::errors::Errors;
+use crate::state::{Tick, NUM_REWARDS};
+
+// Range orders on one side of a fill tick that were placed between two of its fills, keyed by the tick's fill count
+// when they were placed. The growth inside their range at the fill that settles them is archived here, so orders
+// still get what they earned up to their fill after the tick filled again.
+// space = 8 + RangeOrderFill::INIT_SPACE
+#[account]
+#[derive(InitSpace)]
+pub struct RangeOrderFill {
+    pub pool: Pubkey, // Pool of the orders
+    pub tick_index: i32, // Fill tick of the orders
+    pub sells_a: bool, // Orders selling A, filled when price crosses the tick upward
+    pub fills: u64, // Fill count of the tick when the orders were placed
+    pub liquidity: u128, // Liquidity of the orders not claimed yet, the record closes once it is all claimed
+    pub archived: bool, // The growth at the fill below is set
+    pub fee_growth_inside_a: u128, // Fee growth inside the orders' range when they filled
+    pub fee_growth_inside_b: u128,
+    pub reward_growths_inside: [u128; NUM_REWARDS], // Reward growth inside the orders' range when they filled
+    pub bump: u8, // Bump for PDA derivation
+}
+
+impl RangeOrderFill {
+    // Copies the growth the fill tick kept from its last fill once the orders filled. The tick cannot fill again
+    // before an order placed after that fill archives it, so an unarchived record always finds it on the tick.
+    pub fn archive(&mut self, fill_tick: &Tick) -> Result<()> {
+        if self.archived {
+            return Ok(());
+        }
+        let (tick_fills, fee_growth_inside_a, fee_growth_inside_b, reward_growths_inside) = if self.sells_a {
+            (
+                fill_tick.range_order_fills_up,
+                fill_tick.range_order_fee_growth_up_a,
+                fill_tick.range_order_fee_growth_up_b,
+                fill_tick.range_order_reward_growths_up,
+            )
+        } else {
+            (
+                fill_tick.range_order_fills_down,
+                fill_tick.range_order_fee_growth_down_a,
+                fill_tick.range_order_fee_growth_down_b,
+                fill_tick.range_order_reward_growths_down,
+            )
+        };
+        if tick_fills == self.fills + 1 {
+            self.fee_growth_inside_a = fee_growth_inside_a;
+            self.fee_growth_inside_b = fee_growth_inside_b;
+            self.reward_growths_inside = reward_growths_inside;
+            self.archived = true;
+        }
+        require!(self.archived, Errors::InvalidRangeOrderFill);
+        Ok(())
+    }
+}
@@ -1,7 +1,9 @@
 use anchor_lang::prelude::*;
+use crate::state::NUM_REWARDS;
 
-// space = 8 + 4 + 16 + 16 + 16 + 1 + 1;
+// space = 8 + Tick::INIT_SPACE
 #[account]
+#[derive(InitSpace)]
 pub struct Tick {
     pub tick_index: i32,
     pub liquidity_net: i128,
@@ -9,4 +11,19 @@ pub struct Tick {
     pub fee_growth_outside_b: u128,
     pub initialized: bool,
     pub bump: u8,
+    // Open range orders selling A in [tick - spacing, tick], filled when price crosses the tick upward
+    pub range_order_liquidity_up: u128,
+    // Open range orders selling B in [tick, tick + spacing], filled when price crosses the tick downward
+    pub range_order_liquidity_down: u128,
+    pub range_order_fills_up: u64, // Number of times the orders below were filled
+    pub range_order_fills_down: u64, // Number of times the orders above were filled
+    // Fee and reward growth inside the filled range at the last fill, what the orders filled then earned. Kept
+    // until the next order on the same side archives it into the RangeOrderFill record of the filled orders
+    pub range_order_fee_growth_up_a: u128,
+    pub range_order_fee_growth_up_b: u128,
+    pub range_order_fee_growth_down_a: u128,
+    pub range_order_fee_growth_down_b: u128,
+    pub range_order_reward_growths_up: [u128; NUM_REWARDS],
+    pub range_order_reward_growths_down: [u128; NUM_REWARDS],
+    pub reward_growths_outside: [u128; NUM_REWARDS],
 }
@@ -0,0 +1,5 @@
+pub mod native;
+pub mod token;
+
+pub use native::*;
+pub use token::*;
@@ -0,0 +1,165 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_lang::system_program::{self, Allocate, Assign, CreateAccount, Transfer};
+use anchor_spl::token::spl_token;
+use anchor_spl::token_2022::spl_token_2022;
+use anchor_spl::token_interface::{
+    self, CloseAccount, InitializeAccount3, Mint, SyncNative, TokenAccount, TokenInterface,
+};
+
+use crate::errors::Errors;
+
+// Wrapped SOL mints of SPL Token and Token-2022
+pub fn is_native_mint(mint: &Pubkey) -> bool {
+    *mint == spl_token::native_mint::ID || *mint == spl_token_2022::native_mint::ID
+}
+
+// Returns the user's token account for a side, or a temporary wSOL account holding `lamports` when none was passed
+// The flag is set when the caller has to unwrap the temporary account once its transfers are done
+#[allow(clippy::too_many_arguments)]
+pub fn get_user_token_account<'info>(
+    user_token_account: &Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    user: &Signer<'info>,
+    temp_wsol_account: &Option<UncheckedAccount<'info>>,
+    temp_wsol_bump: Option<u8>,
+    mint: &InterfaceAccount<'info, Mint>,
+    token_program: &Interface<'info, TokenInterface>,
+    system_program: &Program<'info, System>,
+    lamports: u64,
+) -> Result<(AccountInfo<'info>, bool)> {
+    match user_token_account {
+        Some(account) => Ok((account.to_account_info(), false)),
+        None => {
+            let account = wrap_native_sol(
+                user,
+                temp_wsol_account,
+                temp_wsol_bump,
+                mint,
+                token_program,
+                system_program,
+                lamports,
+            )?;
+            Ok((account, true))
+        }
+    }
+}
+
+// Creates the temporary wSOL account at [b"wsol", user] owned by the user and wraps `lamports` into it
+pub fn wrap_native_sol<'info>(
+    user: &Signer<'info>,
+    temp_wsol_account: &Option<UncheckedAccount<'info>>,
+    temp_wsol_bump: Option<u8>,
+    mint: &InterfaceAccount<'info, Mint>,
+    token_program: &Interface<'info, TokenInterface>,
+    system_program: &Program<'info, System>,
+    lamports: u64,
+) -> Result<AccountInfo<'info>> {
+    let (temp_wsol_account, bump) = match (temp_wsol_account, temp_wsol_bump) {
+        (Some(account), Some(bump)) if is_native_mint(&mint.key()) => (account, bump),
+        _ => return Err(Errors::NativeSolNotSupported.into()),
+    };
+
+    let user_key = user.key();
+    let seeds = &[b"wsol", user_key.as_ref(), &[bump]];
+    let signer = &[&seeds[..]];
+
+    // Anyone can send lamports to the PDA ahead of time, which would make create_account fail,
+    // so a prefunded account is topped up to rent exemption and then allocated and assigned instead
+    let space = spl_token::state::Account::LEN;
+    let rent = Rent::get()?.minimum_balance(space);
+    let current_lamports = temp_wsol_account.lamports();
+    if current_lamports == 0 {
+        system_program::create_account(
+            CpiContext::new_with_signer(
+                system_program.to_account_info(),
+                CreateAccount {
+                    from: user.to_account_info(),
+                    to: temp_wsol_account.to_account_info(),
+                },
+                signer,
+            ),
+            rent,
+            space as u64,
+            token_program.key,
+        )?;
+    } else {
+        let required_lamports = rent.saturating_sub(current_lamports);
+        if required_lamports > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    system_program.to_account_info(),
+                    Transfer {
+                        from: user.to_account_info(),
+                        to: temp_wsol_account.to_account_info(),
+                    },
+                ),
+                required_lamports,
+            )?;
+        }
+        system_program::allocate(
+            CpiContext::new_with_signer(
+                system_program.to_account_info(),
+                Allocate {
+                    account_to_allocate: temp_wsol_account.to_account_info(),
+                },
+                signer,
+            ),
+            space as u64,
+        )?;
+        system_program::assign(
+            CpiContext::new_with_signer(
+                system_program.to_account_info(),
+                Assign {
+                    account_to_assign: temp_wsol_account.to_account_info(),
+                },
+                signer,
+            ),
+            token_program.key,
+        )?;
+    }
+    token_interface::initialize_account3(CpiContext::new(
+        token_program.to_account_info(),
+        InitializeAccount3 {
+            account: temp_wsol_account.to_account_info(),
+            mint: mint.to_account_info(),
+            authority: user.to_account_info(),
+        },
+    ))?;
+
+    if lamports > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.to_account_info(),
+                Transfer {
+                    from: user.to_account_info(),
+                    to: temp_wsol_account.to_account_info(),
+                },
+            ),
+            lamports,
+        )?;
+        token_interface::sync_native(CpiContext::new(
+            token_program.to_account_info(),
+            SyncNative {
+                account: temp_wsol_account.to_account_info(),
+            },
+        ))?;
+    }
+
+    Ok(temp_wsol_account.to_account_info())
+}
+
+// Closes the temporary wSOL account, returning its rent and any wrapped SOL left in it to the user as lamports
+pub fn unwrap_native_sol<'info>(
+    user: &Signer<'info>,
+    temp_wsol_account: &AccountInfo<'info>,
+    token_program: &Interface<'info, TokenInterface>,
+) -> Result<()> {
+    token_interface::close_account(CpiContext::new(
+        token_program.to_account_info(),
+        CloseAccount {
+            account: temp_wsol_account.clone(),
+            destination: user.to_account_info(),
+            authority: user.to_account_info(),
+        },
+    ))
+}
@@ -0,0 +1,118 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_2022::spl_token_2022::extension::transfer_fee::TransferFeeConfig;
+use anchor_spl::token_2022::spl_token_2022::extension::{transfer_hook, BaseStateWithExtensions, StateWithExtensions};
+use anchor_spl::token_2022::spl_token_2022::onchain;
+use anchor_spl::token_2022::spl_token_2022::state::Mint as MintState;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::errors::Errors;
+use crate::state::Pool;
+
+// Transfers tokens from a user's account into one of the pool vaults
+// Extra accounts required by a transfer hook are looked up by key in hook_accounts
+pub fn transfer_from_user<'info>(
+    authority: &Signer<'info>,
+    from: AccountInfo<'info>,
+    vault: &InterfaceAccount<'info, TokenAccount>,
+    mint: &InterfaceAccount<'info, Mint>,
+    token_program: &Interface<'info, TokenInterface>,
+    hook_accounts: &[AccountInfo<'info>],
+    amount: u64,
+) -> Result<()> {
+    onchain::invoke_transfer_checked(
+        token_program.key,
+        from,
+        mint.to_account_info(),
+        vault.to_account_info(),
+        authority.to_account_info(),
+        hook_accounts,
+        amount,
+        mint.decimals,
+        &[],
+    )
+    .map_err(Into::into)
+}
+
+// Transfers tokens out of a vault owned by the pool, signing with the pool seeds
+pub fn transfer_from_vault<'info>(
+    pool: &Account<'info, Pool>,
+    vault: &InterfaceAccount<'info, TokenAccount>,
+    to: AccountInfo<'info>,
+    mint: &InterfaceAccount<'info, Mint>,
+    token_program: &Interface<'info, TokenInterface>,
+    hook_accounts: &[AccountInfo<'info>],
+    amount: u64,
+) -> Result<()> {
+    let seeds = &[
+        b"pool",
+        pool.token_a_mint.as_ref(),
+        pool.token_b_mint.as_ref(),
+        &[pool.bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    onchain::invoke_transfer_checked(
+        token_program.key,
+        vault.to_account_info(),
+        mint.to_account_info(),
+        to,
+        pool.to_account_info(),
+        hook_accounts,
+        amount,
+        mint.decimals,
+        signer,
+    )
+    .map_err(Into::into)
+}
+
+// Program the mint's transfer hook extension points to, if any
+pub fn get_transfer_hook_program_id(mint: &InterfaceAccount<Mint>) -> Option<Pubkey> {
+    let mint_info = mint.to_account_info();
+    if *mint_info.owner == anchor_spl::token::ID {
+        return None;
+    }
+    let mint_data = mint_info.try_borrow_data().ok()?;
+    let mint_state = StateWithExtensions::<MintState>::unpack(&mint_data).ok()?;
+    transfer_hook::get_program_id(&mint_state)
+}
+
+// Amount that reaches the destination when pre_fee_amount is sent, after the mint's transfer fee for the current epoch
+pub fn get_transfer_fee_excluded_amount(mint: &InterfaceAccount<Mint>, pre_fee_amount: u64) -> Result<u64> {
+    let fee = match get_transfer_fee_config(mint)? {
+        Some(config) => config
+            .calculate_epoch_fee(Clock::get()?.epoch, pre_fee_amount)
+            .ok_or(Errors::TransferFeeCalculationError)?,
+        None => 0,
+    };
+    pre_fee_amount
+        .checked_sub(fee)
+        .ok_or(Errors::TransferFeeCalculationError.into())
+}
+
+// Amount that has to be sent so that post_fee_amount reaches the destination
+pub fn get_transfer_fee_included_amount(mint: &InterfaceAccount<Mint>, post_fee_amount: u64) -> Result<u64> {
+    // A zero transfer is never charged, the inverse fee would otherwise round it up to one token
+    if post_fee_amount == 0 {
+        return Ok(0);
+    }
+    let fee = match get_transfer_fee_config(mint)? {
+        Some(config) => config
+            .calculate_inverse_epoch_fee(Clock::get()?.epoch, post_fee_amount)
+            .ok_or(Errors::TransferFeeCalculationError)?,
+        None => 0,
+    };
+    post_fee_amount
+        .checked_add(fee)
+        .ok_or(Errors::TransferFeeCalculationError.into())
+}
+
+// Reads the TransferFeeConfig extension, SPL Token mints and Token-2022 mints without it charge nothing
+fn get_transfer_fee_config(mint: &InterfaceAccount<Mint>) -> Result<Option<TransferFeeConfig>> {
+    let mint_info = mint.to_account_info();
+    if *mint_info.owner == anchor_spl::token::ID {
+        return Ok(None);
+    }
+    let mint_data = mint_info.try_borrow_data()?;
+    let mint_state = StateWithExtensions::<MintState>::unpack(&mint_data)?;
+    Ok(mint_state.get_extension::<TransferFeeConfig>().ok().copied())
+}
@@ -0,0 +1,45 @@
+mod common;
+
+use anchor_lang::Space;
+use clmm_dex::errors::Errors;
+use clmm_dex::state::MAX_INITIALIZED_TICKS;
+use clmm_sdk::{Pool, Position, Tick};
+use common::TestEnv;
+
+#[tokio::test]
+async fn accounts_are_allocated_for_their_largest_state() {
+    let mut env = TestEnv::new().await;
+    let pool = env.pool_keys.pool;
+    assert_eq!(env.account_data(&pool).await.unwrap().len(), 8 + Pool::INIT_SPACE);
+
+    // Every position adds two ticks, the pool keeps deserializing as initialized_ticks grows
+    for bundle_index in 0..8 {
+        let tick = (bundle_index as i32 + 1) * 100;
+        let position_keys = env.position_keys(bundle_index, -tick, tick);
+        env.provide_liquidity(&position_keys, 1_000_000, false).await.unwrap();
+    }
+    assert_eq!(env.pool().await.initialized_ticks.len(), 16);
+
+    let position_keys = env.position_keys(0, -100, 100);
+    assert_eq!(env.account_data(&position_keys.position).await.unwrap().len(), 8 + Position::INIT_SPACE);
+    assert_eq!(env.account_data(&position_keys.lower_tick).await.unwrap().len(), 8 + Tick::INIT_SPACE);
+}
+
+#[tokio::test]
+async fn initialized_ticks_are_bounded() {
+    let mut env = TestEnv::new().await;
+    let mut pool = env.pool().await;
+
+    // Inserting keeps the list sorted and ignores ticks already in it
+    pool.insert_initialized_tick(-10).unwrap();
+    pool.insert_initialized_tick(10).unwrap();
+    pool.insert_initialized_tick(-10).unwrap();
+    assert_eq!(pool.initialized_ticks, vec![-10, 10]);
+
+    while pool.initialized_ticks.len() < MAX_INITIALIZED_TICKS {
+        let tick = pool.initialized_ticks.last().unwrap() + 10;
+        pool.insert_initialized_tick(tick).unwrap();
+    }
+    assert_eq!(pool.insert_initialized_tick(-20).unwrap_err(), Errors::TooManyTicks.into());
+    pool.insert_initialized_tick(10).unwrap();
+}
@@ -0,0 +1,373 @@
+// Runs the program natively inside solana-program-test with the SPL Token and Token-2022 programs it ships with. Instructions are
+// built with clmm-sdk so the tests exercise the same account orders as the clients.
+#![allow(dead_code)]
+
+use anchor_lang::prelude::*;
+#[allow(deprecated)]
+use anchor_lang::solana_program::bpf_loader_upgradeable;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::solana_program::instruction::{Instruction, InstructionError};
+use anchor_lang::solana_program::program_pack::Pack;
+#[allow(deprecated)]
+use anchor_lang::solana_program::system_instruction;
+use anchor_spl::token::spl_token;
+use anchor_spl::token_2022::spl_token_2022;
+use anchor_spl::token_2022::spl_token_2022::extension::{transfer_fee, BaseStateWithExtensions, ExtensionType, StateWithExtensions};
+use clmm_dex::errors::Errors;
+use clmm_sdk::instructions::{self, PoolKeys, PositionKeys, RangeOrderKeys};
+use clmm_sdk::{
+    decode_account, decode_observation, find_pool_address, find_program_data_address, find_tick_address, Observation,
+    ObservationEntry, Tick, PROGRAM_ID,
+};
+use solana_program_test::{processor, BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::account::Account as SolanaAccount;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::transaction::{Transaction, TransactionError};
+
+pub const TICK_SPACING: u16 = 10;
+pub const FEE: u8 = 30;
+pub const USER_TOKENS: u64 = 1_000_000_000_000;
+
+// The processor gets accounts for the duration of the call, the program's entry wants them for 'info
+fn process(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let accounts = Box::leak(accounts.to_vec().into_boxed_slice());
+    clmm_dex::entry(program_id, accounts, data)
+}
+
+pub fn program_test() -> ProgramTest {
+    ProgramTest::new("clmm_dex", PROGRAM_ID, processor!(process))
+}
+
+// A pool at price 1 over two SPL Token mints, or a Token-2022 mint A with a transfer fee. The admin is the program's upgrade authority, the config admin and
+// pause authority, and the pool authority. The payer of the context is the user, holding both tokens.
+pub struct TestEnv {
+    pub context: ProgramTestContext,
+    pub admin: Keypair,
+    pub pool_keys: PoolKeys,
+    pub user_token_a: Pubkey,
+    pub user_token_b: Pubkey,
+}
+
+impl TestEnv {
+    pub async fn new() -> Self {
+        Self::with_program_test(program_test()).await
+    }
+
+    // For tests that register other programs first
+    pub async fn with_program_test(program_test: ProgramTest) -> Self {
+        Self::start(program_test, None).await
+    }
+
+    // Token A is a Token-2022 mint charging the transfer fee, in basis points with no maximum
+    pub async fn with_transfer_fee(transfer_fee_basis_points: u16) -> Self {
+        Self::start(program_test(), Some(transfer_fee_basis_points)).await
+    }
+
+    async fn start(mut program_test: ProgramTest, transfer_fee_basis_points: Option<u16>) -> Self {
+        let admin = Keypair::new();
+        program_test.add_account(
+            admin.pubkey(),
+            SolanaAccount::new(1_000_000_000_000, 0, &solana_sdk::system_program::ID),
+        );
+        // UpgradeableLoaderState::ProgramData in bincode: variant, deployment slot, upgrade authority
+        let mut program_data = 3u32.to_le_bytes().to_vec();
+        program_data.extend_from_slice(&0u64.to_le_bytes());
+        program_data.push(1);
+        program_data.extend_from_slice(admin.pubkey().as_ref());
+        program_test.add_account(
+            find_program_data_address().0,
+            SolanaAccount {
+                lamports: 1_000_000_000,
+                data: program_data,
+                owner: bpf_loader_upgradeable::ID,
+                executable: false,
+                rent_epoch: 0,
+            },
+        );
+
+        let context = program_test.start_with_context().await;
+        let mut env = Self {
+            context,
+            admin,
+            pool_keys: PoolKeys {
+                pool: Pubkey::default(),
+                token_mint_a: Pubkey::default(),
+                token_mint_b: Pubkey::default(),
+                token_vault_a: Pubkey::default(),
+                token_vault_b: Pubkey::default(),
+                token_program_a: spl_token::ID,
+                token_program_b: spl_token::ID,
+            },
+            user_token_a: Pubkey::default(),
+            user_token_b: Pubkey::default(),
+        };
+
+        let admin = env.admin.pubkey();
+        env.process_as_admin(&[instructions::initialize_config(admin, admin, 0)]).await.unwrap();
+
+        let (token_mint_a, token_program_a) = match transfer_fee_basis_points {
+            Some(basis_points) => (env.create_transfer_fee_mint(basis_points).await, spl_token_2022::ID),
+            None => (env.create_mint().await, spl_token::ID),
+        };
+        let token_mint_b = env.create_mint().await;
+        let token_vault_a = Keypair::new();
+        let token_vault_b = Keypair::new();
+        env.pool_keys = PoolKeys {
+            pool: find_pool_address(&token_mint_a, &token_mint_b).0,
+            token_mint_a,
+            token_mint_b,
+            token_vault_a: token_vault_a.pubkey(),
+            token_vault_b: token_vault_b.pubkey(),
+            token_program_a,
+            token_program_b: spl_token::ID,
+        };
+        let initialize_pool = instructions::initialize_pool(admin, &env.pool_keys, 1 << 64, TICK_SPACING, FEE, 0, 0);
+        let admin_keypair = env.admin.insecure_clone();
+        env.process(&[initialize_pool], &[&admin_keypair, &token_vault_a, &token_vault_b]).await.unwrap();
+
+        let user = env.user();
+        env.user_token_a = env.create_token_account(&token_mint_a, &user).await;
+        env.user_token_b = env.create_token_account(&token_mint_b, &user).await;
+        env.mint_to(&token_mint_a, &env.user_token_a.clone(), USER_TOKENS).await;
+        env.mint_to(&token_mint_b, &env.user_token_b.clone(), USER_TOKENS).await;
+        env.process(&[instructions::initialize_position_bundle(env.pool_keys.pool, user)], &[]).await.unwrap();
+
+        env
+    }
+
+    pub fn user(&self) -> Pubkey {
+        self.context.payer.pubkey()
+    }
+
+    // Signed by the payer and the given signers, on a fresh blockhash so identical transactions are not deduplicated
+    pub async fn process(&mut self, instructions: &[Instruction], signers: &[&Keypair]) -> std::result::Result<(), BanksClientError> {
+        let blockhash = self.context.get_new_latest_blockhash().await.unwrap();
+        let mut all_signers = vec![&self.context.payer];
+        all_signers.extend_from_slice(signers);
+        let transaction =
+            Transaction::new_signed_with_payer(instructions, Some(&self.context.payer.pubkey()), &all_signers, blockhash);
+        self.context.banks_client.process_transaction(transaction).await
+    }
+
+    pub async fn process_as_admin(&mut self, instructions: &[Instruction]) -> std::result::Result<(), BanksClientError> {
+        let admin = self.admin.insecure_clone();
+        self.process(instructions, &[&admin]).await
+    }
+
+    pub async fn account_data(&mut self, address: &Pubkey) -> Option<Vec<u8>> {
+        self.context.banks_client.get_account(*address).await.unwrap().map(|account| account.data)
+    }
+
+    pub async fn account<T: AccountDeserialize>(&mut self, address: &Pubkey) -> T {
+        let data = self.account_data(address).await.expect("account exists");
+        decode_account(&data).unwrap()
+    }
+
+    pub async fn pool(&mut self) -> clmm_sdk::Pool {
+        let pool = self.pool_keys.pool;
+        self.account(&pool).await
+    }
+
+    // Header and slots of the pool's oracle
+    pub async fn observation(&mut self) -> (Observation, Vec<ObservationEntry>) {
+        let data = self.account_data(&self.pool_keys.observation()).await.expect("observation exists");
+        decode_observation(&data).unwrap()
+    }
+
+    // Token-2022 reads SPL Token accounts as well
+    pub async fn balance(&mut self, token_account: &Pubkey) -> u64 {
+        let data = self.account_data(token_account).await.expect("token account exists");
+        StateWithExtensions::<spl_token_2022::state::Account>::unpack(&data).unwrap().base.amount
+    }
+
+    // What an instruction passes back through return data, from a simulated transaction
+    pub async fn return_data<T: AnchorDeserialize>(&mut self, instruction: Instruction) -> T {
+        let blockhash = self.context.get_new_latest_blockhash().await.unwrap();
+        let transaction =
+            Transaction::new_signed_with_payer(&[instruction], Some(&self.user()), &[&self.context.payer], blockhash);
+        let simulation = self.context.banks_client.simulate_transaction(transaction).await.unwrap();
+        simulation.result.unwrap().unwrap();
+        let return_data = simulation.simulation_details.unwrap().return_data.expect("return data");
+        T::deserialize(&mut &return_data.data[..]).unwrap()
+    }
+
+    // Moves the clock forward, the program reads unix_timestamp for rewards, the oracle and fee changes
+    pub async fn warp(&mut self, seconds: i64) {
+        let mut clock: Clock = self.context.banks_client.get_sysvar().await.unwrap();
+        clock.unix_timestamp += seconds;
+        self.context.set_sysvar(&clock);
+    }
+
+    pub async fn create_mint(&mut self) -> Pubkey {
+        let mint = Keypair::new();
+        let rent = self.context.banks_client.get_rent().await.unwrap();
+        let instructions = [
+            system_instruction::create_account(
+                &self.user(),
+                &mint.pubkey(),
+                rent.minimum_balance(spl_token::state::Mint::LEN),
+                spl_token::state::Mint::LEN as u64,
+                &spl_token::ID,
+            ),
+            spl_token::instruction::initialize_mint2(&spl_token::ID, &mint.pubkey(), &self.admin.pubkey(), None, 6).unwrap(),
+        ];
+        self.process(&instructions, &[&mint]).await.unwrap();
+        mint.pubkey()
+    }
+
+    pub async fn create_transfer_fee_mint(&mut self, transfer_fee_basis_points: u16) -> Pubkey {
+        let mint = Keypair::new();
+        let rent = self.context.banks_client.get_rent().await.unwrap();
+        let space =
+            ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(&[ExtensionType::TransferFeeConfig])
+                .unwrap();
+        let admin = self.admin.pubkey();
+        let instructions = [
+            system_instruction::create_account(
+                &self.user(),
+                &mint.pubkey(),
+                rent.minimum_balance(space),
+                space as u64,
+                &spl_token_2022::ID,
+            ),
+            transfer_fee::instruction::initialize_transfer_fee_config(
+                &spl_token_2022::ID,
+                &mint.pubkey(),
+                Some(&admin),
+                Some(&admin),
+                transfer_fee_basis_points,
+                u64::MAX,
+            )
+            .unwrap(),
+            spl_token_2022::instruction::initialize_mint2(&spl_token_2022::ID, &mint.pubkey(), &admin, None, 6).unwrap(),
+        ];
+        self.process(&instructions, &[&mint]).await.unwrap();
+        mint.pubkey()
+    }
+
+    // Under the program that owns the mint, with room for the extensions the mint requires of its accounts
+    pub async fn create_token_account(&mut self, mint: &Pubkey, owner: &Pubkey) -> Pubkey {
+        let account = Keypair::new();
+        let rent = self.context.banks_client.get_rent().await.unwrap();
+        let (token_program, mint_extensions) = self.mint_extensions(mint).await;
+        let space = ExtensionType::try_calculate_account_len::<spl_token_2022::state::Account>(
+            &ExtensionType::get_required_init_account_extensions(&mint_extensions),
+        )
+        .unwrap();
+        let instructions = [
+            system_instruction::create_account(
+                &self.user(),
+                &account.pubkey(),
+                rent.minimum_balance(space),
+                space as u64,
+                &token_program,
+            ),
+            spl_token_2022::instruction::initialize_account3(&token_program, &account.pubkey(), mint, owner).unwrap(),
+        ];
+        self.process(&instructions, &[&account]).await.unwrap();
+        account.pubkey()
+    }
+
+    pub async fn mint_to(&mut self, mint: &Pubkey, token_account: &Pubkey, amount: u64) {
+        let token_program = self.mint_extensions(mint).await.0;
+        let admin = self.admin.pubkey();
+        let instruction =
+            spl_token_2022::instruction::mint_to(&token_program, mint, token_account, &admin, &[], amount).unwrap();
+        self.process_as_admin(&[instruction]).await.unwrap();
+    }
+
+    // Owning token program of the mint and its extensions, none for SPL Token
+    async fn mint_extensions(&mut self, mint: &Pubkey) -> (Pubkey, Vec<ExtensionType>) {
+        let account = self.context.banks_client.get_account(*mint).await.unwrap().expect("mint exists");
+        let mint_state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&account.data).unwrap();
+        (account.owner, mint_state.get_extension_types().unwrap())
+    }
+
+    pub fn position_keys(&self, bundle_index: u16, tick_lower: i32, tick_upper: i32) -> PositionKeys {
+        PositionKeys::new(&self.pool_keys.pool, self.user(), bundle_index, tick_lower, tick_upper)
+    }
+
+    pub async fn provide_liquidity(
+        &mut self,
+        position_keys: &PositionKeys,
+        liquidity: u128,
+        range_order: bool,
+    ) -> std::result::Result<(), BanksClientError> {
+        let range_order = if range_order { Some(self.range_order_keys(position_keys).await) } else { None };
+        let instruction = instructions::provide_liquidity(
+            &self.pool_keys,
+            position_keys,
+            Some(self.user_token_a),
+            Some(self.user_token_b),
+            liquidity,
+            range_order,
+            &[],
+        );
+        self.process(&[instruction], &[]).await
+    }
+
+    // Fill records of a range order placed now, the fill tick has not filled while no position uses it
+    pub async fn range_order_keys(&mut self, position_keys: &PositionKeys) -> RangeOrderKeys {
+        let pool = self.pool().await;
+        let sells_a = pool.tick_current < position_keys.tick_lower;
+        let fill_tick = if sells_a { position_keys.tick_upper } else { position_keys.tick_lower };
+        let fills = match self.account_data(&find_tick_address(&self.pool_keys.pool, fill_tick).0).await {
+            Some(data) => {
+                let tick: Tick = decode_account(&data).unwrap();
+                if sells_a { tick.range_order_fills_up } else { tick.range_order_fills_down }
+            }
+            None => 0,
+        };
+        RangeOrderKeys::new(&self.pool_keys.pool, position_keys, pool.tick_current, fills)
+    }
+
+    pub fn remove_liquidity_instruction(&self, position_keys: &PositionKeys, remaining_accounts: &[AccountMeta]) -> Instruction {
+        instructions::remove_liquidity(
+            &self.pool_keys,
+            position_keys,
+            Some(self.user_token_a),
+            Some(self.user_token_b),
+            remaining_accounts,
+        )
+    }
+
+    // Ticks crossed by the swap are passed in crossing order
+    pub async fn swap(&mut self, amount_in: u64, a_to_b: bool, crossed_ticks: &[i32]) -> std::result::Result<(), BanksClientError> {
+        let ticks = instructions::tick_account_metas(&self.pool_keys.pool, crossed_ticks, true);
+        let instruction = instructions::swap(
+            &self.pool_keys,
+            self.user(),
+            Some(self.user_token_a),
+            Some(self.user_token_b),
+            amount_in,
+            0,
+            a_to_b,
+            &ticks,
+        );
+        self.process(&[instruction], &[]).await
+    }
+
+    // A flash loan to the user with its repayment, to send in one transaction
+    pub fn flash_loan_instructions(&self, amount_a: u64, amount_b: u64) -> [Instruction; 2] {
+        let (user, user_token_a, user_token_b) = (self.user(), self.user_token_a, self.user_token_b);
+        [
+            instructions::flash_loan(&self.pool_keys, user, user_token_a, user_token_b, amount_a, amount_b, &[]),
+            instructions::flash_repay(&self.pool_keys, user, user_token_a, user_token_b, amount_a, amount_b, &[]),
+        ]
+    }
+
+    pub async fn set_pause_flags(&mut self, pause_flags: u8) {
+        let instruction = instructions::set_pool_pause_flags(self.pool_keys.pool, self.admin.pubkey(), pause_flags);
+        self.process_as_admin(&[instruction]).await.unwrap();
+    }
+}
+
+// The transaction failed with the program error
+pub fn assert_error(result: std::result::Result<(), BanksClientError>, error: Errors) {
+    match result {
+        Err(BanksClientError::TransactionError(TransactionError::InstructionError(_, InstructionError::Custom(code)))) => {
+            assert_eq!(code, u32::from(error), "expected {error:?}")
+        }
+        other => panic!("expected {error:?}, got {other:?}"),
+    }
+}
@@ -0,0 +1,47 @@
+mod common;
+
+use clmm_dex::errors::Errors;
+use clmm_sdk::{instructions, DynamicFeeParams};
+use common::{assert_error, TestEnv};
+use solana_sdk::signature::Signer;
+
+fn dynamic_fee_params(max_fee: u32) -> DynamicFeeParams {
+    DynamicFeeParams {
+        enabled: true,
+        filter_period: 30,
+        decay_period: 600,
+        reduction_factor: 5_000,
+        variable_fee_control: 40_000,
+        max_volatility_accumulator: 350_000,
+        max_fee,
+    }
+}
+
+// The test config has no fee change delay, so proposals can be applied right away
+#[tokio::test]
+async fn proposals_are_checked_against_the_pending_change_on_the_other_side() {
+    let mut env = TestEnv::new().await;
+    let pool = env.pool_keys.pool;
+    let admin = env.admin.pubkey();
+    env.process_as_admin(&[instructions::set_dynamic_fee(pool, admin, dynamic_fee_params(100))]).await.unwrap();
+    env.process(&[instructions::apply_fee_change(pool)], &[]).await.unwrap();
+
+    // A fee above the max_fee in effect is rejected up front instead of blocking apply_fee_change
+    let propose_fee_change = instructions::propose_fee_change(pool, admin, 200);
+    assert_error(env.process_as_admin(&[propose_fee_change]).await, Errors::InvalidFeeRate);
+    env.process_as_admin(&[instructions::propose_fee_change(pool, admin, 80)]).await.unwrap();
+
+    // Dynamic fee parameters have to fit the pending fee as well as the current one
+    let set_dynamic_fee = instructions::set_dynamic_fee(pool, admin, dynamic_fee_params(50));
+    assert_error(env.process_as_admin(&[set_dynamic_fee]).await, Errors::InvalidFeeRate);
+    env.process_as_admin(&[instructions::set_dynamic_fee(pool, admin, dynamic_fee_params(90))]).await.unwrap();
+
+    // And a later fee proposal has to fit the pending dynamic fee
+    let propose_fee_change = instructions::propose_fee_change(pool, admin, 95);
+    assert_error(env.process_as_admin(&[propose_fee_change]).await, Errors::InvalidFeeRate);
+
+    env.process(&[instructions::apply_fee_change(pool)], &[]).await.unwrap();
+    let pool_state = env.pool().await;
+    assert_eq!((pool_state.fee, pool_state.dynamic_fee.max_fee), (80, 90));
+    assert_eq!((pool_state.pending_fee_activation, pool_state.pending_dynamic_fee_activation), (0, 0));
+}
@@ -0,0 +1,48 @@
+mod common;
+
+use anchor_lang::AccountSerialize;
+use clmm_dex::errors::Errors;
+use clmm_sdk::{decode_pool, instructions};
+use common::{assert_error, TestEnv};
+
+const LIQUIDITY: u128 = 100_000_000_000;
+
+#[tokio::test]
+async fn loans_need_in_range_liquidity_to_pay_their_fee_to() {
+    let mut env = TestEnv::new().await;
+    let flash_loan = env.flash_loan_instructions(1_000_000, 0);
+    assert_error(env.process(&flash_loan, &[]).await, Errors::InsufficientLiquidity);
+
+    let position_keys = env.position_keys(0, -1_000, 1_000);
+    env.provide_liquidity(&position_keys, LIQUIDITY, false).await.unwrap();
+    let balance = env.balance(&env.user_token_a.clone()).await;
+    env.process(&flash_loan, &[]).await.unwrap();
+
+    // The fee is rounded up and credited to the position's liquidity
+    let pool = env.pool().await;
+    let fee = env.balance(&env.user_token_a.clone()).await.abs_diff(balance);
+    assert!(fee > 0);
+    assert_eq!(pool.fee_growth_global_a, ((fee as u128) << 64) / LIQUIDITY);
+    assert_eq!((pool.flash_loan_amount_a, pool.flash_loan_fee_a), (0, 0));
+}
+
+#[tokio::test]
+async fn repayments_are_rejected_while_a_flash_swap_holds_the_lock() {
+    let mut env = TestEnv::new().await;
+    let position_keys = env.position_keys(0, -1_000, 1_000);
+    env.provide_liquidity(&position_keys, LIQUIDITY, false).await.unwrap();
+
+    // The pool as a flash swap callback sees it, locked with a loan outstanding
+    let address = env.pool_keys.pool;
+    let mut account = env.context.banks_client.get_account(address).await.unwrap().unwrap();
+    let mut pool = decode_pool(&account.data).unwrap();
+    (pool.locked, pool.flash_loan_amount_a) = (true, 1_000_000);
+    let mut data = Vec::new();
+    pool.try_serialize(&mut data).unwrap();
+    account.data[..data.len()].copy_from_slice(&data);
+    env.context.set_account(&address, &account.into());
+
+    let (user, user_token_a, user_token_b) = (env.user(), env.user_token_a, env.user_token_b);
+    let flash_repay = instructions::flash_repay(&env.pool_keys, user, user_token_a, user_token_b, 1_000_000, 0, &[]);
+    assert_error(env.process(&[flash_repay], &[]).await, Errors::PoolLocked);
+}
@@ -0,0 +1,105 @@
+mod common;
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::program::invoke;
+use anchor_spl::token::spl_token;
+use clmm_dex::errors::Errors;
+use clmm_sdk::instructions;
+use common::{assert_error, program_test, TestEnv};
+use solana_program_test::processor;
+
+const LIQUIDITY: u128 = 100_000_000_000;
+const LOAN: u64 = 10_000_000;
+const SWAP: u64 = 1_000_000;
+
+// Receiver modes, the first byte of the flash swap data
+const PAY: u8 = 0;
+const REPAY_LOAN: u8 = 1;
+
+fn receiver_id() -> Pubkey {
+    Pubkey::new_from_array([7; 32])
+}
+
+// flash_swap_callback(amount_owed, data) gets [user, input vault, input mint, input token program] and the swap's
+// remaining_accounts. PAY transfers amount_owed from the user's token account that follows. REPAY_LOAN instead
+// settles the pool's flash loan with the flash_repay accounts that follow, hoping the repayment passes for payment.
+fn receiver(_program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let amount_owed = u64::from_le_bytes(data[8..16].try_into().unwrap());
+    let mode = data[20];
+    let (user, vault_in) = (&accounts[0], &accounts[1]);
+    let instruction = match mode {
+        PAY => {
+            let user_token_in = &accounts[4];
+            spl_token::instruction::transfer(&spl_token::ID, user_token_in.key, vault_in.key, user.key, &[], amount_owed)?
+        }
+        _ => {
+            let flash_repay = &accounts[4..];
+            let mut data = clmm_dex::instruction::FlashRepay::DISCRIMINATOR.to_vec();
+            (0u64, LOAN).serialize(&mut data)?;
+            Instruction {
+                program_id: clmm_dex::ID,
+                accounts: flash_repay.iter().map(meta).collect(),
+                data,
+            }
+        }
+    };
+    invoke(&instruction, accounts)
+}
+
+fn meta(account: &AccountInfo) -> AccountMeta {
+    AccountMeta {
+        pubkey: *account.key,
+        is_signer: account.is_signer,
+        is_writable: account.is_writable,
+    }
+}
+
+async fn env_with_receiver() -> TestEnv {
+    let mut program_test = program_test();
+    program_test.add_program("receiver", receiver_id(), processor!(receiver));
+    let mut env = TestEnv::with_program_test(program_test).await;
+    let position_keys = env.position_keys(0, -1_000, 1_000);
+    env.provide_liquidity(&position_keys, LIQUIDITY, false).await.unwrap();
+    env
+}
+
+// Swaps B for A, paid for by the receiver
+fn flash_swap(env: &TestEnv, mode: u8, remaining_accounts: &[AccountMeta]) -> Instruction {
+    let (user, user_token_a, user_token_b) = (env.user(), env.user_token_a, env.user_token_b);
+    let pool_keys = &env.pool_keys;
+    instructions::flash_swap(pool_keys, user, user_token_a, user_token_b, receiver_id(), SWAP, false, vec![mode], remaining_accounts)
+}
+
+#[tokio::test]
+async fn receiver_that_pays_completes_the_swap() {
+    let mut env = env_with_receiver().await;
+    let user_token_b = env.user_token_b;
+    let (before_a, before_b) = (env.balance(&env.user_token_a.clone()).await, env.balance(&user_token_b).await);
+
+    let instruction = flash_swap(&env, PAY, &[AccountMeta::new(user_token_b, false)]);
+    env.process(&[instruction], &[]).await.unwrap();
+
+    assert!(env.balance(&env.user_token_a.clone()).await > before_a);
+    assert_eq!(before_b - env.balance(&user_token_b).await, SWAP);
+    assert!(!env.pool().await.locked);
+}
+
+#[tokio::test]
+async fn flash_swaps_are_rejected_while_a_loan_is_outstanding() {
+    let mut env = env_with_receiver().await;
+    let (user, user_token_a, user_token_b) = (env.user(), env.user_token_a, env.user_token_b);
+
+    // Borrow B, have the callback repay the loan into the input vault as if it paid for the swap, then take and
+    // repay a second loan so the outer flash_repay still has one to settle
+    let [flash_loan, flash_repay] = env.flash_loan_instructions(0, LOAN);
+    let inner_repay = instructions::flash_repay(&env.pool_keys, user, user_token_a, user_token_b, 0, LOAN, &[]);
+    let attack = flash_swap(&env, REPAY_LOAN, &inner_repay.accounts);
+    let instructions = [flash_loan.clone(), attack, flash_loan, flash_repay];
+    assert_error(env.process(&instructions, &[]).await, Errors::FlashLoanInProgress);
+
+    // The failed transaction left no loan behind, so the pool flash swaps again
+    let pay = flash_swap(&env, PAY, &[AccountMeta::new(user_token_b, false)]);
+    env.process(&[pay], &[]).await.unwrap();
+}
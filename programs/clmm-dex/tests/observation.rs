@@ -0,0 +1,84 @@
+mod common;
+
+use clmm_sdk::{find_observation_address, instructions, Observation};
+use common::TestEnv;
+
+const LIQUIDITY: u128 = 100_000_000_000;
+
+#[tokio::test]
+async fn cardinality_grows_the_account_paid_by_the_caller() {
+    let mut env = TestEnv::new().await;
+    let pool = env.pool_keys.pool;
+    let user = env.user();
+    let observation_address = find_observation_address(&pool).0;
+    let position_keys = env.position_keys(0, -1_000, 1_000);
+    env.provide_liquidity(&position_keys, LIQUIDITY, false).await.unwrap();
+
+    // The pool starts with the default slots, the caller pays for the account to grow
+    let size = |cardinality| Observation::space(cardinality);
+    assert_eq!(env.account_data(&observation_address).await.unwrap().len(), size(Observation::DEFAULT_CARDINALITY));
+    let cardinality_next = Observation::DEFAULT_CARDINALITY + 2;
+    let before = env.context.banks_client.get_balance(user).await.unwrap();
+    let increase = instructions::increase_observation_cardinality(pool, user, cardinality_next);
+    env.process(&[increase], &[]).await.unwrap();
+    assert!(env.context.banks_client.get_balance(user).await.unwrap() < before - 5_000);
+    assert_eq!(env.account_data(&observation_address).await.unwrap().len(), size(cardinality_next));
+    let (observation, observations) = env.observation().await;
+    let cardinalities = ({ observation.cardinality }, { observation.cardinality_next });
+    assert_eq!(cardinalities, (Observation::DEFAULT_CARDINALITY, cardinality_next));
+    assert_eq!(observations.len(), cardinality_next as usize);
+
+    // Writes fill the current buffer one slot at a time, then grow into the new slots instead of wrapping around
+    for second in 1..=Observation::DEFAULT_CARDINALITY as i64 {
+        env.warp(1).await;
+        env.swap(1_000, false, &[]).await.unwrap();
+        let (observation, observations) = env.observation().await;
+        let (newest, first) = (observations[second as usize], observations[0]);
+        assert_eq!(observation.observation_index as i64, second);
+        assert_eq!({ newest.timestamp }, first.timestamp + second);
+    }
+    let (observation, observations) = env.observation().await;
+    assert_eq!({ observation.cardinality }, cardinality_next);
+    assert!(!observations[cardinality_next as usize - 1].initialized);
+
+    // A smaller cardinality_next leaves the buffer as is
+    env.process(&[instructions::increase_observation_cardinality(pool, user, 1)], &[]).await.unwrap();
+    let (observation, _) = env.observation().await;
+    assert_eq!({ observation.cardinality_next }, cardinality_next);
+    assert_eq!(env.account_data(&observation_address).await.unwrap().len(), size(cardinality_next));
+
+    // One call grows the account by at most 10 KiB, long histories are paid for over several calls
+    let too_large = cardinality_next + Observation::MAX_CARDINALITY_INCREASE + 1;
+    assert!(env.process(&[instructions::increase_observation_cardinality(pool, user, too_large)], &[]).await.is_err());
+    let mut cardinality_next = cardinality_next;
+    for _ in 0..3 {
+        cardinality_next += Observation::MAX_CARDINALITY_INCREASE;
+        env.process(&[instructions::increase_observation_cardinality(pool, user, cardinality_next)], &[]).await.unwrap();
+    }
+    assert_eq!(env.account_data(&observation_address).await.unwrap().len(), size(cardinality_next));
+    let (observation, observations) = env.observation().await;
+    assert_eq!({ observation.cardinality_next }, cardinality_next);
+    assert_eq!(observations.len(), cardinality_next as usize);
+}
+
+#[tokio::test]
+async fn liquidity_changes_are_observed_first() {
+    let mut env = TestEnv::new().await;
+    let position_keys = env.position_keys(0, -1_000, 1_000);
+
+    // The deposit records the 10 seconds the pool spent without liquidity
+    env.warp(10).await;
+    env.provide_liquidity(&position_keys, LIQUIDITY, false).await.unwrap();
+    let (observation, observations) = env.observation().await;
+    let entry = observations[observation.observation_index as usize];
+    assert_eq!({ entry.seconds_per_liquidity_cumulative }, 10u128 << 64);
+
+    // The withdrawal records the 10 seconds with the position's liquidity in range
+    env.warp(10).await;
+    let remove_liquidity = env.remove_liquidity_instruction(&position_keys, &[]);
+    env.process(&[remove_liquidity], &[]).await.unwrap();
+    let (observation, observations) = env.observation().await;
+    let entry = observations[observation.observation_index as usize];
+    assert_eq!({ observation.observation_index }, 2);
+    assert_eq!({ entry.seconds_per_liquidity_cumulative }, (10u128 << 64) + (10u128 << 64) / LIQUIDITY);
+}
@@ -0,0 +1,130 @@
+mod common;
+
+use anchor_spl::token::spl_token;
+use clmm_dex::errors::Errors;
+use clmm_dex::state::{PAUSE_DEPOSITS, PAUSE_FEE_COLLECTION, PAUSE_FLASH_LOANS, PAUSE_SWAPS, PAUSE_WITHDRAWALS};
+use clmm_sdk::instructions::{self, PositionKeys};
+use clmm_sdk::{find_reward_vault_address, Position};
+use common::{assert_error, TestEnv};
+use solana_sdk::signature::Signer;
+
+const LIQUIDITY: u128 = 100_000_000_000;
+const EMISSIONS_PER_SECOND: u128 = 1_000;
+
+#[tokio::test]
+async fn pool_flags_freeze_only_their_operation() {
+    let mut env = TestEnv::new().await;
+    let pool = env.pool_keys.pool;
+    let (user, user_token_a, user_token_b) = (env.user(), env.user_token_a, env.user_token_b);
+    let reward_mint = env.create_mint().await;
+    env.process_as_admin(&[instructions::initialize_reward(pool, env.admin.pubkey(), reward_mint, spl_token::ID, 0)])
+        .await
+        .unwrap();
+    let user_reward_token = env.create_token_account(&reward_mint, &user).await;
+
+    let keys = env.position_keys(0, -1_000, 1_000);
+    env.provide_liquidity(&keys, LIQUIDITY, false).await.unwrap();
+    let order_keys = env.position_keys(1, -20, -10);
+    env.provide_liquidity(&order_keys, LIQUIDITY, true).await.unwrap();
+    let order: Position = env.account(&order_keys.position).await;
+    let order_keys = PositionKeys::from_position(&pool, &order);
+
+    let collect_fees = instructions::collect_fees(&env.pool_keys, &keys, user, user_token_a, user_token_b, &[]);
+    let collect_reward =
+        instructions::collect_reward(pool, &keys, user, reward_mint, user_reward_token, spl_token::ID, 0, &[]);
+    let compound_fees = instructions::compound_fees(pool, &keys, user);
+    let remove_liquidity = env.remove_liquidity_instruction(&keys, &[]);
+    let claim_range_order = instructions::claim_range_order(&env.pool_keys, &order_keys, user_token_a, user_token_b, &[]);
+    let flash_loan = env.flash_loan_instructions(1_000, 1_000);
+
+    // Frozen swaps leave flash loans, fee collection and deposits open
+    env.set_pause_flags(PAUSE_SWAPS).await;
+    assert_error(env.swap(1_000, false, &[]).await, Errors::OperationPaused);
+    env.process(&flash_loan, &[]).await.unwrap();
+    env.process(std::slice::from_ref(&collect_fees), &[]).await.unwrap();
+    env.provide_liquidity(&env.position_keys(2, -1_000, 1_000), LIQUIDITY, false).await.unwrap();
+
+    // Deposits cover compounding fees into a position
+    env.set_pause_flags(PAUSE_DEPOSITS).await;
+    let deposit = env.provide_liquidity(&env.position_keys(3, -1_000, 1_000), LIQUIDITY, false).await;
+    assert_error(deposit, Errors::OperationPaused);
+    assert_error(env.process(&[compound_fees], &[]).await, Errors::OperationPaused);
+    env.swap(1_000, false, &[]).await.unwrap();
+
+    // Rewards are part of exiting, so fee collection leaves them open
+    env.set_pause_flags(PAUSE_FEE_COLLECTION).await;
+    assert_error(env.process(std::slice::from_ref(&collect_fees), &[]).await, Errors::OperationPaused);
+    env.process(std::slice::from_ref(&collect_reward), &[]).await.unwrap();
+    env.swap(1_000, false, &[]).await.unwrap();
+
+    env.set_pause_flags(PAUSE_FLASH_LOANS).await;
+    assert_error(env.process(&flash_loan, &[]).await, Errors::OperationPaused);
+    env.swap(1_000, false, &[]).await.unwrap();
+
+    // Withdrawals cover claiming range orders and collecting rewards
+    env.set_pause_flags(PAUSE_WITHDRAWALS).await;
+    assert_error(env.process(std::slice::from_ref(&collect_reward), &[]).await, Errors::OperationPaused);
+    assert_error(env.process(std::slice::from_ref(&remove_liquidity), &[]).await, Errors::OperationPaused);
+    assert_error(env.process(std::slice::from_ref(&claim_range_order), &[]).await, Errors::OperationPaused);
+    env.swap(1_000, false, &[]).await.unwrap();
+
+    // Clearing the flags reopens the pool
+    env.set_pause_flags(0).await;
+    env.process(&[collect_fees, collect_reward, remove_liquidity, claim_range_order], &[]).await.unwrap();
+    env.process(&flash_loan, &[]).await.unwrap();
+    assert!(env.account_data(&keys.position).await.is_none());
+    assert!(env.account_data(&order_keys.position).await.is_none());
+}
+
+#[tokio::test]
+async fn global_pause_freezes_everything_but_withdrawals() {
+    let mut env = TestEnv::new().await;
+    let pool = env.pool_keys.pool;
+    let admin = env.admin.pubkey();
+    let (user, user_token_a, user_token_b) = (env.user(), env.user_token_a, env.user_token_b);
+    let reward_mint = env.create_mint().await;
+    env.process_as_admin(&[
+        instructions::initialize_reward(pool, admin, reward_mint, spl_token::ID, 0),
+        instructions::set_reward_emissions(pool, admin, 0, EMISSIONS_PER_SECOND << 64),
+    ])
+    .await
+    .unwrap();
+    env.mint_to(&reward_mint, &find_reward_vault_address(&pool, &reward_mint).0, 1_000_000_000).await;
+    let user_reward_token = env.create_token_account(&reward_mint, &user).await;
+    let collect_reward =
+        |keys| instructions::collect_reward(pool, keys, user, reward_mint, user_reward_token, spl_token::ID, 0, &[]);
+
+    let keys = env.position_keys(0, -1_000, 1_000);
+    env.provide_liquidity(&keys, LIQUIDITY, false).await.unwrap();
+    let exit_keys = env.position_keys(1, -1_000, 1_000);
+    env.provide_liquidity(&exit_keys, LIQUIDITY, false).await.unwrap();
+    env.warp(100).await;
+
+    // Only the pause authority flips the switch
+    assert!(env.process(&[instructions::set_global_pause(user, true)], &[]).await.is_err());
+    env.process_as_admin(&[instructions::set_global_pause(admin, true)]).await.unwrap();
+
+    assert_error(env.swap(1_000, false, &[]).await, Errors::OperationPaused);
+    let deposit = env.provide_liquidity(&env.position_keys(2, -1_000, 1_000), LIQUIDITY, false).await;
+    assert_error(deposit, Errors::OperationPaused);
+    let collect_fees = instructions::collect_fees(&env.pool_keys, &keys, user, user_token_a, user_token_b, &[]);
+    assert_error(env.process(&[collect_fees], &[]).await, Errors::OperationPaused);
+    let compound_fees = instructions::compound_fees(pool, &keys, user);
+    assert_error(env.process(&[compound_fees], &[]).await, Errors::OperationPaused);
+    let flash_loan = env.flash_loan_instructions(1_000, 1_000);
+    assert_error(env.process(&flash_loan, &[]).await, Errors::OperationPaused);
+
+    // Users can still exit with the rewards they are owed, unless withdrawals are frozen on the pool itself
+    let exit = env.remove_liquidity_instruction(&exit_keys, &[]);
+    assert_error(env.process(std::slice::from_ref(&exit), &[]).await, Errors::RewardsOwed);
+    env.process(&[collect_reward(&exit_keys), exit], &[]).await.unwrap();
+    assert!(env.balance(&user_reward_token).await > 0);
+    env.set_pause_flags(PAUSE_WITHDRAWALS).await;
+    let remove_liquidity = env.remove_liquidity_instruction(&keys, &[]);
+    assert_error(env.process(std::slice::from_ref(&remove_liquidity), &[]).await, Errors::OperationPaused);
+
+    env.set_pause_flags(0).await;
+    env.process_as_admin(&[instructions::set_global_pause(admin, false)]).await.unwrap();
+    env.swap(1_000, false, &[]).await.unwrap();
+    env.process(&[collect_reward(&keys), remove_liquidity], &[]).await.unwrap();
+}
@@ -0,0 +1,149 @@
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_spl::token::spl_token;
+use clmm_sdk::clmm_math::{compute_amount, get_amount_a_delta, get_sqrt_price_from_tick};
+use clmm_sdk::instructions::{self, PositionKeys};
+use clmm_sdk::{find_reward_vault_address, Position, RangeOrderFill};
+use common::TestEnv;
+use solana_sdk::signature::Signer;
+
+const LIQUIDITY: u128 = 100_000_000_000;
+const ORDER_LIQUIDITY: u128 = 1_000_000_000;
+const EMISSIONS_PER_SECOND: u128 = 1_000;
+
+// Token B a swap from price 1 takes to reach tick 16, with the order in [10, 20] on top of the background position
+fn amount_b_to_tick_16() -> u64 {
+    let sqrt_price = |tick| get_sqrt_price_from_tick(tick).unwrap();
+    let below_order = (LIQUIDITY * (sqrt_price(10) - sqrt_price(0))) >> 64;
+    let in_order = ((LIQUIDITY + ORDER_LIQUIDITY) * (sqrt_price(16) - sqrt_price(10))) >> 64;
+    (below_order + in_order) as u64
+}
+
+// Token A taking the price down to tick 5 over the background position alone, with room for the swap fee
+fn amount_a_to_tick_5(sqrt_price: u128) -> u64 {
+    let amount = get_amount_a_delta(get_sqrt_price_from_tick(5).unwrap(), sqrt_price, LIQUIDITY, true).unwrap();
+    (amount * 101 / 100) as u64
+}
+
+// Keys of a placed position, with the fill record of a range order
+async fn placed_position_keys(env: &mut TestEnv, bundle_index: u16, tick_lower: i32, tick_upper: i32) -> PositionKeys {
+    let position: Position = env.account(&env.position_keys(bundle_index, tick_lower, tick_upper).position).await;
+    PositionKeys::from_position(&env.pool_keys.pool, &position)
+}
+
+fn collect_reward(env: &TestEnv, position_keys: &PositionKeys, reward_mint: Pubkey, user_reward_token: Pubkey) -> Instruction {
+    let pool = env.pool_keys.pool;
+    instructions::collect_reward(pool, position_keys, env.user(), reward_mint, user_reward_token, spl_token::ID, 0, &[])
+}
+
+#[tokio::test]
+async fn orders_keep_what_they_earned_after_their_tick_fills_again() {
+    let mut env = TestEnv::new().await;
+    let pool = env.pool_keys.pool;
+    let admin = env.admin.pubkey();
+    let reward_mint = env.create_mint().await;
+    env.process_as_admin(&[
+        instructions::initialize_reward(pool, admin, reward_mint, spl_token::ID, 0),
+        instructions::set_reward_emissions(pool, admin, 0, EMISSIONS_PER_SECOND << 64),
+    ])
+    .await
+    .unwrap();
+    env.mint_to(&reward_mint, &find_reward_vault_address(&pool, &reward_mint).0, 1_000_000_000).await;
+    let user = env.user();
+    let user_reward_token = env.create_token_account(&reward_mint, &user).await;
+
+    let background_keys = env.position_keys(0, -1_000, 1_000);
+    env.provide_liquidity(&background_keys, LIQUIDITY, false).await.unwrap();
+
+    // The first order sells A in [10, 20] and earns while the price sits in its range, then fills at tick 20
+    let first_keys = env.position_keys(1, 10, 20);
+    env.provide_liquidity(&first_keys, ORDER_LIQUIDITY, true).await.unwrap();
+    env.swap(amount_b_to_tick_16(), false, &[10]).await.unwrap();
+    assert!((10..20).contains(&env.pool().await.tick_current));
+    env.warp(100).await;
+    env.swap(200_000_000, false, &[20, 10]).await.unwrap();
+    assert!(env.pool().await.tick_current >= 20);
+    let first_keys = placed_position_keys(&mut env, 1, 10, 20).await;
+
+    // A second order on the same tick archives the first fill, then the tick fills again
+    let sqrt_price = env.pool().await.sqrt_price;
+    env.swap(amount_a_to_tick_5(sqrt_price), true, &[20, 10]).await.unwrap();
+    assert!((0..10).contains(&env.pool().await.tick_current));
+    let second_keys = env.position_keys(2, 10, 20);
+    env.provide_liquidity(&second_keys, ORDER_LIQUIDITY, true).await.unwrap();
+    let second_keys = placed_position_keys(&mut env, 2, 10, 20).await;
+    assert_ne!(first_keys.range_order_fill, second_keys.range_order_fill);
+    env.swap(1_200_000_000, false, &[10, 20, 10]).await.unwrap();
+    assert!(env.pool().await.tick_current >= 20);
+
+    let first_fill: RangeOrderFill = env.account(&first_keys.range_order_fill.unwrap()).await;
+    assert!(first_fill.archived);
+    assert_eq!((first_fill.tick_index, first_fill.sells_a, first_fill.fills), (20, true, 0));
+    assert_eq!(first_fill.liquidity, ORDER_LIQUIDITY);
+    let tick_20: clmm_sdk::Tick = env.account(&first_keys.upper_tick).await;
+    assert_eq!(tick_20.range_order_fills_up, 2);
+
+    // The first order's rewards come from the range while the price sat in it, about its share of 100 seconds
+    let before = env.balance(&user_reward_token).await;
+    env.process(&[collect_reward(&env, &first_keys, reward_mint, user_reward_token)], &[]).await.unwrap();
+    let rewards = env.balance(&user_reward_token).await - before;
+    let share = 100 * EMISSIONS_PER_SECOND * ORDER_LIQUIDITY / (LIQUIDITY + ORDER_LIQUIDITY);
+    assert!((share as u64 - 1..=share as u64 + 10).contains(&rewards), "{rewards} {share}");
+
+    // Claiming pays the principal at the fill price and the fees up to the first fill, from the archived record
+    let position: Position = env.account(&first_keys.position).await;
+    let fees_b = ((first_fill.fee_growth_inside_b - position.fee_growth_inside_b) * ORDER_LIQUIDITY) >> 64;
+    assert!(fees_b > 0);
+    let principal = compute_amount(ORDER_LIQUIDITY, 20, 10, get_sqrt_price_from_tick(20).unwrap(), false).unwrap();
+    let (before_a, before_b) = (env.balance(&env.user_token_a.clone()).await, env.balance(&env.user_token_b.clone()).await);
+    let claim = instructions::claim_range_order(&env.pool_keys, &first_keys, env.user_token_a, env.user_token_b, &[]);
+    env.process(&[claim], &[]).await.unwrap();
+    assert_eq!(env.balance(&env.user_token_a.clone()).await - before_a, principal.0 as u64);
+    assert_eq!(env.balance(&env.user_token_b.clone()).await - before_b, (principal.1 + fees_b) as u64);
+    assert!(env.account_data(&first_keys.position).await.is_none());
+    assert!(env.account_data(&first_keys.range_order_fill.unwrap()).await.is_none());
+
+    // The second order's fees are collected from the tick's snapshot, archiving it, and its record closes with it
+    let before_b = env.balance(&env.user_token_b.clone()).await;
+    let collect = instructions::collect_fees(&env.pool_keys, &second_keys, user, env.user_token_a, env.user_token_b, &[]);
+    env.process(&[collect], &[]).await.unwrap();
+    assert!(env.balance(&env.user_token_b.clone()).await > before_b);
+    let second_fill: RangeOrderFill = env.account(&second_keys.range_order_fill.unwrap()).await;
+    assert!(second_fill.archived);
+    assert_eq!(second_fill.fills, 1);
+    env.process(&[collect_reward(&env, &second_keys, reward_mint, user_reward_token)], &[]).await.unwrap();
+    let claim = instructions::claim_range_order(&env.pool_keys, &second_keys, env.user_token_a, env.user_token_b, &[]);
+    env.process(&[claim], &[]).await.unwrap();
+    assert!(env.account_data(&second_keys.range_order_fill.unwrap()).await.is_none());
+}
+
+#[tokio::test]
+async fn cancelled_orders_close_their_record() {
+    let mut env = TestEnv::new().await;
+    let background_keys = env.position_keys(0, -1_000, 1_000);
+    env.provide_liquidity(&background_keys, LIQUIDITY, false).await.unwrap();
+
+    // Two orders below the price share the record of their fill tick, the last one out closes it
+    for bundle_index in [1, 2] {
+        let keys = env.position_keys(bundle_index, -20, -10);
+        env.provide_liquidity(&keys, ORDER_LIQUIDITY, true).await.unwrap();
+    }
+    let first_keys = placed_position_keys(&mut env, 1, -20, -10).await;
+    let second_keys = placed_position_keys(&mut env, 2, -20, -10).await;
+    assert_eq!(first_keys.range_order_fill, second_keys.range_order_fill);
+    let record = first_keys.range_order_fill.unwrap();
+    let fill: RangeOrderFill = env.account(&record).await;
+    assert_eq!((fill.tick_index, fill.sells_a, fill.fills), (-20, false, 0));
+    assert_eq!(fill.liquidity, 2 * ORDER_LIQUIDITY);
+
+    let claim = instructions::claim_range_order(&env.pool_keys, &first_keys, env.user_token_a, env.user_token_b, &[]);
+    env.process(&[claim], &[]).await.unwrap();
+    let fill: RangeOrderFill = env.account(&record).await;
+    assert_eq!(fill.liquidity, ORDER_LIQUIDITY);
+    let claim = instructions::claim_range_order(&env.pool_keys, &second_keys, env.user_token_a, env.user_token_b, &[]);
+    env.process(&[claim], &[]).await.unwrap();
+    assert!(env.account_data(&record).await.is_none());
+    assert_eq!(env.pool().await.liquidity, LIQUIDITY);
+}
@@ -0,0 +1,70 @@
+mod common;
+
+use clmm_dex::errors::Errors;
+use clmm_dex::state::{PAUSE_DEPOSITS, PAUSE_WITHDRAWALS};
+use clmm_sdk::instructions;
+use clmm_sdk::{find_tick_address, Position, Tick};
+use common::{assert_error, TestEnv};
+
+const LIQUIDITY: u128 = 1_000_000_000_000;
+
+#[tokio::test]
+async fn rebalance_into_fresh_ticks() {
+    let mut env = TestEnv::new().await;
+    let pool = env.pool_keys.pool;
+    let position_keys = env.position_keys(0, -100, 100);
+    env.provide_liquidity(&position_keys, LIQUIDITY, false).await.unwrap();
+    let balances = (env.balance(&env.user_token_a.clone()).await, env.balance(&env.user_token_b.clone()).await);
+
+    // Neither tick of the new range exists yet, the authority pays for them
+    let (new_lower, new_upper) = (find_tick_address(&pool, -300).0, find_tick_address(&pool, 500).0);
+    assert!(env.account_data(&new_lower).await.is_none());
+    assert!(env.account_data(&new_upper).await.is_none());
+
+    env.warp(10).await;
+    let instruction = instructions::rebalance_position(pool, &position_keys, env.user(), -300, 500, 1, 0, true, &[]);
+    env.process(&[instruction], &[]).await.unwrap();
+
+    for (address, tick_index) in [(new_lower, -300), (new_upper, 500)] {
+        let tick: Tick = env.account(&address).await;
+        assert!(tick.initialized);
+        assert_eq!(tick.tick_index, tick_index);
+    }
+    let position: Position = env.account(&position_keys.position).await;
+    assert_eq!((position.tick_lower, position.tick_upper), (-300, 500));
+    assert!(position.liquidity > 0 && position.liquidity < LIQUIDITY);
+    let pool_state = env.pool().await;
+    assert_eq!(pool_state.liquidity, position.liquidity);
+    assert!(pool_state.initialized_ticks.contains(&-300) && pool_state.initialized_ticks.contains(&500));
+    let new_lower_tick: Tick = env.account(&new_lower).await;
+    assert_eq!(new_lower_tick.liquidity_net, position.liquidity as i128);
+
+    // The old range is empty and no tokens left the vaults
+    let old_lower_tick: Tick = env.account(&position_keys.lower_tick).await;
+    assert_eq!(old_lower_tick.liquidity_net, 0);
+    assert_eq!(env.balance(&env.user_token_a.clone()).await, balances.0);
+    assert_eq!(env.balance(&env.user_token_b.clone()).await, balances.1);
+
+    // The observation covers the 10 seconds with the liquidity in effect before the rebalance
+    let (observation, observations) = env.observation().await;
+    let entry = observations[observation.observation_index as usize];
+    assert_eq!({ entry.seconds_per_liquidity_cumulative }, (10u128 << 64) / LIQUIDITY);
+}
+
+#[tokio::test]
+async fn rebalance_rejected_while_deposits_or_withdrawals_are_paused() {
+    let mut env = TestEnv::new().await;
+    let pool = env.pool_keys.pool;
+    let position_keys = env.position_keys(0, -100, 100);
+    env.provide_liquidity(&position_keys, LIQUIDITY, false).await.unwrap();
+
+    for pause_flags in [PAUSE_WITHDRAWALS, PAUSE_DEPOSITS] {
+        env.set_pause_flags(pause_flags).await;
+        let instruction = instructions::rebalance_position(pool, &position_keys, env.user(), -200, 200, 0, 0, true, &[]);
+        assert_error(env.process(&[instruction], &[]).await, Errors::OperationPaused);
+    }
+
+    env.set_pause_flags(0).await;
+    let instruction = instructions::rebalance_position(pool, &position_keys, env.user(), -200, 200, 0, 0, true, &[]);
+    env.process(&[instruction], &[]).await.unwrap();
+}
@@ -0,0 +1,80 @@
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_spl::token::spl_token;
+use clmm_dex::errors::Errors;
+use clmm_sdk::instructions::{self, PositionKeys};
+use clmm_sdk::{find_reward_vault_address, Position};
+use common::{assert_error, TestEnv};
+use solana_sdk::signature::Signer;
+
+const LIQUIDITY: u128 = 1_000_000_000_000;
+const EMISSIONS_PER_SECOND: u128 = 1_000;
+
+// A reward emitting EMISSIONS_PER_SECOND in slot 0, with its vault holding vault_amount
+async fn initialize_reward(env: &mut TestEnv, vault_amount: u64) -> (Pubkey, Pubkey) {
+    let pool = env.pool_keys.pool;
+    let admin = env.admin.pubkey();
+    let reward_mint = env.create_mint().await;
+    env.process_as_admin(&[
+        instructions::initialize_reward(pool, admin, reward_mint, spl_token::ID, 0),
+        instructions::set_reward_emissions(pool, admin, 0, EMISSIONS_PER_SECOND << 64),
+    ])
+    .await
+    .unwrap();
+    let reward_vault = find_reward_vault_address(&pool, &reward_mint).0;
+    if vault_amount > 0 {
+        env.mint_to(&reward_mint, &reward_vault, vault_amount).await;
+    }
+    let user = env.user();
+    let user_reward_token = env.create_token_account(&reward_mint, &user).await;
+    (reward_mint, user_reward_token)
+}
+
+fn collect_reward(env: &TestEnv, position_keys: &PositionKeys, reward_mint: Pubkey, user_reward_token: Pubkey) -> Instruction {
+    let pool = env.pool_keys.pool;
+    instructions::collect_reward(pool, position_keys, env.user(), reward_mint, user_reward_token, spl_token::ID, 0, &[])
+}
+
+#[tokio::test]
+async fn rewards_accrue_and_are_collected_before_closing() {
+    let mut env = TestEnv::new().await;
+    let (reward_mint, user_reward_token) = initialize_reward(&mut env, 1_000_000_000).await;
+    let position_keys = env.position_keys(0, -100, 100);
+    env.provide_liquidity(&position_keys, LIQUIDITY, false).await.unwrap();
+
+    // The only position in range earns all emissions, less rounding
+    env.warp(100).await;
+    let remove_liquidity = env.remove_liquidity_instruction(&position_keys, &[]);
+    assert_error(env.process(std::slice::from_ref(&remove_liquidity), &[]).await, Errors::RewardsOwed);
+
+    let collect = collect_reward(&env, &position_keys, reward_mint, user_reward_token);
+    env.process(&[collect, remove_liquidity], &[]).await.unwrap();
+    let collected = env.balance(&user_reward_token).await;
+    assert!(collected <= 100 * EMISSIONS_PER_SECOND as u64 && collected >= 100 * EMISSIONS_PER_SECOND as u64 - 1, "{collected}");
+    assert!(env.account_data(&position_keys.position).await.is_none());
+}
+
+#[tokio::test]
+async fn position_owed_rewards_of_an_empty_vault_closes_with_the_vault_passed() {
+    let mut env = TestEnv::new().await;
+    let (reward_mint, user_reward_token) = initialize_reward(&mut env, 0).await;
+    let position_keys = env.position_keys(0, -100, 100);
+    env.provide_liquidity(&position_keys, LIQUIDITY, false).await.unwrap();
+    env.warp(100).await;
+
+    // Collecting from the empty vault pays nothing and leaves the rewards owed
+    let collect = collect_reward(&env, &position_keys, reward_mint, user_reward_token);
+    env.process(&[collect], &[]).await.unwrap();
+    assert_eq!(env.balance(&user_reward_token).await, 0);
+    let position: Position = env.account(&position_keys.position).await;
+    assert!(position.reward_infos[0].amount_owed > 0);
+
+    let remove_liquidity = env.remove_liquidity_instruction(&position_keys, &[]);
+    assert_error(env.process(&[remove_liquidity], &[]).await, Errors::RewardsOwed);
+    let pool = env.pool().await;
+    let remove_liquidity = env.remove_liquidity_instruction(&position_keys, &instructions::reward_vault_accounts(&pool));
+    env.process(&[remove_liquidity], &[]).await.unwrap();
+    assert!(env.account_data(&position_keys.position).await.is_none());
+}
@@ -0,0 +1,24 @@
+mod common;
+
+use common::TestEnv;
+
+const LIQUIDITY: u128 = 100_000_000_000;
+
+#[tokio::test]
+async fn fresh_pool_swaps_down_from_its_starting_price() {
+    let mut env = TestEnv::new().await;
+    assert!(env.pool().await.initialized_ticks.is_empty());
+    let position_keys = env.position_keys(0, -1_000, 1_000);
+    env.provide_liquidity(&position_keys, LIQUIDITY, false).await.unwrap();
+    assert_eq!(env.pool().await.initialized_ticks, vec![-1_000, 1_000]);
+
+    // The starting tick has no account, so neither the first swap down nor the way back up stops to cross it
+    env.swap(1_000_000, true, &[]).await.unwrap();
+    let pool = env.pool().await;
+    assert!(pool.tick_current < 0);
+    assert_eq!(pool.liquidity, LIQUIDITY);
+    env.swap(2_000_000, false, &[]).await.unwrap();
+    let pool = env.pool().await;
+    assert!(pool.tick_current >= 0);
+    assert_eq!(pool.liquidity, LIQUIDITY);
+}
@@ -0,0 +1,53 @@
+mod common;
+
+use clmm_dex::errors::Errors;
+use clmm_sdk::{instructions, SwapQuote};
+use common::{assert_error, TestEnv};
+
+const LIQUIDITY: u128 = 100_000_000_000;
+const TRANSFER_FEE_BASIS_POINTS: u16 = 100;
+
+#[tokio::test]
+async fn quotes_match_what_the_recipient_receives_after_the_transfer_fee() {
+    let mut env = TestEnv::with_transfer_fee(TRANSFER_FEE_BASIS_POINTS).await;
+    let position_keys = env.position_keys(0, -1_000, 1_000);
+    env.provide_liquidity(&position_keys, LIQUIDITY, false).await.unwrap();
+    let (user_token_a, user_token_b, vault_a) = (env.user_token_a, env.user_token_b, env.pool_keys.token_vault_a);
+
+    // Token A comes out of the vault at the booked amount, the user receives it net of the fee and the quote says so
+    let quote: SwapQuote = env.return_data(instructions::quote_swap(&env.pool_keys, 100_000_000, false, true, 0, &[])).await;
+    let (before_a, before_b, before_vault_a) =
+        (env.balance(&user_token_a).await, env.balance(&user_token_b).await, env.balance(&vault_a).await);
+    env.swap(100_000_000, false, &[]).await.unwrap();
+    let received = env.balance(&user_token_a).await - before_a;
+    assert_eq!(received, quote.amount_out);
+    assert_eq!(before_b - env.balance(&user_token_b).await, quote.amount_in);
+    let booked = before_vault_a - env.balance(&vault_a).await;
+    assert_eq!(booked - received, (booked * TRANSFER_FEE_BASIS_POINTS as u64).div_ceil(10_000));
+
+    // Token A going in is charged on its way to the vault, the quote includes it in amount_in
+    let quote: SwapQuote = env.return_data(instructions::quote_swap(&env.pool_keys, 1_000_000, true, true, 0, &[])).await;
+    let (before_a, before_b) = (env.balance(&user_token_a).await, env.balance(&user_token_b).await);
+    env.swap(1_000_000, true, &[]).await.unwrap();
+    assert_eq!(before_a - env.balance(&user_token_a).await, quote.amount_in);
+    assert_eq!(env.balance(&user_token_b).await - before_b, quote.amount_out);
+}
+
+#[tokio::test]
+async fn swap_slippage_is_checked_on_the_amount_received_after_the_transfer_fee() {
+    let mut env = TestEnv::with_transfer_fee(TRANSFER_FEE_BASIS_POINTS).await;
+    let position_keys = env.position_keys(0, -1_000, 1_000);
+    env.provide_liquidity(&position_keys, LIQUIDITY, false).await.unwrap();
+    let (user, user_token_a, user_token_b) = (env.user(), env.user_token_a, env.user_token_b);
+    let pool_keys = env.pool_keys;
+    let swap = |min_amount_out| {
+        instructions::swap(&pool_keys, user, Some(user_token_a), Some(user_token_b), 100_000_000, min_amount_out, false, &[])
+    };
+
+    // The vault books more than the user receives, asking for one more than the net amount fails
+    let quote: SwapQuote = env.return_data(instructions::quote_swap(&pool_keys, 100_000_000, false, true, 0, &[])).await;
+    assert_error(env.process(&[swap(quote.amount_out + 1)], &[]).await, Errors::SlippageExceeded);
+    let before = env.balance(&user_token_a).await;
+    env.process(&[swap(quote.amount_out)], &[]).await.unwrap();
+    assert_eq!(env.balance(&user_token_a).await - before, quote.amount_out);
+}